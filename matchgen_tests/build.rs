@@ -1,6 +1,9 @@
 //! Generate code for matchers used in tests.
 
-use matchgen::{FlatMatcher, Input, TreeMatcher};
+use matchgen::{
+    Anchor, Boundary, Canonical, FlatMatcher, Input, KeywordEnum, Order,
+    Pattern, Semantics, TreeMatcher, Unmatched,
+};
 use std::env;
 use std::error::Error;
 use std::fs::{self, File};
@@ -52,6 +55,27 @@ fn main() -> Result<(), Box<dyn Error>> {
         )?;
     writeln!(out)?;
 
+    let mut matcher = TreeMatcher::new("pub fn escaped_bytes", "u8");
+    matcher
+        .add(b"'", "1")
+        .add(b"\\", "2")
+        .add(b"\n", "3")
+        .add(b"\r", "4")
+        .add(b"\t", "5")
+        .doc("Match bytes that need to be escaped in byte literals.")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    FlatMatcher::new("pub fn escaped_bytes_flat", "u8")
+        .add(b"'", "1")
+        .add(b"\\", "2")
+        .add(b"\n", "3")
+        .add(b"\r", "4")
+        .add(b"\t", "5")
+        .doc("Match bytes that need to be escaped in byte literals (flat).")
+        .render(&mut out)?;
+    writeln!(out)?;
+
     TreeMatcher::new("pub fn slice_in_tuple", "(bool, &'static [u8])")
         .add(b"aab", "(true, &[1, 1])")
         .add(b"aa", "(false, &[1, 1])")
@@ -105,6 +129,1107 @@ fn main() -> Result<(), Box<dyn Error>> {
         .render(&mut out)?;
     writeln!(out)?;
 
+    let mut matcher = TreeMatcher::new("pub fn http_header_iter", "u8");
+    matcher
+        .add(b"content-length", "1")
+        .add(b"content-type", "2")
+        .add(b"Host", "3")
+        .doc("Match HTTP header names regardless of case.\n\nIterator version.")
+        .ignore_ascii_case(true)
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn http_header_slice".to_owned();
+    matcher
+        .doc("Match HTTP header names regardless of case.\n\nSlice version.")
+        .input_type(Input::Slice)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    FlatMatcher::new("pub fn http_header_flat", "u8")
+        .add(b"content-length", "1")
+        .add(b"content-type", "2")
+        .add(b"Host", "3")
+        .doc("Match HTTP header names regardless of case.\n\nFlat version.")
+        .ignore_ascii_case(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn keyword_exact_iter", "u8");
+    matcher
+        .add(b"if", "1")
+        .add(b"in", "2")
+        .add(b"int", "3")
+        .add(b"else", "4")
+        .doc("Match keywords exactly.\n\nIterator version.")
+        .anchor(Anchor::Exact)
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn keyword_exact_slice".to_owned();
+    matcher
+        .doc("Match keywords exactly.\n\nSlice version.")
+        .input_type(Input::Slice)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    FlatMatcher::new("pub fn keyword_exact_flat", "u8")
+        .add(b"if", "1")
+        .add(b"in", "2")
+        .add(b"int", "3")
+        .add(b"else", "4")
+        .doc("Match keywords exactly.\n\nConst flat version.")
+        .anchor(Anchor::Exact)
+        .return_index()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    for (semantics, name) in [
+        (Semantics::Shortest, "shortest"),
+        (Semantics::Priority, "priority"),
+    ] {
+        let mut matcher = TreeMatcher::new(
+            format!("pub fn {}_iter", name),
+            "(u8, &'static [u8])",
+        );
+        matcher
+            .add(b"ab", "(1, b\"ab\")")
+            .add(b"a", "(2, b\"a\")")
+            .add(b"abc", "(3, b\"abc\")")
+            .add(b"b", "(4, b\"b\")")
+            .doc(format!("Match with {:?} semantics.", semantics))
+            .semantics(semantics)
+            .input_type(Input::Iterator)
+            .render(&mut out)?;
+        writeln!(out)?;
+
+        matcher.fn_name = format!("pub fn {}_slice", name);
+        matcher.input_type(Input::Slice).render(&mut out)?;
+        writeln!(out)?;
+
+        let mut matcher = FlatMatcher::new(
+            format!("pub fn {}_flat", name),
+            "(u8, &'static [u8])",
+        );
+        matcher
+            .add(b"ab", "(1, b\"ab\")")
+            .add(b"a", "(2, b\"a\")")
+            .add(b"abc", "(3, b\"abc\")")
+            .add(b"b", "(4, b\"b\")")
+            .doc(format!("Match with {:?} semantics.", semantics))
+            .semantics(semantics)
+            .render(&mut out)?;
+        writeln!(out)?;
+    }
+
+    let mut matcher = TreeMatcher::new("pub fn unit_suffix_iter", "u64");
+    matcher
+        .add(b"B", "1")
+        .add(b"KiB", "1024")
+        .add(b"MiB", "1_048_576")
+        .add(b"s", "1000")
+        .add(b"ms", "1")
+        .doc("Match units at the end of the input.\n\nIterator version.")
+        .anchor(Anchor::Suffix)
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn unit_suffix_slice".to_owned();
+    matcher
+        .doc("Match units at the end of the input.\n\nSlice version.")
+        .input_type(Input::Slice)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = FlatMatcher::new("pub fn unit_suffix_flat", "u64");
+    matcher
+        .add(b"B", "1")
+        .add(b"KiB", "1024")
+        .add(b"MiB", "1_048_576")
+        .add(b"s", "1000")
+        .add(b"ms", "1")
+        .doc("Match units at the end of the input.\n\nFlat version.")
+        .anchor(Anchor::Suffix)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn unit_suffix_flat_index".to_owned();
+    matcher
+        .doc("Match units at the end of the input.\n\nFlat index version.")
+        .return_index()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        TreeMatcher::new("pub fn unit_suffix_shortest_slice", "u64");
+    matcher
+        .add(b"B", "1")
+        .add(b"KiB", "1024")
+        .add(b"s", "1000")
+        .add(b"ms", "1")
+        .doc("Match the shortest unit at the end of the input.")
+        .anchor(Anchor::Suffix)
+        .semantics(Semantics::Shortest)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        FlatMatcher::new("pub fn unit_suffix_shortest_flat", "u64");
+    matcher
+        .add(b"B", "1")
+        .add(b"KiB", "1024")
+        .add(b"s", "1000")
+        .add(b"ms", "1")
+        .doc("Match the shortest unit at the end of the input.")
+        .anchor(Anchor::Suffix)
+        .semantics(Semantics::Shortest)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn word_iter", "u8");
+    matcher
+        .add(b"i", "1")
+        .add(b"in", "2")
+        .add(b"int", "3")
+        .add(b"a", "4")
+        .add(b"a-b", "5")
+        .doc("Match whole words.\n\nIterator version.")
+        .boundary(Boundary::word())
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn word_slice".to_owned();
+    matcher
+        .doc("Match whole words.\n\nSlice version.")
+        .input_type(Input::Slice)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = FlatMatcher::new("pub fn word_flat", "u8");
+    matcher
+        .add(b"i", "1")
+        .add(b"in", "2")
+        .add(b"int", "3")
+        .add(b"a", "4")
+        .add(b"a-b", "5")
+        .doc("Match whole words.\n\nFlat version.")
+        .boundary(Boundary::word())
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn word_priority_iter", "u8");
+    matcher
+        .add(b"in", "1")
+        .add(b"int", "2")
+        .add(b"a", "3")
+        .add(b"a-b", "4")
+        .doc("Match whole words, preferring the first added.\n\nIterator version.")
+        .boundary(Boundary::word())
+        .semantics(Semantics::Priority)
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn word_priority_slice".to_owned();
+    matcher
+        .doc("Match whole words, preferring the first added.\n\nSlice version.")
+        .input_type(Input::Slice)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn unit_word_iter", "u64");
+    matcher
+        .add(b"s", "1000")
+        .add(b"ms", "1")
+        .doc("Match units after a number.\n\nIterator version.")
+        .anchor(Anchor::Suffix)
+        .boundary(Boundary::FollowedBy(b"0123456789 ".to_vec()))
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn unit_word_slice".to_owned();
+    matcher
+        .doc("Match units after a number.\n\nSlice version.")
+        .input_type(Input::Slice)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = FlatMatcher::new("pub fn unit_word_flat", "u64");
+    matcher
+        .add(b"s", "1000")
+        .add(b"ms", "1")
+        .doc("Match units after a number.\n\nFlat version.")
+        .anchor(Anchor::Suffix)
+        .boundary(Boundary::FollowedBy(b"0123456789 ".to_vec()))
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn word_case_slice", "u8");
+    matcher
+        .add(b"", "1")
+        .add(b"aba", "2")
+        .doc("Match words regardless of case.\n\nSlice version.")
+        .ignore_ascii_case(true)
+        .semantics(Semantics::Shortest)
+        .boundary(Boundary::NotFollowedBy(b"a".to_vec()))
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = FlatMatcher::new("pub fn word_case_flat", "u8");
+    matcher
+        .add(b"", "1")
+        .add(b"aba", "2")
+        .doc("Match words regardless of case.\n\nFlat version.")
+        .ignore_ascii_case(true)
+        .semantics(Semantics::Shortest)
+        .boundary(Boundary::NotFollowedBy(b"a".to_vec()))
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn word_empty_iter", "u8");
+    matcher
+        .add(b"", "1")
+        .add(b"bb", "2")
+        .doc(
+            "Match whole words, including the empty word.\n\nIterator version.",
+        )
+        .boundary(Boundary::word())
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn word_empty_chars".to_owned();
+    matcher
+        .doc("Match whole words, including the empty word.\n\nChars version.")
+        .input_type(Input::Chars)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn all_iter", "u8");
+    matcher
+        .add(b"a", "1")
+        .add(b"ab", "2")
+        .add(b"abc", "3")
+        .add(b"b", "4")
+        .doc("Visit all matching prefixes.\n\nIterator version.")
+        .semantics(Semantics::All)
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn all_slice".to_owned();
+    matcher
+        .doc("Visit all matching prefixes.\n\nSlice version.")
+        .input_type(Input::Slice)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = FlatMatcher::new("pub fn all_flat", "u8");
+    matcher
+        .add(b"a", "1")
+        .add(b"ab", "2")
+        .add(b"abc", "3")
+        .add(b"b", "4")
+        .doc("Visit all matching prefixes.\n\nFlat version.")
+        .semantics(Semantics::All)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn all_unit_iter", "u64");
+    matcher
+        .add(b"s", "1000")
+        .add(b"ms", "1")
+        .add(b"ams", "2")
+        .doc("Visit all units after a number.\n\nIterator version.")
+        .semantics(Semantics::All)
+        .anchor(Anchor::Suffix)
+        .boundary(Boundary::FollowedBy(b"0123456789 ".to_vec()))
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn all_unit_slice".to_owned();
+    matcher
+        .doc("Visit all units after a number.\n\nSlice version.")
+        .input_type(Input::Slice)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = FlatMatcher::new("pub fn all_unit_flat", "u64");
+    matcher
+        .add(b"s", "1000")
+        .add(b"ms", "1")
+        .add(b"ams", "2")
+        .doc("Visit all units after a number.\n\nFlat version.")
+        .semantics(Semantics::All)
+        .anchor(Anchor::Suffix)
+        .boundary(Boundary::FollowedBy(b"0123456789 ".to_vec()))
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        TreeMatcher::new("pub fn find_entity_iter", "&'static str");
+    matcher
+        .add(b"&amp", r#""&""#)
+        .add(b"&amp;", r#""&""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity.\n\nIterator version.")
+        .find_fn("pub fn find_first_entity_iter")
+        .find_iter_fn("pub fn find_all_entity_iter")
+        .replace_fn("pub fn decode_entity_iter")
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn find_entity_slice".to_owned();
+    matcher
+        .doc("Match an entity.\n\nSlice version.")
+        .find_fn("pub fn find_first_entity_slice")
+        .find_iter_fn("pub fn find_all_entity_slice")
+        .replace_fn("pub fn decode_entity_slice")
+        .replace_str_fn("pub fn decode_entity_str")
+        .input_type(Input::Slice)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        FlatMatcher::new("pub fn find_entity_flat", "&'static str");
+    matcher
+        .add(b"&amp", r#""&""#)
+        .add(b"&amp;", r#""&""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity.\n\nFlat version.")
+        .find_fn("pub fn find_first_entity_flat")
+        .find_iter_fn("pub fn find_all_entity_flat")
+        .replace_fn("pub fn decode_entity_flat")
+        .replace_write_fn("pub fn write_decoded_entity_flat")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn find_entity_flat_index".to_owned();
+    matcher
+        .doc("Match an entity.\n\nFlat index version.")
+        .find_fn("pub fn find_first_entity_flat_index")
+        .find_iter_fn("pub fn find_all_entity_flat_index")
+        .replace_fn("pub fn decode_entity_flat_index")
+        .replace_write_fn("pub fn write_decoded_entity_flat_index")
+        .return_index()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn find_empty", "u8")
+        .add(b"", "0")
+        .add(b"a", "1")
+        .doc("Match an empty key.")
+        .find_iter_fn("pub fn find_all_empty")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn str_prefix", "u8");
+    matcher
+        .add(b"a", "1")
+        .add(b"ab", "2")
+        .add("×".as_bytes(), "3")
+        .add("×÷".as_bytes(), "4")
+        .add("é".as_bytes(), "5")
+        .doc("Match a prefix of a `&str`.")
+        .input_type(Input::Str)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn str_suffix".to_owned();
+    matcher
+        .doc("Match a suffix of a `&str`.")
+        .anchor(Anchor::Suffix)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn str_exact".to_owned();
+    matcher
+        .doc("Match an entire `&str`.")
+        .anchor(Anchor::Exact)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn str_word".to_owned();
+    matcher
+        .doc("Match a word at the start of a `&str`.")
+        .anchor(Anchor::Prefix)
+        .boundary(Boundary::word())
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn str_all".to_owned();
+    matcher
+        .doc("Visit all prefixes of a `&str`.")
+        .semantics(Semantics::All)
+        .boundary(Boundary::Any)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn str_chars_prefix", "u8");
+    matcher
+        .add(b"a", "1")
+        .add(b"ab", "2")
+        .add("×".as_bytes(), "3")
+        .add("×÷".as_bytes(), "4")
+        .add("é".as_bytes(), "5")
+        .doc("Match a prefix of a `&str` on `char`.")
+        .input_type(Input::StrChars)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn str_chars_prefix_index".to_owned();
+    matcher
+        .doc("Match a prefix of a `&str` on `char` and return its length.")
+        .return_index()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn str_chars_suffix_index".to_owned();
+    matcher
+        .doc("Match a suffix of a `&str` on `char` and return its start.")
+        .anchor(Anchor::Suffix)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn str_chars_suffix".to_owned();
+    matcher
+        .doc("Match a suffix of a `&str` on `char`.")
+        .return_slice()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn str_chars_exact".to_owned();
+    matcher
+        .doc("Match an entire `&str` on `char`.")
+        .anchor(Anchor::Exact)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn str_chars_word".to_owned();
+    matcher
+        .doc("Match a word at the start of a `&str` on `char`.")
+        .anchor(Anchor::Prefix)
+        .boundary(Boundary::word())
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn chars_prefix", "u8");
+    matcher
+        .add(b"a", "1")
+        .add(b"ab", "2")
+        .add("×".as_bytes(), "3")
+        .add("×÷".as_bytes(), "4")
+        .add("é".as_bytes(), "5")
+        .add("👍".as_bytes(), "6")
+        .doc("Match a prefix of an iterator over `char`.")
+        .input_type(Input::Chars)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn chars_suffix".to_owned();
+    matcher
+        .doc("Match a suffix of an iterator over `char`.")
+        .anchor(Anchor::Suffix)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn chars_exact".to_owned();
+    matcher
+        .doc("Match an entire iterator over `char`.")
+        .anchor(Anchor::Exact)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn chars_word".to_owned();
+    matcher
+        .doc("Match a word at the start of an iterator over `char`.")
+        .anchor(Anchor::Prefix)
+        .boundary(Boundary::word())
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn chars_all".to_owned();
+    matcher
+        .doc("Visit all prefixes of an iterator over `char`.")
+        .semantics(Semantics::All)
+        .boundary(Boundary::Any)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn chars_ignore_case".to_owned();
+    matcher
+        .doc("Match a prefix of an iterator over `char` ignoring ASCII case.")
+        .semantics(Semantics::Longest)
+        .ignore_ascii_case(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        TreeMatcher::new("pub fn buf_read_entity", "&'static str");
+    matcher
+        .add(b"&amp;", r#""&""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&lt", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity at the start of a reader.")
+        .input_type(Input::BufRead)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn buf_read_stub".to_owned();
+    matcher
+        .doc("Match an entity at the start of a reader, hidden from clippy.")
+        .disable_clippy(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn buf_read_word", "u8");
+    matcher
+        .add(b"if", "1")
+        .add(b"in", "2")
+        .add(b"i", "3")
+        .doc("Match a word at the start of a reader.")
+        .input_type(Input::BufRead)
+        .boundary(Boundary::word())
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        TreeMatcher::new("pub fn owned_iter_entity", "&'static str");
+    matcher
+        .add(b"&amp;", r#""&""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&lt", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity at the start of an iterator over owned bytes.")
+        .input_type(Input::OwnedIterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn owned_iter_stub".to_owned();
+    matcher
+        .doc("Match an entity at the start of an iterator, hidden from clippy.")
+        .disable_clippy(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn owned_iter_word", "u8")
+        .add(b"if", "1")
+        .add(b"in", "2")
+        .add(b"i", "3")
+        .doc("Match a word at the start of an iterator, ignoring ASCII case.")
+        .input_type(Input::OwnedIterator)
+        .boundary(Boundary::word())
+        .ignore_ascii_case(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn owned_iter_empty", "u8")
+        .add(b"", "1")
+        .doc("Match the empty key without reading from an iterator.")
+        .input_type(Input::OwnedIterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        TreeMatcher::new("pub fn in_place_entity", "&'static str");
+    matcher
+        .add(b"&amp;", r#""&""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&lt", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity at the start of a slice and advance past it.")
+        .input_type(Input::InPlace)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn in_place_stub".to_owned();
+    matcher
+        .doc("Match an entity and advance past it, hidden from clippy.")
+        .disable_clippy(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn cursor_entity".to_owned();
+    matcher
+        .doc("Match an entity at a position in a slice and advance past it.")
+        .disable_clippy(false)
+        .input_type(Input::Cursor)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn cursor_stub".to_owned();
+    matcher
+        .doc("Match an entity at a position and advance, hidden from clippy.")
+        .disable_clippy(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn cursor_word", "u8")
+        .add(b"if", "1")
+        .add(b"in", "2")
+        .add(b"i", "3")
+        .doc("Match a word at a position in a slice and advance past it.")
+        .input_type(Input::Cursor)
+        .boundary(Boundary::word())
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    // A `const fn` with `&mut` parameters needs Rust 1.83.
+    writeln!(out, "#[rustversion::since(1.83)]")?;
+    TreeMatcher::new("pub fn cursor_index", "&'static str")
+        .add(b"&amp;", r#""&""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&lt", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity at a position in a slice in a `const fn`.")
+        .input_type(Input::Cursor)
+        .return_index()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn index_entity", "&'static str");
+    matcher
+        .add(b"&amp;", r#""&""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&lt", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity at the start of a slice and return its length.")
+        .return_index()
+        .find_fn("pub fn index_find")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn index_stub".to_owned();
+    matcher.find_fn = None;
+    matcher
+        .doc("Match an entity and return its length, hidden from clippy.")
+        .disable_clippy(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn index_uncollapsed".to_owned();
+    matcher
+        .doc("Match an entity with nested single arm `match` statements.")
+        .disable_clippy(false)
+        .collapse_nested_single_arms(false)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn index_str".to_owned();
+    matcher
+        .doc("Match an entity at the start of a `&str` and return its length.")
+        .collapse_nested_single_arms(true)
+        .input_type(Input::Str)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn index_suffix".to_owned();
+    matcher
+        .doc("Match an entity at the end of a slice and return its start.")
+        .input_type(Input::Slice)
+        .anchor(Anchor::Suffix)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn index_exact".to_owned();
+    matcher
+        .doc("Check if an entire slice is an entity in a `const fn`.")
+        .anchor(Anchor::Exact)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn index_word", "u8")
+        .add(b"if", "1")
+        .add(b"in", "2")
+        .add(b"i", "3")
+        .doc("Match a word at the start of a slice and return its length.")
+        .boundary(Boundary::word())
+        .return_index()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn index_empty", "u8")
+        .add(b"", "1")
+        .doc("Match the empty key and return its length.")
+        .return_index()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    KeywordEnum::new("pub enum Keyword")
+        .add(b"if", "If")
+        .add(b"in", "In")
+        .add(b"else", "Else")
+        .add(b"\"", "Quote")
+        .doc("A keyword.")
+        .boundary(Boundary::word())
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    KeywordEnum::new("pub enum Marker")
+        .add(b"\xff", "High")
+        .add(b"BOM", "Bom")
+        .doc("A marker that isn’t always UTF-8.")
+        .ignore_ascii_case(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    KeywordEnum::new("pub enum LineEnd")
+        .add(b"\n", "Lf")
+        .add(b"\r\n", "CrLf")
+        .add(b"a\nb", "Split")
+        .doc("A key with control characters.")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn reverse_entity", "&'static str")
+        .add(b"&amp;", r#""&""#)
+        .add(b"&AMP;", r#""&""#)
+        .add(b"&lt", r#""<""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity at the start of a slice.")
+        .reverse_fn("pub fn reverse_entity_encode")
+        .canonical(Canonical::Longest)
+        .tables("pub const REVERSE_ENTITY_")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    FlatMatcher::new("pub fn reverse_flat", "u32")
+        .add(b"LT", "u32::from(b'<')")
+        .add(b"lt", "u32::from(b'<')")
+        .add(b"less", "u32::from(b'<')")
+        .add(b"gt", "0x3e")
+        .add(b"GT", "0x3e")
+        .doc("Match an entity name at the start of a slice, ignoring case.")
+        .ignore_ascii_case(true)
+        .reverse_fn("pub fn reverse_flat_encode")
+        .canonical(Canonical::Last)
+        .value_pattern("u32::from(b'<')", "0x3c")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn tables_empty", "u8")
+        .doc("Match nothing.")
+        .tables("pub const EMPTY_")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn indexed_entity", "&'static str");
+    matcher
+        .add(b"&amp;", r#""&""#)
+        .add(b"&AMP;", r#""&""#)
+        .add(b"&lt", r#""<""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity at the start of a slice.")
+        .index_fn("pub fn indexed_entity_index")
+        .values_static("pub static INDEXED_ENTITY_VALUES")
+        .find_fn("pub fn indexed_entity_find")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn indexed_exact".to_owned();
+    matcher.find_fn = None;
+    matcher
+        .doc("Match an entire slice.")
+        .index_fn("pub fn indexed_exact_index")
+        .values_static("static INDEXED_EXACT_VALUES")
+        .anchor(Anchor::Exact)
+        .return_index()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn indexed_str".to_owned();
+    matcher
+        .doc("Match an entity at the start of a `&str`, ignoring case.")
+        .index_fn("pub fn indexed_str_index")
+        .values_static("static INDEXED_STR_VALUES")
+        .anchor(Anchor::Prefix)
+        .input_type(Input::Str)
+        .ignore_ascii_case(true)
+        .return_slice()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    FlatMatcher::new("pub fn hoisted_all_flat", "&'static str")
+        .add(b"a", r#""x""#)
+        .add(b"ab", r#""x""#)
+        .add(b"b", r#""y""#)
+        .doc("Visit all prefixes of a slice.")
+        .semantics(Semantics::All)
+        .hoist_values(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn state_entity", "&'static str")
+        .add(b"&amp;", r#""&""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&lt", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity at the start of a slice.")
+        .state_machine_struct("pub struct EntityState")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn state_word", "u8")
+        .add(b"if", "1")
+        .add(b"in", "2")
+        .add(b"i", "3")
+        .doc("Match a word at the start of a slice, ignoring ASCII case.")
+        .boundary(Boundary::word())
+        .ignore_ascii_case(true)
+        .state_machine_struct("pub struct WordState")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn incomplete_entity", "&'static str")
+        .add(b"&amp;", r#""&""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&lt", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity, or report that the input is incomplete.")
+        .incomplete_enum("pub enum EntityMatch")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    TreeMatcher::new("pub fn incomplete_word", "u8")
+        .add(b"if", "1")
+        .add(b"in", "2")
+        .add(b"i", "3")
+        .doc("Match a word, or report that the input is incomplete.")
+        .boundary(Boundary::word())
+        .ignore_ascii_case(true)
+        .disable_clippy(true)
+        .incomplete_enum("pub enum WordMatch")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    FlatMatcher::new("pub fn incomplete_flat", "&'static str")
+        .add(b"&amp;", r#""&""#)
+        .add(b"&lt;", r#""<""#)
+        .add(b"&lt", r#""<""#)
+        .add(b"&gt;", r#"">""#)
+        .doc("Match an entity, or report that the input is incomplete.")
+        .return_index()
+        .incomplete_enum("pub enum FlatEntityMatch")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    FlatMatcher::new("pub fn incomplete_flat_stub", "&'static str")
+        .add(b"&amp;", r#""&""#)
+        .doc("Match an entity, or report that the input is incomplete.")
+        .disable_clippy(true)
+        .incomplete_enum("pub enum FlatStubMatch")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let utf16 = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+    let mut matcher =
+        TreeMatcher::<u16>::new_generic("pub fn utf16_tree", "u8");
+    matcher
+        .add(&utf16("a"), "1")
+        .add(&utf16("ab"), "2")
+        .add(&utf16("Ω"), "3")
+        .add(&utf16("😀"), "4")
+        .doc("Match a prefix of UTF-16.")
+        .reverse_fn("pub fn utf16_tree_reverse")
+        .tables("pub const UTF16_")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn utf16_tree_iter".to_owned();
+    matcher.reverse_fn = None;
+    matcher.tables = None;
+    matcher
+        .doc("Match a prefix of an iterator over UTF-16.")
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn utf16_tree_suffix".to_owned();
+    matcher
+        .doc("Match a suffix of UTF-16.")
+        .input_type(Input::Slice)
+        .anchor(Anchor::Suffix)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        FlatMatcher::<u16>::new_generic("pub fn utf16_flat", "u8");
+    matcher
+        .add(&utf16("a"), "1")
+        .add(&utf16("ab"), "2")
+        .add(&utf16("Ω"), "3")
+        .add(&utf16("😀"), "4")
+        .doc("Match a prefix of UTF-16.")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn utf16_flat_index".to_owned();
+    matcher
+        .doc("Match a prefix of UTF-16 and return the index after it.")
+        .return_index()
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let utf32 = |s: &str| s.chars().map(u32::from).collect::<Vec<u32>>();
+    let mut matcher =
+        TreeMatcher::<u32>::new_generic("pub fn utf32_word", "u8");
+    matcher
+        .add(&utf32("a"), "1")
+        .add(&utf32("ab"), "2")
+        .add(&utf32("Ω"), "3")
+        .add(&utf32("😀"), "4")
+        .doc("Match a word at the start of UTF-32, ignoring ASCII case.")
+        .boundary(Boundary::word())
+        .ignore_ascii_case(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        FlatMatcher::<u32>::new_generic("pub fn utf32_exact", "u8");
+    matcher
+        .add(&utf32("a"), "1")
+        .add(&utf32("ab"), "2")
+        .add(&utf32("Ω"), "3")
+        .add(&utf32("😀"), "4")
+        .doc("Match an entire slice of UTF-32.")
+        .anchor(Anchor::Exact)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let pattern = |patterns: &[&str]| {
+        patterns
+            .iter()
+            .map(|&p| Pattern::from(p))
+            .collect::<Vec<_>>()
+    };
+    let mut matcher =
+        TreeMatcher::<Pattern>::new_generic("pub fn pattern_tree", "Rule");
+    matcher
+        .element_type("Tok")
+        .order(Order::Insertion)
+        .add(&pattern(&["Tok::Ident", "Tok::LParen"]), "Rule::Call")
+        .add(
+            &pattern(&["Tok::Ident", "Tok::LParen", "Tok::RParen"]),
+            "Rule::EmptyCall",
+        )
+        .add(&pattern(&["Tok::Ident"]), "Rule::Name")
+        .add(
+            &pattern(&["Tok::LParen", "Tok::Num(0)", "Tok::RParen"]),
+            "Rule::Zero",
+        )
+        .add(&pattern(&["Tok::Num(_)"]), "Rule::Num")
+        .doc("Match a prefix of a slice of tokens.")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn pattern_iter".to_owned();
+    matcher
+        .doc("Match a prefix of an iterator over tokens.")
+        .input_type(Input::Iterator)
+        .state_machine_struct("pub struct TokState")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn pattern_owned".to_owned();
+    matcher.state_machine_struct = None;
+    matcher
+        .doc("Match a prefix of an iterator over owned tokens.")
+        .input_type(Input::OwnedIterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        FlatMatcher::<Pattern>::new_generic("pub fn pattern_flat", "Rule");
+    matcher
+        .element_type("Tok")
+        .order(Order::Insertion)
+        .add(&pattern(&["Tok::Ident", "Tok::LParen"]), "Rule::Call")
+        .add(
+            &pattern(&["Tok::Ident", "Tok::LParen", "Tok::RParen"]),
+            "Rule::EmptyCall",
+        )
+        .add(&pattern(&["Tok::Ident"]), "Rule::Name")
+        .add(
+            &pattern(&["Tok::LParen", "Tok::Num(0)", "Tok::RParen"]),
+            "Rule::Zero",
+        )
+        .add(&pattern(&["Tok::Num(_)"]), "Rule::Num")
+        .doc("Match a prefix of a slice of tokens.")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn pattern_exact".to_owned();
+    matcher
+        .doc("Match an entire slice of tokens.")
+        .anchor(Anchor::Exact)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher =
+        TreeMatcher::<Pattern>::new_generic("pub fn pattern_literal", "u8");
+    matcher
+        .element_type("Tok")
+        .add(&pattern(&["Tok::Num(0)"]), "0")
+        .add(&pattern(&["Tok::Num(1)", "Tok::RParen"]), "1")
+        .add(&pattern(&["Tok::Ident"]), "2")
+        .doc("Match patterns that only differ in literals.")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = TreeMatcher::new("pub fn token_iter", "Token");
+    matcher
+        .add(b"+", "Token::Plus")
+        .add(b"+=", "Token::PlusEq")
+        .add(b"let", "Token::Let")
+        .add(b" ", "Token::Space")
+        .doc("Match a token.\n\nIterator version.")
+        .tokenizer_struct("pub struct SkipLexer")
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn token_slice".to_owned();
+    matcher
+        .doc("Match a token.\n\nSlice version.")
+        .tokenizer_struct("pub struct OtherLexer")
+        .unmatched(Unmatched::Token("Token::Other".to_owned()))
+        .input_type(Input::Slice)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    let mut matcher = FlatMatcher::new("pub fn token_flat", "Token");
+    matcher
+        .add(b"+", "Token::Plus")
+        .add(b"+=", "Token::PlusEq")
+        .add(b"let", "Token::Let")
+        .add(b" ", "Token::Space")
+        .doc("Match a token.\n\nFlat version.")
+        .tokenizer_struct("pub struct FallbackLexer")
+        .unmatched(Unmatched::Fallback("lex_word".to_owned()))
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn token_flat_index".to_owned();
+    matcher
+        .doc("Match a token.\n\nFlat index version.")
+        .tokenizer_struct("pub struct OtherIndexLexer")
+        .unmatched(Unmatched::Token("Token::Other".to_owned()))
+        .return_index()
+        .render(&mut out)?;
+    writeln!(out)?;
+
     let input = fs::read("most-html-entities.json")?;
     let input: serde_json::Map<String, serde_json::Value> =
         serde_json::from_slice(&input)?;
@@ -137,6 +1262,45 @@ fn main() -> Result<(), Box<dyn Error>> {
         .doc("Decode most HTML entities.\n\nSlice collapse version.")
         .input_type(Input::Slice)
         .collapse_nested_single_arms(true)
+        .replace_str_fn("pub fn most_entity_decode_str")
+        .reverse_fn("pub fn most_entity_encode")
+        .tables("pub const MOST_ENTITY_")
+        .canonical(Canonical::Longest)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn most_entity_decode_indexed".to_owned();
+    matcher.replace_str_fn = None;
+    matcher.reverse_fn = None;
+    matcher.tables = None;
+    matcher
+        .doc("Decode most HTML entities.\n\nIndexed slice version.")
+        .index_fn("pub fn most_entity_decode_index")
+        .values_static("static MOST_ENTITY_VALUES")
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn most_entity_decode_hoisted".to_owned();
+    matcher.index_fn = None;
+    matcher
+        .doc("Decode most HTML entities.\n\nHoisted slice version.")
+        .disable_clippy(false)
+        .hoist_values(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn most_entity_decode_hoisted_iter".to_owned();
+    matcher
+        .doc("Decode most HTML entities.\n\nHoisted iterator version.")
+        .input_type(Input::Iterator)
+        .render(&mut out)?;
+    writeln!(out)?;
+
+    matcher.fn_name = "pub fn most_entity_decode_hoisted_buf_read".to_owned();
+    matcher
+        .doc("Decode most HTML entities.\n\nHoisted `BufRead` version.")
+        .disable_clippy(true)
+        .input_type(Input::BufRead)
         .render(&mut out)?;
     writeln!(out)?;
 
@@ -171,5 +1335,12 @@ fn main() -> Result<(), Box<dyn Error>> {
     matcher.render(&mut out)?;
     writeln!(out)?;
 
+    matcher.fn_name = "pub fn most_entity_decode_flat_hoisted".to_owned();
+    matcher
+        .doc("Decode most HTML entities.\n\nHoisted const flat version.")
+        .hoist_values(true)
+        .render(&mut out)?;
+    writeln!(out)?;
+
     Ok(())
 }
@@ -7,5 +7,61 @@
 // could check if they could be made `const`.
 #![allow(clippy::missing_const_for_fn)]
 
+/// Tokens matched by the generated tokenizers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Token {
+    /// `+`
+    Plus,
+    /// `+=`
+    PlusEq,
+    /// `let`
+    Let,
+    /// ` `
+    Space,
+    /// A run of ASCII alphanumerics.
+    Word,
+    /// Anything else.
+    Other,
+}
+
+/// Fallback for `FallbackLexer`: match a run of ASCII alphanumerics as a word,
+/// or a single byte as `Token::Other`.
+#[must_use]
+pub fn lex_word(slice: &[u8]) -> (Token, usize) {
+    match slice.iter().position(|b| !b.is_ascii_alphanumeric()) {
+        Some(0) => (Token::Other, 1),
+        Some(len) => (Token::Word, len),
+        None => (Token::Word, slice.len()),
+    }
+}
+
+/// Tokens matched by the generated `pattern_*` functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tok {
+    /// An identifier.
+    Ident,
+    /// `(`
+    LParen,
+    /// `)`
+    RParen,
+    /// A number.
+    Num(u32),
+}
+
+/// Rules matched by the generated `pattern_*` functions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// A function call, e.g. `f(`.
+    Call,
+    /// An empty call, e.g. `f()`.
+    EmptyCall,
+    /// An identifier.
+    Name,
+    /// The number 0 in parentheses, `(0)`.
+    Zero,
+    /// A number.
+    Num,
+}
+
 // Include generated code.
 include!(concat!(env!("OUT_DIR"), "/test-matchers.rs"));
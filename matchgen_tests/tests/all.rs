@@ -0,0 +1,78 @@
+//! Test generated `all*` functions.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    all_flat, all_iter, all_slice, all_unit_flat, all_unit_iter, all_unit_slice,
+};
+use paste::paste;
+
+macro_rules! test {
+    ($name:ident, $input:expr, $expected:expr) => {
+        paste! {
+            #[test]
+            fn [<$name _iter>]() {
+                let mut found = Vec::new();
+                all_iter($input, |value, len| found.push((value, len)));
+                check!(found == $expected);
+            }
+
+            #[test]
+            fn [<$name _slice>]() {
+                let mut found = Vec::new();
+                all_slice($input, |value, len| found.push((value, len)));
+                check!(found == $expected);
+            }
+
+            #[test]
+            fn [<$name _flat>]() {
+                let mut found = Vec::new();
+                all_flat($input, |value, len| found.push((value, len)));
+                check!(found == $expected);
+            }
+        }
+    };
+}
+
+test!(nothing, b"", []);
+test!(none, b"c", []);
+test!(a, b"a", [(1, 1)]);
+test!(ab, b"ab", [(1, 1), (2, 2)]);
+test!(abc, b"abcd", [(1, 1), (2, 2), (3, 3)]);
+test!(ac, b"ac", [(1, 1)]);
+test!(b, b"ba", [(4, 1)]);
+
+macro_rules! test_unit {
+    ($name:ident, $input:expr, $expected:expr) => {
+        paste! {
+            #[test]
+            fn [<unit_ $name _iter>]() {
+                let mut found = Vec::new();
+                all_unit_iter($input, |value, len| found.push((value, len)));
+                check!(found == $expected);
+            }
+
+            #[test]
+            fn [<unit_ $name _slice>]() {
+                let mut found = Vec::new();
+                all_unit_slice($input, |value, len| found.push((value, len)));
+                check!(found == $expected);
+            }
+
+            #[test]
+            fn [<unit_ $name _flat>]() {
+                let mut found = Vec::new();
+                all_unit_flat($input, |value, len| found.push((value, len)));
+                check!(found == $expected);
+            }
+        }
+    };
+}
+
+test_unit!(nothing, b"", []);
+test_unit!(s, b"1 s", [(1000, 1)]);
+test_unit!(ms, b"1ms", [(1, 2)]);
+test_unit!(ams, b"1ams", [(2, 3)]);
+test_unit!(ams_at_start, b"ams", [(2, 3)]);
+test_unit!(xms, b"1xms", []);
@@ -0,0 +1,170 @@
+//! Test generated `word*` and `unit_word*` functions.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    unit_word_flat, unit_word_iter, unit_word_slice, word_case_flat,
+    word_case_slice, word_empty_chars, word_empty_iter, word_flat, word_iter,
+    word_priority_iter, word_priority_slice, word_slice,
+};
+use paste::paste;
+
+macro_rules! test {
+    ($name:ident, $input:expr, $result:expr, $remainder:expr) => {
+        paste! {
+            #[test]
+            fn [<$name _iter>]() {
+                let mut iter = $input.iter();
+                check!(word_iter(&mut iter) == $result);
+                check!(iter.as_slice() == $remainder);
+            }
+
+            #[test]
+            fn [<$name _slice>]() {
+                check!(word_slice($input) == ($result, $remainder.as_slice()));
+            }
+
+            #[test]
+            fn [<$name _flat>]() {
+                check!(word_flat($input) == ($result, $remainder.as_slice()));
+            }
+        }
+    };
+}
+
+test!(nothing, b"", None, b"");
+test!(i, b"i", Some(1), b"");
+test!(i_space, b"i x", Some(1), b" x");
+test!(i_word, b"ix", None, b"ix");
+test!(in_end, b"in", Some(2), b"");
+test!(in_paren, b"in(x)", Some(2), b"(x)");
+test!(in_word, b"inx", None, b"inx");
+test!(int, b"int", Some(3), b"");
+test!(int_space, b"int x", Some(3), b" x");
+test!(int_word, b"integer", None, b"integer");
+test!(int_underscore, b"int_", None, b"int_");
+test!(a, b"a", Some(4), b"");
+test!(a_dash, b"a-", Some(4), b"-");
+test!(a_dash_x, b"a-x", Some(4), b"-x");
+test!(a_dash_b, b"a-b", Some(5), b"");
+test!(a_dash_b_word, b"a-bc", Some(4), b"-bc");
+test!(a_dash_b_dash, b"a-b-", Some(5), b"-");
+
+macro_rules! test_priority {
+    ($name:ident, $input:expr, $result:expr, $remainder:expr) => {
+        paste! {
+            #[test]
+            fn [<priority_ $name _iter>]() {
+                let mut iter = $input.iter();
+                check!(word_priority_iter(&mut iter) == $result);
+                check!(iter.as_slice() == $remainder);
+            }
+
+            #[test]
+            fn [<priority_ $name _slice>]() {
+                check!(
+                    word_priority_slice($input)
+                    == ($result, $remainder.as_slice())
+                );
+            }
+        }
+    };
+}
+
+test_priority!(in_space, b"in x", Some(1), b" x");
+test_priority!(int, b"int x", Some(2), b" x");
+test_priority!(int_word, b"intx", None, b"intx");
+test_priority!(a, b"a x", Some(3), b" x");
+test_priority!(a_dash_b, b"a-b", Some(3), b"-b");
+
+macro_rules! test_unit {
+    ($name:ident, $input:expr, $result:expr, $before:expr) => {
+        paste! {
+            #[test]
+            fn [<unit_ $name _iter>]() {
+                let mut iter = $input.iter();
+                check!(unit_word_iter(&mut iter) == $result);
+                check!(iter.as_slice() == $before);
+            }
+
+            #[test]
+            fn [<unit_ $name _slice>]() {
+                check!(
+                    unit_word_slice($input) == ($result, $before.as_slice())
+                );
+            }
+
+            #[test]
+            fn [<unit_ $name _flat>]() {
+                check!(
+                    unit_word_flat($input) == ($result, $before.as_slice())
+                );
+            }
+        }
+    };
+}
+
+test_unit!(nothing, b"", None, b"");
+test_unit!(s, b"s", Some(1000), b"");
+test_unit!(s_number, b"10s", Some(1000), b"10");
+test_unit!(s_space, b"10 s", Some(1000), b"10 ");
+test_unit!(s_word, b"10 xs", None, b"10 xs");
+test_unit!(ms, b"10ms", Some(1), b"10");
+test_unit!(ms_word, b"10 xms", None, b"10 xms");
+
+macro_rules! test_case {
+    ($name:ident, $input:expr, $result:expr, $remainder:expr) => {
+        paste! {
+            #[test]
+            fn [<case_ $name _slice>]() {
+                check!(
+                    word_case_slice($input)
+                    == ($result, $remainder.as_slice())
+                );
+            }
+
+            #[test]
+            fn [<case_ $name _flat>]() {
+                check!(
+                    word_case_flat($input) == ($result, $remainder.as_slice())
+                );
+            }
+        }
+    };
+}
+
+test_case!(empty, b".", Some(1), b".");
+test_case!(upper_boundary, b"Abaa", None, b"Abaa");
+test_case!(lower_boundary, b"abaA", None, b"abaA");
+test_case!(upper_key, b"ABA.", Some(2), b".");
+
+macro_rules! test_empty {
+    ($name:ident, $input:expr, $result:expr, $remainder:expr) => {
+        paste! {
+            #[test]
+            fn [<empty_ $name _iter>]() {
+                let input: &str = $input;
+                let remainder: &str = $remainder;
+                let mut iter = input.as_bytes().iter();
+                check!(word_empty_iter(&mut iter) == $result);
+                check!(iter.as_slice() == remainder.as_bytes());
+            }
+
+            #[test]
+            fn [<empty_ $name _chars>]() {
+                let mut iter = $input.chars();
+                check!(word_empty_chars(&mut iter) == $result);
+                check!(iter.as_str() == $remainder);
+            }
+        }
+    };
+}
+
+test_empty!(nothing, "", Some(1), "");
+test_empty!(space, " x", Some(1), " x");
+test_empty!(bb, "bb", Some(2), "");
+test_empty!(bb_space, "bb x", Some(2), " x");
+test_empty!(bb_word, "bbx", None, "bbx");
+test_empty!(b_word, "bx", None, "bx");
+test_empty!(word, "a", None, "a");
@@ -0,0 +1,132 @@
+//! Test generated `buf_read_*` functions, which accept `std::io::BufRead`.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{buf_read_entity, buf_read_stub, buf_read_word};
+use std::io::{self, BufRead, BufReader, Read};
+
+/// Find the length of the bytes to skip to the next possible match.
+fn skip(bytes: &[u8]) -> usize {
+    (1..bytes.len())
+        .find(|&i| bytes[i] == b'&')
+        .unwrap_or(bytes.len())
+}
+
+/// Decode entities in `input`, read through a buffer of `capacity` bytes.
+fn decode(input: &[u8], capacity: usize) -> io::Result<Vec<u8>> {
+    let mut reader = BufReader::with_capacity(capacity, input);
+    let mut pending = Vec::new();
+    let mut out = Vec::new();
+    loop {
+        if let Some(value) = buf_read_entity(&mut reader, &mut pending)? {
+            out.extend_from_slice(value.as_bytes());
+        } else if !pending.is_empty() {
+            let len = skip(&pending);
+            out.extend(pending.drain(..len));
+        } else {
+            let buffer = reader.fill_buf()?;
+            if buffer.is_empty() {
+                break;
+            }
+            let len = skip(buffer);
+            out.extend_from_slice(&buffer[..len]);
+            reader.consume(len);
+        }
+    }
+    Ok(out)
+}
+
+/// Read everything left in the input.
+fn rest<R: Read>(mut reader: R, pending: &[u8]) -> Vec<u8> {
+    let mut out = pending.to_vec();
+    reader.read_to_end(&mut out).unwrap();
+    out
+}
+
+#[test]
+fn entity_every_capacity() {
+    let input = b"a &amp;&lt &lt;&gt;& &gt &am;&&lt;z&lt";
+    let expected = b"a &< <>& &gt &am;&<z<";
+    for capacity in 1..=input.len() {
+        check!(decode(input, capacity).unwrap() == expected, "{}", capacity);
+    }
+}
+
+#[test]
+fn entity_consumes_only_match() {
+    for capacity in 1..8 {
+        let mut reader = BufReader::with_capacity(capacity, &b"&lt;xyz"[..]);
+        let mut pending = Vec::new();
+        check!(
+            buf_read_entity(&mut reader, &mut pending).unwrap() == Some("<")
+        );
+        check!(rest(reader, &pending) == b"xyz");
+    }
+}
+
+#[test]
+fn entity_no_match_leaves_input() {
+    for capacity in 1..8 {
+        let mut reader = BufReader::with_capacity(capacity, &b"&am;xyz"[..]);
+        let mut pending = Vec::new();
+        check!(buf_read_entity(&mut reader, &mut pending).unwrap() == None);
+        check!(pending.len() <= 5);
+        check!(rest(reader, &pending) == b"&am;xyz");
+    }
+}
+
+#[test]
+fn entity_shorter_match() {
+    for capacity in 1..8 {
+        let mut reader = BufReader::with_capacity(capacity, &b"&ltx"[..]);
+        let mut pending = Vec::new();
+        check!(
+            buf_read_entity(&mut reader, &mut pending).unwrap() == Some("<")
+        );
+        check!(rest(reader, &pending) == b"x");
+    }
+}
+
+#[test]
+fn entity_end_of_input() {
+    let mut reader = BufReader::with_capacity(1, &b"&lt"[..]);
+    let mut pending = Vec::new();
+    check!(buf_read_entity(&mut reader, &mut pending).unwrap() == Some("<"));
+    check!(pending.is_empty());
+    check!(buf_read_entity(&mut reader, &mut pending).unwrap() == None);
+}
+
+#[test]
+fn entity_dyn_reader() {
+    let mut reader: Box<dyn BufRead> = Box::new(&b"&gt;"[..]);
+    let mut pending = Vec::new();
+    check!(buf_read_entity(&mut *reader, &mut pending).unwrap() == Some(">"));
+}
+
+#[test]
+fn word() {
+    for capacity in 1..6 {
+        let mut reader = BufReader::with_capacity(capacity, &b"in x"[..]);
+        let mut pending = Vec::new();
+        check!(buf_read_word(&mut reader, &mut pending).unwrap() == Some(2));
+        check!(rest(reader, &pending) == b" x");
+
+        let mut reader = BufReader::with_capacity(capacity, &b"int"[..]);
+        let mut pending = Vec::new();
+        check!(buf_read_word(&mut reader, &mut pending).unwrap() == None);
+        check!(rest(reader, &pending) == b"int");
+
+        let mut reader = BufReader::with_capacity(capacity, &b"if"[..]);
+        let mut pending = Vec::new();
+        check!(buf_read_word(&mut reader, &mut pending).unwrap() == Some(1));
+        check!(rest(reader, &pending) == b"");
+    }
+}
+
+#[test]
+fn stub() {
+    let mut reader = BufReader::with_capacity(1, &b"&amp;"[..]);
+    let mut pending = Vec::new();
+    check!(buf_read_stub(&mut reader, &mut pending).unwrap() == Some("&"));
+}
@@ -0,0 +1,117 @@
+//! Test generated `chars_*` functions, which accept iterators over `char`.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    chars_all, chars_exact, chars_ignore_case, chars_prefix, chars_suffix,
+    chars_word,
+};
+
+#[test]
+fn prefix() {
+    let mut iter = "".chars();
+    check!(chars_prefix(&mut iter) == None);
+
+    let mut iter = "b".chars();
+    check!(chars_prefix(&mut iter) == None);
+    check!(iter.as_str() == "b");
+
+    let mut iter = "abc".chars();
+    check!(chars_prefix(&mut iter) == Some(2));
+    check!(iter.as_str() == "c");
+
+    let mut iter = "a×".chars();
+    check!(chars_prefix(&mut iter) == Some(1));
+    check!(iter.as_str() == "×");
+
+    let mut iter = "×÷×".chars();
+    check!(chars_prefix(&mut iter) == Some(4));
+    check!(iter.as_str() == "×");
+
+    let mut iter = "××".chars();
+    check!(chars_prefix(&mut iter) == Some(3));
+    check!(iter.as_str() == "×");
+
+    let mut iter = "👍👎".chars();
+    check!(chars_prefix(&mut iter) == Some(6));
+    check!(iter.as_str() == "👎");
+
+    let mut iter = "è".chars();
+    check!(chars_prefix(&mut iter) == None);
+    check!(iter.as_str() == "è");
+}
+
+#[test]
+fn suffix() {
+    let mut iter = "cab".chars();
+    check!(chars_suffix(&mut iter) == Some(2));
+    check!(iter.as_str() == "c");
+
+    let mut iter = "é×÷".chars();
+    check!(chars_suffix(&mut iter) == Some(4));
+    check!(iter.as_str() == "é");
+
+    let mut iter = "÷×".chars();
+    check!(chars_suffix(&mut iter) == Some(3));
+    check!(iter.as_str() == "÷");
+
+    let mut iter = "è".chars();
+    check!(chars_suffix(&mut iter) == None);
+    check!(iter.as_str() == "è");
+}
+
+#[test]
+fn exact() {
+    check!(chars_exact("".chars()) == None);
+    check!(chars_exact("ab".chars()) == Some(2));
+    check!(chars_exact("×÷".chars()) == Some(4));
+    check!(chars_exact("👍".chars()) == Some(6));
+    check!(chars_exact("×÷ ".chars()) == None);
+    check!(chars_exact(['é']) == Some(5));
+}
+
+#[test]
+fn word() {
+    let mut iter = "ab c".chars();
+    check!(chars_word(&mut iter) == Some(2));
+    check!(iter.as_str() == " c");
+
+    let mut iter = "abc".chars();
+    check!(chars_word(&mut iter) == None);
+    check!(iter.as_str() == "abc");
+
+    let mut iter = "a×".chars();
+    check!(chars_word(&mut iter) == Some(1));
+    check!(iter.as_str() == "×");
+
+    let mut iter = "é_".chars();
+    check!(chars_word(&mut iter) == None);
+    check!(iter.as_str() == "é_");
+}
+
+#[test]
+fn all() {
+    let mut found = Vec::new();
+    chars_all("×÷×".chars(), |value, len| found.push((value, len)));
+    check!(found == [(3, 1), (4, 2)]);
+
+    found.clear();
+    chars_all("b".chars(), |value, len| found.push((value, len)));
+    check!(found.is_empty());
+}
+
+#[test]
+fn ignore_case() {
+    let mut iter = "ABc".chars();
+    check!(chars_ignore_case(&mut iter) == Some(2));
+    check!(iter.as_str() == "c");
+
+    let mut iter = "A×".chars();
+    check!(chars_ignore_case(&mut iter) == Some(1));
+    check!(iter.as_str() == "×");
+
+    let mut iter = "É".chars();
+    check!(chars_ignore_case(&mut iter) == None);
+    check!(iter.as_str() == "É");
+}
@@ -0,0 +1,29 @@
+//! Test generated `escaped_bytes*` functions.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{escaped_bytes, escaped_bytes_flat};
+use paste::paste;
+
+macro_rules! test {
+    ($name:ident, $input:expr, $result:expr) => {
+        paste! {
+            #[test]
+            fn [<$name _tree>]() {
+                check!(escaped_bytes($input) == ($result, &$input[1..]));
+            }
+
+            #[test]
+            fn [<$name _flat>]() {
+                check!(escaped_bytes_flat($input) == ($result, &$input[1..]));
+            }
+        }
+    };
+}
+
+test!(quote, b"'a", Some(1));
+test!(backslash, b"\\a", Some(2));
+test!(newline, b"\na", Some(3));
+test!(carriage_return, b"\ra", Some(4));
+test!(tab, b"\ta", Some(5));
@@ -0,0 +1,90 @@
+//! Test generated `find_*` functions.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    find_all_empty, find_all_entity_flat, find_all_entity_flat_index,
+    find_all_entity_iter, find_all_entity_slice, find_first_entity_flat,
+    find_first_entity_flat_index, find_first_entity_iter,
+    find_first_entity_slice,
+};
+use paste::paste;
+
+macro_rules! test {
+    ($name:ident, $input:expr, $expected:expr) => {
+        paste! {
+            #[test]
+            fn [<$name _iter>]() {
+                let expected: &[(usize, usize, &str)] = &$expected;
+                check!(
+                    find_first_entity_iter($input) == expected.first().copied()
+                );
+                check!(
+                    find_all_entity_iter($input).collect::<Vec<_>>()
+                    == expected
+                );
+            }
+
+            #[test]
+            fn [<$name _slice>]() {
+                let expected: &[(usize, usize, &str)] = &$expected;
+                check!(
+                    find_first_entity_slice($input)
+                    == expected.first().copied()
+                );
+                check!(
+                    find_all_entity_slice($input).collect::<Vec<_>>()
+                    == expected
+                );
+            }
+
+            #[test]
+            fn [<$name _flat>]() {
+                let expected: &[(usize, usize, &str)] = &$expected;
+                check!(
+                    find_first_entity_flat($input) == expected.first().copied()
+                );
+                check!(
+                    find_all_entity_flat($input).collect::<Vec<_>>()
+                    == expected
+                );
+            }
+
+            #[test]
+            fn [<$name _flat_index>]() {
+                let expected: &[(usize, usize, &str)] = &$expected;
+                check!(
+                    find_first_entity_flat_index($input)
+                    == expected.first().copied()
+                );
+                check!(
+                    find_all_entity_flat_index($input).collect::<Vec<_>>()
+                    == expected
+                );
+            }
+        }
+    };
+}
+
+test!(nothing, b"", []);
+test!(no_match, b"a & b", []);
+test!(start, b"&lt;a", [(0, 4, "<")]);
+test!(middle, b"a &lt; b", [(2, 6, "<")]);
+test!(end, b"a &gt;", [(2, 6, ">")]);
+test!(longest, b"&amp;&amp", [(0, 5, "&"), (5, 9, "&")]);
+test!(adjacent, b"&lt;&gt;", [(0, 4, "<"), (4, 8, ">")]);
+test!(false_start, b"&&lt;", [(1, 5, "<")]);
+test!(
+    several,
+    b"1 &lt; 2 &amp;&amp; 3 &gt 2",
+    [(2, 6, "<"), (9, 14, "&"), (14, 19, "&")]
+);
+
+#[test]
+fn empty_key() {
+    check!(
+        find_all_empty(b"ab").collect::<Vec<_>>()
+            == [(0, 1, 1), (1, 1, 0), (2, 2, 0)]
+    );
+}
@@ -0,0 +1,53 @@
+//! Test generated matchers that output repeated values once as constants.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    hoisted_all_flat, most_entity_decode_flat_const,
+    most_entity_decode_flat_hoisted, most_entity_decode_hoisted,
+    most_entity_decode_hoisted_buf_read, most_entity_decode_hoisted_iter,
+    most_entity_decode_slice, MOST_ENTITY_KEYS,
+};
+
+/// Decode at compile time.
+const AMP: (Option<&str>, usize) = most_entity_decode_flat_hoisted(b"&amp;");
+
+#[test]
+fn most_entity() {
+    for key in MOST_ENTITY_KEYS {
+        let expected = most_entity_decode_slice(key);
+        check!(most_entity_decode_hoisted(key) == expected);
+
+        let mut iter = key.iter();
+        check!(most_entity_decode_hoisted_iter(&mut iter) == expected.0);
+        check!(iter.as_slice() == expected.1);
+
+        let mut reader = *key;
+        let mut pending = Vec::new();
+        check!(
+            most_entity_decode_hoisted_buf_read(&mut reader, &mut pending)
+                .unwrap()
+                == expected.0
+        );
+
+        check!(
+            most_entity_decode_flat_hoisted(key)
+                == most_entity_decode_flat_const(key)
+        );
+    }
+}
+
+#[test]
+fn fallback() {
+    check!(most_entity_decode_hoisted(b"&ampx") == (Some("&"), &b"x"[..]));
+    check!(most_entity_decode_hoisted(b"&am") == (None, &b"&am"[..]));
+    check!(AMP == (Some("&"), 5));
+}
+
+#[test]
+fn all() {
+    let mut found = Vec::new();
+    hoisted_all_flat(b"abc", |value, len| found.push((value, len)));
+    check!(found == [("x", 1), ("x", 2)]);
+}
@@ -0,0 +1,46 @@
+//! Test generated `http_header*` functions.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{http_header_flat, http_header_iter, http_header_slice};
+use paste::paste;
+
+macro_rules! test {
+    ($name:ident, $input:expr, $result:expr, $remainder:expr) => {
+        paste! {
+            #[test]
+            fn [<$name _iter>]() {
+                let input = $input;
+                let mut iter = input.iter();
+                check!(http_header_iter(&mut iter) == $result);
+                check!(iter.as_slice() == $remainder);
+            }
+
+            #[test]
+            fn [<$name _slice>]() {
+                check!(
+                    http_header_slice($input)
+                    == ($result, $remainder.as_slice())
+                );
+            }
+
+            #[test]
+            fn [<$name _flat>]() {
+                check!(
+                    http_header_flat($input)
+                    == ($result, $remainder.as_slice())
+                );
+            }
+        }
+    };
+}
+
+test!(nothing, b"", None, b"");
+test!(invalid, b"accept:", None, b"accept:");
+test!(lower, b"content-length: 0", Some(1), b": 0");
+test!(upper, b"CONTENT-TYPE: text/plain", Some(2), b": text/plain");
+test!(mixed, b"Content-Type: text/plain", Some(2), b": text/plain");
+test!(host_lower, b"host:", Some(3), b":");
+test!(host_mixed, b"hOsT:", Some(3), b":");
+test!(partial, b"content-", None, b"content-");
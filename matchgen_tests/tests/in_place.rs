@@ -0,0 +1,138 @@
+//! Test generated `in_place_*` and `cursor_*` functions, which advance their
+//! input past the match.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+#[rustversion::since(1.83)]
+use matchgen_tests::cursor_index;
+use matchgen_tests::{
+    cursor_entity, cursor_stub, cursor_word, in_place_entity, in_place_stub,
+};
+
+/// Match twice at compile time. A `const fn` with `&mut` parameters needs
+/// Rust 1.83.
+#[rustversion::since(1.83)]
+const fn decode_two(input: &[u8]) -> (Option<&str>, Option<&str>, usize) {
+    let mut pos = 0;
+    let first = cursor_index(input, &mut pos);
+    let second = cursor_index(input, &mut pos);
+    (first, second, pos)
+}
+
+/// Decoded at compile time.
+#[rustversion::since(1.83)]
+const LT_GT: (Option<&str>, Option<&str>, usize) = decode_two(b"&lt&gt;x");
+
+#[test]
+fn in_place() {
+    let mut input = &b"&amp;&lt;&ltx&gt"[..];
+    check!(in_place_entity(&mut input) == Some("&"));
+    check!(input == b"&lt;&ltx&gt");
+    check!(in_place_entity(&mut input) == Some("<"));
+    check!(in_place_entity(&mut input) == Some("<"));
+    check!(input == b"x&gt");
+    check!(in_place_entity(&mut input) == None);
+    check!(input == b"x&gt");
+
+    let mut input = &b""[..];
+    check!(in_place_entity(&mut input) == None);
+    check!(input == b"");
+}
+
+#[test]
+fn in_place_decode() {
+    let mut input = &b"a &amp;&lt &lt;&gt;& &gt &am;&&lt;z&lt"[..];
+    let mut out = Vec::new();
+    while let Some((&first, rest)) = input.split_first() {
+        if let Some(value) = in_place_entity(&mut input) {
+            out.extend_from_slice(value.as_bytes());
+        } else {
+            out.push(first);
+            input = rest;
+        }
+    }
+    check!(out == b"a &< <>& &gt &am;&<z<");
+}
+
+#[test]
+fn in_place_stub_matches() {
+    let mut input = &b"&gt;x"[..];
+    check!(in_place_stub(&mut input) == Some(">"));
+    check!(input == b"x");
+}
+
+#[test]
+fn cursor() {
+    let input = b"&amp;&lt;&ltx&gt";
+    let mut pos = 0;
+    check!(cursor_entity(input, &mut pos) == Some("&"));
+    check!(pos == 5);
+    check!(cursor_entity(input, &mut pos) == Some("<"));
+    check!(cursor_entity(input, &mut pos) == Some("<"));
+    check!(pos == 12);
+    check!(cursor_entity(input, &mut pos) == None);
+    check!(pos == 12);
+
+    pos = 13;
+    check!(cursor_entity(input, &mut pos) == None);
+    check!(pos == 13);
+}
+
+#[test]
+fn cursor_past_end() {
+    let mut pos = 4;
+    check!(cursor_entity(b"&lt;", &mut pos) == None);
+    check!(pos == 4);
+
+    pos = 10;
+    check!(cursor_entity(b"&lt;", &mut pos) == None);
+    check!(pos == 10);
+}
+
+#[test]
+fn cursor_stub_matches() {
+    let mut pos = 1;
+    check!(cursor_stub(b"x&gt;", &mut pos) == Some(">"));
+    check!(pos == 5);
+}
+
+#[test]
+fn cursor_boundary() {
+    let input = b"if in i.ifx";
+    let mut pos = 0;
+    check!(cursor_word(input, &mut pos) == Some(1));
+    check!(pos == 2);
+    pos = 3;
+    check!(cursor_word(input, &mut pos) == Some(2));
+    pos = 6;
+    check!(cursor_word(input, &mut pos) == Some(3));
+    check!(pos == 7);
+    pos = 8;
+    check!(cursor_word(input, &mut pos) == None);
+    check!(pos == 8);
+}
+
+#[rustversion::since(1.83)]
+#[test]
+fn cursor_const() {
+    check!(LT_GT == (Some("<"), Some(">"), 7));
+}
+
+#[rustversion::since(1.83)]
+#[test]
+fn cursor_index_matches() {
+    let input = b"&amp;&lt;&ltx&gt";
+    let mut pos = 0;
+    check!(cursor_index(input, &mut pos) == Some("&"));
+    check!(pos == 5);
+    check!(cursor_index(input, &mut pos) == Some("<"));
+    check!(cursor_index(input, &mut pos) == Some("<"));
+    check!(pos == 12);
+    check!(cursor_index(input, &mut pos) == None);
+    check!(pos == 12);
+
+    pos = 17;
+    check!(cursor_index(input, &mut pos) == None);
+    check!(pos == 17);
+}
@@ -0,0 +1,59 @@
+//! Test generated `incomplete_*` functions, which report when the input ends
+//! inside a possible key.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    incomplete_entity, incomplete_flat, incomplete_flat_stub, incomplete_word,
+    EntityMatch, FlatEntityMatch, FlatStubMatch, WordMatch,
+};
+
+#[test]
+fn entity() {
+    check!(incomplete_entity(b"") == EntityMatch::Incomplete(0));
+    check!(incomplete_entity(b"&") == EntityMatch::Incomplete(1));
+    check!(incomplete_entity(b"&am") == EntityMatch::Incomplete(3));
+    check!(incomplete_entity(b"&amp") == EntityMatch::Incomplete(4));
+    check!(incomplete_entity(b"&amp;") == EntityMatch::Matched("&", b""));
+    check!(incomplete_entity(b"&amp;x") == EntityMatch::Matched("&", b"x"));
+
+    // A shorter key matches, but a longer one might.
+    check!(incomplete_entity(b"&lt") == EntityMatch::Incomplete(3));
+    check!(incomplete_entity(b"&ltx") == EntityMatch::Matched("<", b"x"));
+    check!(incomplete_entity(b"&lt;") == EntityMatch::Matched("<", b""));
+
+    check!(incomplete_entity(b"x") == EntityMatch::NoMatch);
+    check!(incomplete_entity(b"&x") == EntityMatch::NoMatch);
+    check!(incomplete_entity(b"&amx") == EntityMatch::NoMatch);
+}
+
+#[test]
+fn word() {
+    check!(incomplete_word(b"") == WordMatch::Incomplete(0));
+    check!(incomplete_word(b"I") == WordMatch::Incomplete(1));
+
+    // The key is complete, but the boundary isn’t known yet.
+    check!(incomplete_word(b"iF") == WordMatch::Incomplete(2));
+    check!(incomplete_word(b"iF ") == WordMatch::Matched(1, b" "));
+    check!(incomplete_word(b"in(") == WordMatch::Matched(2, b"("));
+    check!(incomplete_word(b"i.") == WordMatch::Matched(3, b"."));
+    check!(incomplete_word(b"int") == WordMatch::NoMatch);
+    check!(incomplete_word(b"x") == WordMatch::NoMatch);
+}
+
+#[test]
+fn flat() {
+    check!(incomplete_flat(b"") == FlatEntityMatch::Incomplete(0));
+    check!(incomplete_flat(b"&g") == FlatEntityMatch::Incomplete(2));
+    check!(incomplete_flat(b"&lt") == FlatEntityMatch::Incomplete(3));
+    check!(incomplete_flat(b"&ltx") == FlatEntityMatch::Matched("<", b"x"));
+    check!(incomplete_flat(b"&gt;&") == FlatEntityMatch::Matched(">", b"&"));
+    check!(incomplete_flat(b"&gx") == FlatEntityMatch::NoMatch);
+}
+
+#[test]
+fn flat_stub() {
+    check!(incomplete_flat_stub(b"&am") == FlatStubMatch::Incomplete(3));
+    check!(incomplete_flat_stub(b"&amp;") == FlatStubMatch::Matched("&", b""));
+}
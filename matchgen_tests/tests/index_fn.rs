@@ -0,0 +1,62 @@
+//! Test generated matchers that store values in a static array.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    indexed_entity, indexed_entity_find, indexed_entity_index, indexed_exact,
+    indexed_exact_index, indexed_str, indexed_str_index,
+    most_entity_decode_indexed, most_entity_decode_slice,
+    INDEXED_ENTITY_VALUES, MOST_ENTITY_KEYS,
+};
+
+/// Look up an index at compile time.
+const GT: Option<u16> = indexed_exact_index(b"&gt;");
+
+#[test]
+fn entity() {
+    check!(indexed_entity(b"&lt;a") == (Some("<"), &b"a"[..]));
+    check!(indexed_entity(b"&lta") == (Some("<"), &b"a"[..]));
+    check!(indexed_entity(b"&AMP;") == (Some("&"), &b""[..]));
+    check!(indexed_entity(b"&amp") == (None, &b"&amp"[..]));
+    check!(indexed_entity(b"") == (None, &b""[..]));
+}
+
+#[test]
+fn values_once() {
+    check!(INDEXED_ENTITY_VALUES == ["&", "<", ">"]);
+    check!(indexed_entity_index(b"&amp;") == (Some(0), &b""[..]));
+    check!(indexed_entity_index(b"&AMP;") == (Some(0), &b""[..]));
+    check!(indexed_entity_index(b"&gt;") == (Some(2), &b""[..]));
+}
+
+#[test]
+fn find() {
+    check!(indexed_entity_find(b"a &gt; b") == Some((2, 6, ">")));
+}
+
+#[test]
+fn exact() {
+    check!(GT == Some(2));
+    check!(indexed_exact(b"&lt") == Some("<"));
+    check!(indexed_exact(b"&lt;a").is_none());
+    check!(indexed_exact_index(b"&AMP;") == Some(0));
+}
+
+#[test]
+fn str_input() {
+    check!(indexed_str("&LT;a") == (Some("<"), "a"));
+    check!(indexed_str("&Amp;") == (Some("&"), ""));
+    check!(indexed_str("&x") == (None, "&x"));
+    check!(indexed_str_index("&gT;") == (Some(2), ""));
+}
+
+#[test]
+fn most_entity() {
+    for key in MOST_ENTITY_KEYS {
+        check!(
+            most_entity_decode_indexed(key) == most_entity_decode_slice(key)
+        );
+    }
+    check!(most_entity_decode_indexed(b"&amp;&") == (Some("&"), &b"&"[..]));
+}
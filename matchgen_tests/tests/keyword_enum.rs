@@ -0,0 +1,65 @@
+//! Test generated enums from `KeywordEnum`.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    Keyword, LineEnd, Marker, ParseKeywordError, ParseMarkerError,
+};
+use std::convert::TryFrom;
+
+/// Look up a keyword at compile time.
+const ELSE: Option<Keyword> = Keyword::from_bytes(b"else");
+
+#[test]
+fn round_trip() {
+    for keyword in [Keyword::If, Keyword::In, Keyword::Else, Keyword::Quote] {
+        check!(Keyword::from_bytes(keyword.as_bytes()) == Some(keyword));
+        check!(keyword.as_str().parse() == Ok(keyword));
+        check!(keyword.to_string() == keyword.as_str());
+    }
+    check!(Keyword::Quote.as_bytes() == b"\"");
+    check!(ELSE == Some(Keyword::Else));
+}
+
+#[test]
+fn from_str() {
+    check!("if".parse::<Keyword>() == Ok(Keyword::If));
+    check!("i".parse::<Keyword>() == Err(ParseKeywordError));
+    check!("iff".parse::<Keyword>() == Err(ParseKeywordError));
+    check!("".parse::<Keyword>() == Err(ParseKeywordError));
+    check!(ParseKeywordError.to_string() == "invalid Keyword");
+}
+
+#[test]
+fn try_from() {
+    check!(Keyword::try_from(&b"else"[..]) == Ok(Keyword::Else));
+    check!(Keyword::try_from(&b"Else"[..]) == Err(ParseKeywordError));
+}
+
+#[test]
+fn parse_prefix() {
+    check!(Keyword::parse_prefix(b"if x") == (Some(Keyword::If), &b" x"[..]));
+    check!(Keyword::parse_prefix(b"in") == (Some(Keyword::In), &b""[..]));
+    check!(Keyword::parse_prefix(b"iffy") == (None, &b"iffy"[..]));
+    check!(Keyword::parse_prefix(b"\"(") == (Some(Keyword::Quote), &b"("[..]));
+    check!(Keyword::parse_prefix(b"\"a") == (None, &b"\"a"[..]));
+}
+
+#[test]
+fn not_utf8() {
+    check!(Marker::High.as_bytes() == b"\xff");
+    check!(Marker::from_bytes(b"\xff") == Some(Marker::High));
+    check!(Marker::from_bytes(b"bom") == Some(Marker::Bom));
+    check!(Marker::Bom.as_bytes() == b"BOM");
+    check!(Marker::try_from(&b"bomb"[..]) == Err(ParseMarkerError));
+    check!(Marker::parse_prefix(b"BoMb") == (Some(Marker::Bom), &b"b"[..]));
+}
+
+#[test]
+fn control_characters() {
+    check!(LineEnd::from_bytes(b"\r\n") == Some(LineEnd::CrLf));
+    check!(LineEnd::from_bytes(b"a\nb") == Some(LineEnd::Split));
+    check!(LineEnd::Split.as_str() == "a\nb");
+    check!("\n".parse() == Ok(LineEnd::Lf));
+}
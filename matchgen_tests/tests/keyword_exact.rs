@@ -0,0 +1,47 @@
+//! Test generated `keyword_exact*` functions.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    keyword_exact_flat, keyword_exact_iter, keyword_exact_slice,
+};
+use paste::paste;
+
+macro_rules! test {
+    ($name:ident, $input:expr, $result:expr) => {
+        paste! {
+            #[test]
+            fn [<$name _iter>]() {
+                check!(keyword_exact_iter($input.iter()) == $result);
+            }
+
+            #[test]
+            fn [<$name _slice>]() {
+                check!(keyword_exact_slice($input) == $result);
+            }
+
+            #[test]
+            fn [<$name _flat>]() {
+                check!(keyword_exact_flat($input) == $result);
+            }
+        }
+    };
+}
+
+test!(nothing, b"", None);
+test!(invalid, b"for", None);
+test!(kw_if, b"if", Some(1));
+test!(kw_in, b"in", Some(2));
+test!(kw_int, b"int", Some(3));
+test!(kw_else, b"else", Some(4));
+test!(prefix, b"i", None);
+test!(prefix_of_longer, b"els", None);
+test!(trailing, b"if ", None);
+test!(longer, b"ints", None);
+
+#[test]
+fn flat_const() {
+    const INT: Option<u8> = keyword_exact_flat(b"int");
+    check!(INT == Some(3));
+}
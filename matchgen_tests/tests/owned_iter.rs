@@ -0,0 +1,138 @@
+//! Test generated `owned_iter_*` functions, which accept an iterator over
+//! owned bytes that can’t be cloned.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    owned_iter_empty, owned_iter_entity, owned_iter_stub, owned_iter_word,
+    pattern_owned, Rule, Tok,
+};
+
+/// An iterator over owned bytes that can’t be cloned.
+struct Bytes<'a> {
+    input: &'a [u8],
+
+    /// The number of bytes read so far.
+    read: usize,
+}
+
+impl<'a> Bytes<'a> {
+    const fn new(input: &'a [u8]) -> Self {
+        Self { input, read: 0 }
+    }
+}
+
+impl Iterator for Bytes<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let (&b, rest) = self.input.split_first()?;
+        self.input = rest;
+        self.read = self.read.checked_add(1).unwrap();
+        Some(b)
+    }
+}
+
+/// Decode entities in `input`, reading each byte only once.
+fn decode(input: &[u8]) -> Vec<u8> {
+    let mut iter = Bytes::new(input);
+    let mut lookahead = Vec::new();
+    let mut out = Vec::new();
+    loop {
+        // The input is the lookahead from the last call followed by `iter`.
+        let mut old = std::mem::take(&mut lookahead).into_iter();
+        let (value, rest) =
+            owned_iter_entity(&mut old.by_ref().chain(&mut iter));
+        lookahead = rest;
+        lookahead.extend(old);
+
+        if let Some(value) = value {
+            out.extend_from_slice(value.as_bytes());
+        } else if !lookahead.is_empty() {
+            out.push(lookahead.remove(0));
+        } else {
+            break;
+        }
+    }
+    check!(iter.read == input.len());
+    out
+}
+
+#[test]
+fn entity() {
+    let mut iter = Bytes::new(b"&amp;&lt;&ltx&gt&");
+    check!(owned_iter_entity(&mut iter) == (Some("&"), vec![]));
+    check!(owned_iter_entity(&mut iter) == (Some("<"), vec![]));
+    check!(owned_iter_entity(&mut iter) == (Some("<"), b"x".to_vec()));
+    check!(owned_iter_entity(&mut iter) == (None, b"&gt&".to_vec()));
+    check!(owned_iter_entity(&mut iter) == (None, vec![]));
+}
+
+#[test]
+fn entity_reads_only_lookahead() {
+    let mut iter = Bytes::new(b"&lt;&lt;");
+    check!(owned_iter_entity(&mut iter) == (Some("<"), vec![]));
+    check!(iter.read == 4);
+
+    let mut iter = Bytes::new(b"x&lt;");
+    check!(owned_iter_entity(&mut iter) == (None, b"x".to_vec()));
+    check!(iter.read == 1);
+
+    let mut iter = Bytes::new(b"&am&lt;");
+    check!(owned_iter_entity(&mut iter) == (None, b"&am&".to_vec()));
+    check!(iter.read == 4);
+}
+
+#[test]
+fn entity_decode() {
+    check!(
+        decode(b"a &amp;&lt &lt;&gt;& &gt &am;&&lt;z&lt")
+            == b"a &< <>& &gt &am;&<z<"
+    );
+    check!(decode(b"") == b"");
+}
+
+#[test]
+fn stub() {
+    let mut iter = Bytes::new(b"&amp;&am");
+    check!(owned_iter_stub(&mut iter) == (Some("&"), vec![]));
+    check!(owned_iter_stub(&mut iter) == (None, b"&am".to_vec()));
+}
+
+#[test]
+fn word() {
+    let mut iter = Bytes::new(b"iF ");
+    check!(owned_iter_word(&mut iter) == (Some(1), b" ".to_vec()));
+    check!(iter.read == 3);
+
+    check!(owned_iter_word(&mut Bytes::new(b"In")) == (Some(2), vec![]));
+    check!(owned_iter_word(&mut Bytes::new(b"i.")) == (Some(3), b".".to_vec()));
+    check!(owned_iter_word(&mut Bytes::new(b"ifx")) == (None, b"ifx".to_vec()));
+    check!(owned_iter_word(&mut Bytes::new(b"x")) == (None, b"x".to_vec()));
+}
+
+#[test]
+fn empty_key() {
+    let mut iter = Bytes::new(b"abc");
+    check!(owned_iter_empty(&mut iter) == (Some(1), vec![]));
+    check!(iter.read == 0);
+}
+
+#[test]
+fn dyn_iterator() {
+    let mut iter = Bytes::new(b"&gt;");
+    let iter: &mut dyn Iterator<Item = u8> = &mut iter;
+    check!(owned_iter_entity(iter) == (Some(">"), vec![]));
+}
+
+#[test]
+fn tokens() {
+    use Tok::*;
+    let mut iter =
+        vec![Ident, LParen, RParen, Ident, Num(0), Ident, LParen].into_iter();
+    check!(pattern_owned(&mut iter) == (Some(Rule::EmptyCall), vec![]));
+    check!(pattern_owned(&mut iter) == (Some(Rule::Name), vec![Num(0)]));
+    check!(pattern_owned(&mut iter) == (Some(Rule::Call), vec![]));
+    check!(iter.next() == None);
+}
@@ -0,0 +1,81 @@
+//! Test generated `pattern_*` functions, which match slices of tokens.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    pattern_exact, pattern_flat, pattern_iter, pattern_literal, pattern_tree,
+    Rule, Tok,
+};
+
+#[test]
+fn tree() {
+    use Tok::*;
+    check!(pattern_tree(&[]) == (None, &[][..]));
+    check!(
+        pattern_tree(&[Ident, LParen, Num(1)])
+            == (Some(Rule::Call), &[Num(1)][..])
+    );
+    check!(
+        pattern_tree(&[Ident, LParen, RParen])
+            == (Some(Rule::EmptyCall), &[][..])
+    );
+    check!(pattern_tree(&[Ident, RParen]) == (Some(Rule::Name), &[RParen][..]));
+    check!(
+        pattern_tree(&[LParen, Num(0), RParen, Num(1)])
+            == (Some(Rule::Zero), &[Num(1)][..])
+    );
+    check!(
+        pattern_tree(&[LParen, Num(1), RParen])
+            == (None, &[LParen, Num(1), RParen][..])
+    );
+    check!(pattern_tree(&[Num(2)]) == (Some(Rule::Num), &[][..]));
+    check!(pattern_tree(&[RParen]) == (None, &[RParen][..]));
+}
+
+#[test]
+fn iter() {
+    use Tok::*;
+    let tokens = [Ident, LParen, Ident, Num(7), LParen, Num(0), RParen, RParen];
+    let mut iter = tokens.iter();
+    check!(pattern_iter(&mut iter) == Some(Rule::Call));
+    check!(pattern_iter(&mut iter) == Some(Rule::Name));
+    check!(pattern_iter(&mut iter) == Some(Rule::Num));
+    check!(pattern_iter(&mut iter) == Some(Rule::Zero));
+    check!(pattern_iter(&mut iter) == None);
+    check!(iter.next() == Some(&RParen));
+}
+
+#[test]
+fn flat() {
+    use Tok::*;
+    check!(
+        pattern_flat(&[Ident, LParen, RParen, Ident])
+            == (Some(Rule::EmptyCall), &[Ident][..])
+    );
+    check!(pattern_flat(&[Ident, LParen]) == (Some(Rule::Call), &[][..]));
+    check!(
+        pattern_flat(&[LParen, Num(0), RParen]) == (Some(Rule::Zero), &[][..])
+    );
+    check!(pattern_flat(&[Num(3)]) == (Some(Rule::Num), &[][..]));
+    check!(pattern_flat(&[LParen]) == (None, &[LParen][..]));
+}
+
+#[test]
+fn exact() {
+    use Tok::*;
+    check!(pattern_exact(&[Ident, LParen]) == Some(Rule::Call));
+    check!(pattern_exact(&[Ident, LParen, Num(1)]) == None);
+    check!(pattern_exact(&[Num(5)]) == Some(Rule::Num));
+    check!(pattern_exact(&[]) == None);
+}
+
+#[test]
+fn literal() {
+    use Tok::*;
+    check!(pattern_literal(&[Num(0), RParen]) == (Some(0), &[RParen][..]));
+    check!(pattern_literal(&[Num(1), RParen]) == (Some(1), &[][..]));
+    check!(pattern_literal(&[Num(1)]) == (None, &[Num(1)][..]));
+    check!(pattern_literal(&[Num(2)]) == (None, &[Num(2)][..]));
+    check!(pattern_literal(&[Ident]) == (Some(2), &[][..]));
+}
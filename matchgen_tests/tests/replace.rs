@@ -0,0 +1,91 @@
+//! Test generated `replace_*` functions.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    decode_entity_flat, decode_entity_flat_index, decode_entity_iter,
+    decode_entity_slice, decode_entity_str, most_entity_decode_str,
+    write_decoded_entity_flat, write_decoded_entity_flat_index,
+};
+use paste::paste;
+use std::borrow::Cow;
+
+macro_rules! test {
+    ($name:ident, $input:expr, $expected:expr) => {
+        paste! {
+            #[test]
+            fn [<$name _iter>]() {
+                let expected: &str = $expected;
+                check!(decode_entity_iter($input) == expected.as_bytes());
+            }
+
+            #[test]
+            fn [<$name _slice>]() {
+                let expected: &str = $expected;
+                check!(decode_entity_slice($input) == expected.as_bytes());
+            }
+
+            #[test]
+            fn [<$name _str>]() {
+                let expected: &str = $expected;
+                let input = std::str::from_utf8($input).unwrap();
+                check!(decode_entity_str(input) == expected);
+            }
+
+            #[test]
+            fn [<$name _flat>]() {
+                let expected: &str = $expected;
+                check!(decode_entity_flat($input) == expected.as_bytes());
+
+                let mut out = Vec::new();
+                write_decoded_entity_flat($input, &mut out).unwrap();
+                check!(out == expected.as_bytes());
+            }
+
+            #[test]
+            fn [<$name _flat_index>]() {
+                let expected: &str = $expected;
+                check!(
+                    decode_entity_flat_index($input) == expected.as_bytes()
+                );
+
+                let mut out = Vec::new();
+                write_decoded_entity_flat_index($input, &mut out).unwrap();
+                check!(out == expected.as_bytes());
+            }
+        }
+    };
+}
+
+test!(nothing, b"", "");
+test!(no_match, b"a & b", "a & b");
+test!(start, b"&lt;a", "<a");
+test!(middle, b"a &lt; b", "a < b");
+test!(end, b"a &gt;", "a >");
+test!(longest, b"&amp;&amp", "&&");
+test!(adjacent, b"&lt;&gt;", "<>");
+test!(false_start, b"&&lt;", "&<");
+test!(several, b"1 &lt; 2 &amp;&amp; 3 &gt 2", "1 < 2 && 3 &gt 2");
+test!(unicode, "é &lt; ü".as_bytes(), "é < ü");
+
+#[test]
+fn unchanged_is_borrowed() {
+    check!(matches!(decode_entity_slice(b"a & b"), Cow::Borrowed(_)));
+    check!(matches!(decode_entity_str("a & b"), Cow::Borrowed(_)));
+    check!(matches!(decode_entity_flat(b""), Cow::Borrowed(_)));
+}
+
+#[test]
+fn changed_is_owned() {
+    check!(matches!(decode_entity_slice(b"&lt;"), Cow::Owned(_)));
+    check!(matches!(decode_entity_str("&lt;"), Cow::Owned(_)));
+}
+
+#[test]
+fn most_entities() {
+    check!(
+        most_entity_decode_str("&aacute;b &amp &acirc;; &zzz;")
+            == "áb & â; &zzz;"
+    );
+}
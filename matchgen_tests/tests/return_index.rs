@@ -0,0 +1,79 @@
+//! Test generated `index_*` functions, which return an index rather than the
+//! remainder of the input, and are `const`.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    index_empty, index_entity, index_exact, index_find, index_str, index_stub,
+    index_suffix, index_uncollapsed, index_word,
+};
+
+/// Look up a value at compile time.
+const LT: Option<&str> = index_exact(b"&lt;");
+
+/// Match a prefix at compile time.
+const AMP: (Option<&str>, usize) = index_entity(b"&amp;x");
+
+#[test]
+fn const_lookup() {
+    check!(LT == Some("<"));
+    check!(AMP == (Some("&"), 5));
+}
+
+#[test]
+fn prefix() {
+    for matcher in [index_entity, index_stub, index_uncollapsed] {
+        check!(matcher(b"") == (None, 0));
+        check!(matcher(b"&") == (None, 0));
+        check!(matcher(b"&amp;") == (Some("&"), 5));
+        check!(matcher(b"&lt;x") == (Some("<"), 4));
+        check!(matcher(b"&ltx") == (Some("<"), 3));
+        check!(matcher(b"&gt") == (None, 0));
+        check!(matcher(b"x&amp;") == (None, 0));
+    }
+}
+
+#[test]
+fn str_input() {
+    check!(index_str("") == (None, 0));
+    check!(index_str("&lt;×") == (Some("<"), 4));
+    check!(index_str("&lt×") == (Some("<"), 3));
+    check!(index_str("×&lt;") == (None, 0));
+}
+
+#[test]
+fn suffix() {
+    check!(index_suffix(b"") == (None, 0));
+    check!(index_suffix(b"x&amp;") == (Some("&"), 1));
+    check!(index_suffix(b"x&lt") == (Some("<"), 1));
+    check!(index_suffix(b"&gt;") == (Some(">"), 0));
+    check!(index_suffix(b"&amp;x") == (None, 6));
+}
+
+#[test]
+fn exact() {
+    check!(index_exact(b"&amp;") == Some("&"));
+    check!(index_exact(b"&lt") == Some("<"));
+    check!(index_exact(b"&lt;x") == None);
+    check!(index_exact(b"") == None);
+}
+
+#[test]
+fn boundary() {
+    check!(index_word(b"if x") == (Some(1), 2));
+    check!(index_word(b"in") == (Some(2), 2));
+    check!(index_word(b"i.") == (Some(3), 1));
+    check!(index_word(b"ifx") == (None, 0));
+}
+
+#[test]
+fn empty_key() {
+    check!(index_empty(b"abc") == (Some(1), 0));
+}
+
+#[test]
+fn find() {
+    check!(index_find(b"ab&lt;c") == Some((2, 6, "<")));
+    check!(index_find(b"ab&c") == None);
+}
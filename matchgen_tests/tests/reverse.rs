@@ -0,0 +1,57 @@
+//! Test generated reverse functions, which map values back to keys.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    most_entity_decode_slice_collapse, most_entity_encode, reverse_entity,
+    reverse_entity_encode, reverse_flat, reverse_flat_encode,
+    utf16_tree_reverse,
+};
+
+#[test]
+fn entity() {
+    check!(reverse_entity_encode("&") == Some(&b"&amp;"[..]));
+    check!(reverse_entity_encode("<") == Some(&b"&lt;"[..]));
+    check!(reverse_entity_encode(">") == Some(&b"&gt;"[..]));
+    check!(reverse_entity_encode("&amp;").is_none());
+    check!(reverse_entity_encode("").is_none());
+}
+
+#[test]
+fn entity_round_trip() {
+    for value in ["&", "<", ">"] {
+        let key = reverse_entity_encode(value).unwrap();
+        check!(reverse_entity(key) == (Some(value), &b""[..]));
+    }
+}
+
+#[test]
+fn flat_value_pattern() {
+    check!(reverse_flat_encode(0x3c) == Some(&b"less"[..]));
+    check!(reverse_flat_encode(u32::from(b'>')) == Some(&b"GT"[..]));
+    check!(reverse_flat_encode(0).is_none());
+    check!(reverse_flat(b"lEsS") == (Some(0x3c), &b""[..]));
+}
+
+#[test]
+fn utf16() {
+    let utf16 = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+    check!(utf16_tree_reverse(1) == Some(&utf16("a")[..]));
+    check!(utf16_tree_reverse(4) == Some(&utf16("😀")[..]));
+    check!(utf16_tree_reverse(5).is_none());
+}
+
+#[test]
+fn most_entity() {
+    for value in ["&", "<", "\"", "\u{2248}"] {
+        let key = most_entity_encode(value).unwrap();
+        check!(key.ends_with(b";"));
+        check!(
+            most_entity_decode_slice_collapse(key) == (Some(value), &b""[..])
+        );
+    }
+    check!(most_entity_encode("&").unwrap() == b"&amp;");
+    check!(most_entity_encode("\u{2248}").unwrap() == b"&thickapprox;");
+    check!(most_entity_encode("a").is_none());
+}
@@ -0,0 +1,53 @@
+//! Test generated `shortest*` and `priority*` functions.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    priority_flat, priority_iter, priority_slice, shortest_flat, shortest_iter,
+    shortest_slice,
+};
+use paste::paste;
+
+macro_rules! test {
+    ($prefix:ident, $name:ident, $input:expr, $result:expr, $remainder:expr) => {
+        paste! {
+            #[test]
+            fn [<$prefix _ $name _iter>]() {
+                let input = $input;
+                let mut iter = input.iter();
+                check!([<$prefix _iter>](&mut iter).map(|r| r.0) == $result);
+                check!(iter.as_slice() == $remainder);
+            }
+
+            #[test]
+            fn [<$prefix _ $name _slice>]() {
+                let (result, remainder) = [<$prefix _slice>]($input);
+                check!(result.map(|r| r.0) == $result);
+                check!(remainder == $remainder.as_slice());
+            }
+
+            #[test]
+            fn [<$prefix _ $name _flat>]() {
+                let (result, remainder) = [<$prefix _flat>]($input);
+                check!(result.map(|r| r.0) == $result);
+                check!(remainder == $remainder.as_slice());
+            }
+        }
+    };
+}
+
+test!(shortest, nothing, b"", None, b"");
+test!(shortest, invalid, b"c", None, b"c");
+test!(shortest, a, b"a", Some(2), b"");
+test!(shortest, ab, b"ab", Some(2), b"b");
+test!(shortest, abc, b"abcd", Some(2), b"bcd");
+test!(shortest, b, b"bc", Some(4), b"c");
+
+test!(priority, nothing, b"", None, b"");
+test!(priority, invalid, b"c", None, b"c");
+test!(priority, a, b"a", Some(2), b"");
+test!(priority, ax, b"ax", Some(2), b"x");
+test!(priority, ab, b"ab", Some(1), b"");
+test!(priority, abc, b"abcd", Some(1), b"cd");
+test!(priority, b, b"bc", Some(4), b"c");
@@ -0,0 +1,147 @@
+//! Test generated state machine structs, which match input fed in chunks.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    state_entity, state_word, EntityState, EntityStateResult, Rule, Tok,
+    TokState, TokStateResult, WordState, WordStateResult,
+};
+
+/// Feed `input` to a new `EntityState` in chunks of `size` elements.
+fn feed_entity(input: &[u8], size: usize) -> EntityStateResult<&'static str> {
+    let mut state = EntityState::new();
+    for chunk in input.chunks(size) {
+        match state.feed(chunk) {
+            EntityStateResult::NeedMoreInput => {}
+            result => return result,
+        }
+    }
+    state.finish()
+}
+
+#[test]
+fn entity_matches_slice_matcher() {
+    let inputs: &[&[u8]] = &[
+        b"",
+        b"&",
+        b"&l",
+        b"&lt",
+        b"&lt;",
+        b"&ltx",
+        b"&lt;x",
+        b"&amp",
+        b"&amp;",
+        b"&amp;x",
+        b"&gt",
+        b"&gt;&lt;",
+        b"x&lt;",
+        b"&&lt;",
+    ];
+    for input in inputs {
+        let expected = match state_entity(input) {
+            (Some(value), rest) => {
+                EntityStateResult::Matched(value, input.len() - rest.len())
+            }
+            (None, _) => EntityStateResult::NoMatch,
+        };
+        for size in 1..=input.len().max(1) {
+            check!(feed_entity(input, size) == expected, "{:?}", input);
+        }
+    }
+}
+
+#[test]
+fn entity_need_more_input() {
+    let mut state = EntityState::new();
+    check!(state.feed(b"") == EntityStateResult::NeedMoreInput);
+    check!(state.feed(b"&") == EntityStateResult::NeedMoreInput);
+    check!(state.feed(b"am") == EntityStateResult::NeedMoreInput);
+    check!(state.feed(b"p;xyz") == EntityStateResult::Matched("&", 5));
+
+    // The state was reset.
+    check!(state.feed(b"&l") == EntityStateResult::NeedMoreInput);
+    check!(state.feed(b"t") == EntityStateResult::NeedMoreInput);
+    check!(state.finish() == EntityStateResult::Matched("<", 3));
+}
+
+#[test]
+fn entity_no_match() {
+    let mut state = EntityState::new();
+    check!(state.feed(b"x") == EntityStateResult::NoMatch);
+    check!(state.feed(b"&a") == EntityStateResult::NeedMoreInput);
+    check!(state.feed(b"x") == EntityStateResult::NoMatch);
+    check!(state.feed(b"&am") == EntityStateResult::NeedMoreInput);
+    check!(state.finish() == EntityStateResult::NoMatch);
+    check!(state.finish() == EntityStateResult::NoMatch);
+}
+
+#[test]
+fn entity_reset() {
+    let mut state = EntityState::default();
+    check!(state.feed(b"&g") == EntityStateResult::NeedMoreInput);
+    state.reset();
+    check!(state.feed(b"t;") == EntityStateResult::NoMatch);
+}
+
+#[test]
+fn word() {
+    let mut state = WordState::new();
+    check!(state.feed(b"i") == WordStateResult::NeedMoreInput);
+    check!(state.feed(b"F") == WordStateResult::NeedMoreInput);
+    check!(state.feed(b" ") == WordStateResult::Matched(1, 2));
+
+    check!(state.feed(b"IN") == WordStateResult::NeedMoreInput);
+    check!(state.finish() == WordStateResult::Matched(2, 2));
+
+    check!(state.feed(b"int") == WordStateResult::NoMatch);
+    check!(state.feed(b"i(") == WordStateResult::Matched(3, 1));
+    check!(state.feed(b"ifx") == WordStateResult::NoMatch);
+}
+
+#[test]
+fn word_matches_slice_matcher() {
+    let inputs: &[&[u8]] =
+        &[b"", b"i", b"if", b"In", b"i(", b"ix", b"iF_", b"in-"];
+    for input in inputs {
+        let expected = match state_word(input) {
+            (Some(value), rest) => {
+                WordStateResult::Matched(value, input.len() - rest.len())
+            }
+            (None, _) => WordStateResult::NoMatch,
+        };
+        for size in 1..=input.len().max(1) {
+            let mut state = WordState::new();
+            let mut result = WordStateResult::NeedMoreInput;
+            for chunk in input.chunks(size) {
+                result = state.feed(chunk);
+                if result != WordStateResult::NeedMoreInput {
+                    break;
+                }
+            }
+            if result == WordStateResult::NeedMoreInput {
+                result = state.finish();
+            }
+            check!(result == expected, "{:?}", input);
+        }
+    }
+}
+
+#[test]
+fn tokens() {
+    use Tok::*;
+    let mut state = TokState::new();
+    check!(state.feed(&[Ident]) == TokStateResult::NeedMoreInput);
+    check!(state.feed(&[LParen]) == TokStateResult::NeedMoreInput);
+    check!(
+        state.feed(&[RParen, Num(1)])
+            == TokStateResult::Matched(Rule::EmptyCall, 3)
+    );
+    check!(
+        state.feed(&[Ident, Num(1)]) == TokStateResult::Matched(Rule::Name, 1)
+    );
+    check!(state.feed(&[LParen, Num(0)]) == TokStateResult::NeedMoreInput);
+    check!(state.feed(&[RParen]) == TokStateResult::Matched(Rule::Zero, 3));
+    check!(state.feed(&[Num(7)]) == TokStateResult::Matched(Rule::Num, 1));
+    check!(state.feed(&[RParen]) == TokStateResult::NoMatch);
+}
@@ -0,0 +1,91 @@
+//! Test generated `str_*` and `str_chars_*` functions, which accept `&str`.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    str_all, str_chars_exact, str_chars_prefix, str_chars_prefix_index,
+    str_chars_suffix, str_chars_suffix_index, str_chars_word, str_exact,
+    str_prefix, str_suffix, str_word,
+};
+
+#[test]
+fn prefix() {
+    check!(str_prefix("") == (None, ""));
+    check!(str_prefix("b") == (None, "b"));
+    check!(str_prefix("abc") == (Some(2), "c"));
+    check!(str_prefix("a×") == (Some(1), "×"));
+    check!(str_prefix("×÷×") == (Some(4), "×"));
+    check!(str_prefix("××") == (Some(3), "×"));
+    check!(str_prefix("éa") == (Some(5), "a"));
+    check!(str_prefix("è") == (None, "è"));
+}
+
+#[test]
+fn suffix() {
+    check!(str_suffix("") == (None, ""));
+    check!(str_suffix("cab") == (Some(2), "c"));
+    check!(str_suffix("ba") == (Some(1), "b"));
+    check!(str_suffix("é×÷") == (Some(4), "é"));
+    check!(str_suffix("÷×") == (Some(3), "÷"));
+    check!(str_suffix("è") == (None, "è"));
+}
+
+#[test]
+fn exact() {
+    check!(str_exact("") == None);
+    check!(str_exact("ab") == Some(2));
+    check!(str_exact("×÷") == Some(4));
+    check!(str_exact("×÷ ") == None);
+}
+
+#[test]
+fn word() {
+    check!(str_word("ab c") == (Some(2), " c"));
+    check!(str_word("abc") == (None, "abc"));
+    check!(str_word("a×") == (Some(1), "×"));
+    check!(str_word("é_") == (None, "é_"));
+}
+
+#[test]
+fn all() {
+    let mut found = Vec::new();
+    str_all("×÷×", |value, len| found.push((value, len)));
+    check!(found == [(3, 2), (4, 4)]);
+
+    found.clear();
+    str_all("b", |value, len| found.push((value, len)));
+    check!(found.is_empty());
+}
+
+#[test]
+fn chars_prefix() {
+    for input in ["", "b", "abc", "a×", "×÷×", "××", "éa", "è"] {
+        check!(str_chars_prefix(input) == str_prefix(input));
+    }
+    check!(str_chars_prefix_index("×÷×") == (Some(4), 4));
+    check!(str_chars_prefix_index("è") == (None, 0));
+}
+
+#[test]
+fn chars_suffix() {
+    for input in ["", "cab", "ba", "é×÷", "÷×", "è"] {
+        check!(str_chars_suffix(input) == str_suffix(input));
+    }
+    check!(str_chars_suffix_index("é×÷") == (Some(4), 2));
+    check!(str_chars_suffix_index("è") == (None, 2));
+}
+
+#[test]
+fn chars_exact() {
+    for input in ["", "ab", "×÷", "×÷ "] {
+        check!(str_chars_exact(input) == str_exact(input));
+    }
+}
+
+#[test]
+fn chars_word() {
+    for input in ["ab c", "abc", "a×", "é_", "é"] {
+        check!(str_chars_word(input) == str_word(input));
+    }
+}
@@ -0,0 +1,56 @@
+//! Test generated key and value tables.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    most_entity_decode_slice_collapse, reverse_entity, EMPTY_ENTRIES,
+    EMPTY_KEYS, EMPTY_MAX_KEY_LEN, EMPTY_MIN_KEY_LEN, MOST_ENTITY_ENTRIES,
+    MOST_ENTITY_KEYS, MOST_ENTITY_MAX_KEY_LEN, MOST_ENTITY_MIN_KEY_LEN,
+    REVERSE_ENTITY_ENTRIES, REVERSE_ENTITY_KEYS, REVERSE_ENTITY_MAX_KEY_LEN,
+    REVERSE_ENTITY_MIN_KEY_LEN, UTF16_KEYS, UTF16_MAX_KEY_LEN,
+    UTF16_MIN_KEY_LEN,
+};
+
+#[test]
+fn entity() {
+    check!(
+        REVERSE_ENTITY_KEYS
+            == [&b"&AMP;"[..], b"&amp;", b"&gt;", b"&lt", b"&lt;"]
+    );
+    check!(REVERSE_ENTITY_ENTRIES[0] == (&b"&AMP;"[..], "&"));
+    check!(REVERSE_ENTITY_ENTRIES.len() == REVERSE_ENTITY_KEYS.len());
+    check!(REVERSE_ENTITY_MIN_KEY_LEN == 3);
+    check!(REVERSE_ENTITY_MAX_KEY_LEN == 5);
+    for &(key, value) in REVERSE_ENTITY_ENTRIES {
+        check!(reverse_entity(key) == (Some(value), &b""[..]));
+    }
+}
+
+#[test]
+fn empty() {
+    check!(EMPTY_KEYS.is_empty());
+    check!(EMPTY_ENTRIES.is_empty());
+    check!(EMPTY_MIN_KEY_LEN == 0);
+    check!(EMPTY_MAX_KEY_LEN == 0);
+}
+
+#[test]
+fn utf16() {
+    let utf16 = |s: &str| s.encode_utf16().collect::<Vec<u16>>();
+    check!(UTF16_KEYS.contains(&&utf16("😀")[..]));
+    check!(UTF16_MIN_KEY_LEN == 1);
+    check!(UTF16_MAX_KEY_LEN == 2);
+}
+
+#[test]
+fn most_entity() {
+    check!(MOST_ENTITY_KEYS.len() == MOST_ENTITY_ENTRIES.len());
+    check!(MOST_ENTITY_MIN_KEY_LEN == 3);
+    for &(key, value) in MOST_ENTITY_ENTRIES {
+        check!(key.len() <= MOST_ENTITY_MAX_KEY_LEN);
+        check!(
+            most_entity_decode_slice_collapse(key) == (Some(value), &b""[..])
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Test generated tokenizer structs.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    FallbackLexer, OtherIndexLexer, OtherLexer, SkipLexer, Token,
+};
+use paste::paste;
+
+type Span = core::ops::Range<usize>;
+
+macro_rules! test {
+    ($name:ident, $input:expr, $skip:expr, $other:expr, $fallback:expr) => {
+        paste! {
+            #[test]
+            fn [<$name _skip>]() {
+                let expected: &[(Token, Span)] = &$skip;
+                check!(SkipLexer::new($input).collect::<Vec<_>>() == expected);
+            }
+
+            #[test]
+            fn [<$name _other>]() {
+                let expected: &[(Token, Span)] = &$other;
+                check!(OtherLexer::new($input).collect::<Vec<_>>() == expected);
+                check!(
+                    OtherIndexLexer::new($input).collect::<Vec<_>>()
+                    == expected
+                );
+            }
+
+            #[test]
+            fn [<$name _fallback>]() {
+                let expected: &[(Token, Span)] = &$fallback;
+                check!(
+                    FallbackLexer::new($input).collect::<Vec<_>>() == expected
+                );
+            }
+        }
+    };
+}
+
+test!(nothing, b"", [], [], []);
+test!(
+    only_unmatched,
+    b"ab",
+    [],
+    [(Token::Other, 0..2)],
+    [(Token::Word, 0..2)]
+);
+test!(
+    only_matched,
+    b"+=+",
+    [(Token::PlusEq, 0..2), (Token::Plus, 2..3)],
+    [(Token::PlusEq, 0..2), (Token::Plus, 2..3)],
+    [(Token::PlusEq, 0..2), (Token::Plus, 2..3)]
+);
+test!(
+    mixed,
+    b"let x += 1;",
+    [
+        (Token::Let, 0..3),
+        (Token::Space, 3..4),
+        (Token::Space, 5..6),
+        (Token::PlusEq, 6..8),
+        (Token::Space, 8..9),
+    ],
+    [
+        (Token::Let, 0..3),
+        (Token::Space, 3..4),
+        (Token::Other, 4..5),
+        (Token::Space, 5..6),
+        (Token::PlusEq, 6..8),
+        (Token::Space, 8..9),
+        (Token::Other, 9..11),
+    ],
+    [
+        (Token::Let, 0..3),
+        (Token::Space, 3..4),
+        (Token::Word, 4..5),
+        (Token::Space, 5..6),
+        (Token::PlusEq, 6..8),
+        (Token::Space, 8..9),
+        (Token::Word, 9..10),
+        (Token::Other, 10..11),
+    ]
+);
+test!(
+    unmatched_prefix,
+    b"le+",
+    [(Token::Plus, 2..3)],
+    [(Token::Other, 0..2), (Token::Plus, 2..3)],
+    [(Token::Word, 0..2), (Token::Plus, 2..3)]
+);
+
+#[test]
+fn fused() {
+    let mut lexer = OtherLexer::new(b"+");
+    check!(lexer.next() == Some((Token::Plus, 0..1)));
+    check!(lexer.next() == None);
+    check!(lexer.next() == None);
+}
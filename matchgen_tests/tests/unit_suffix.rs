@@ -0,0 +1,72 @@
+//! Test generated `unit_suffix*` functions.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    unit_suffix_flat, unit_suffix_flat_index, unit_suffix_iter,
+    unit_suffix_shortest_flat, unit_suffix_shortest_slice, unit_suffix_slice,
+};
+use paste::paste;
+
+macro_rules! test {
+    ($name:ident, $input:expr, $result:expr, $before:expr) => {
+        paste! {
+            #[test]
+            fn [<$name _iter>]() {
+                let input = $input;
+                let mut iter = input.iter();
+                check!(unit_suffix_iter(&mut iter) == $result);
+                check!(iter.as_slice() == $before);
+            }
+
+            #[test]
+            fn [<$name _slice>]() {
+                check!(
+                    unit_suffix_slice($input)
+                    == ($result, $before.as_slice())
+                );
+            }
+
+            #[test]
+            fn [<$name _flat>]() {
+                check!(
+                    unit_suffix_flat($input)
+                    == ($result, $before.as_slice())
+                );
+            }
+
+            #[test]
+            fn [<$name _flat_index>]() {
+                check!(
+                    unit_suffix_flat_index($input)
+                    == ($result, $before.len())
+                );
+            }
+        }
+    };
+}
+
+test!(nothing, b"", None, b"");
+test!(invalid, b"10 kb", None, b"10 kb");
+test!(bytes, b"10B", Some(1), b"10");
+test!(kibibytes, b"10KiB", Some(1024), b"10");
+test!(mebibytes, b"MiB", Some(1_048_576), b"");
+test!(partial, b"iB", Some(1), b"i");
+test!(seconds, b"5s", Some(1000), b"5");
+test!(milliseconds, b"5ms", Some(1), b"5");
+test!(prefix_only, b"KiB10", None, b"KiB10");
+
+#[test]
+fn shortest_slice() {
+    check!(unit_suffix_shortest_slice(b"10KiB") == (Some(1), &b"10Ki"[..]));
+    check!(unit_suffix_shortest_slice(b"5ms") == (Some(1000), &b"5m"[..]));
+    check!(unit_suffix_shortest_slice(b"KiB5") == (None, &b"KiB5"[..]));
+}
+
+#[test]
+fn shortest_flat() {
+    check!(unit_suffix_shortest_flat(b"10KiB") == (Some(1), &b"10Ki"[..]));
+    check!(unit_suffix_shortest_flat(b"5ms") == (Some(1000), &b"5m"[..]));
+    check!(unit_suffix_shortest_flat(b"KiB5") == (None, &b"KiB5"[..]));
+}
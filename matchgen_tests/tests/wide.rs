@@ -0,0 +1,65 @@
+//! Test generated `utf16_*` and `utf32_*` functions, which match `u16` and
+//! `u32` elements.
+
+#![allow(clippy::missing_docs_in_private_items)]
+
+use assert2::check;
+use matchgen_tests::{
+    utf16_flat, utf16_flat_index, utf16_tree, utf16_tree_iter,
+    utf16_tree_suffix, utf32_exact, utf32_word,
+};
+
+fn utf16(s: &str) -> Vec<u16> {
+    s.encode_utf16().collect()
+}
+
+fn utf32(s: &str) -> Vec<u32> {
+    s.chars().map(u32::from).collect()
+}
+
+#[test]
+fn tree() {
+    check!(utf16_tree(&[]) == (None, &[][..]));
+    check!(utf16_tree(&utf16("abc")) == (Some(2), &utf16("c")[..]));
+    check!(utf16_tree(&utf16("ac")) == (Some(1), &utf16("c")[..]));
+    check!(utf16_tree(&utf16("ΩΩ")) == (Some(3), &utf16("Ω")[..]));
+    check!(utf16_tree(&utf16("😀a")) == (Some(4), &utf16("a")[..]));
+    check!(utf16_tree(&utf16("😁")) == (None, &utf16("😁")[..]));
+}
+
+#[test]
+fn tree_iter() {
+    let input = utf16("😀ab");
+    let mut iter = input.iter();
+    check!(utf16_tree_iter(&mut iter) == Some(4));
+    check!(utf16_tree_iter(&mut iter) == Some(2));
+    check!(utf16_tree_iter(&mut iter) == None);
+}
+
+#[test]
+fn tree_suffix() {
+    check!(utf16_tree_suffix(&utf16("cab")) == (Some(2), &utf16("c")[..]));
+    check!(utf16_tree_suffix(&utf16("c😀")) == (Some(4), &utf16("c")[..]));
+    check!(utf16_tree_suffix(&utf16("c")) == (None, &utf16("c")[..]));
+}
+
+#[test]
+fn flat() {
+    check!(utf16_flat(&utf16("abc")) == (Some(2), &utf16("c")[..]));
+    check!(utf16_flat(&utf16("Ωa")) == (Some(3), &utf16("a")[..]));
+    check!(utf16_flat(&utf16("😀")) == (Some(4), &[][..]));
+    check!(utf16_flat_index(&utf16("😀")) == (Some(4), 2));
+    check!(utf16_flat_index(&utf16("c")) == (None, 0));
+}
+
+#[test]
+fn wide_utf32() {
+    check!(utf32_word(&utf32("AB c")) == (Some(2), &utf32(" c")[..]));
+    check!(utf32_word(&utf32("abc")) == (None, &utf32("abc")[..]));
+    check!(utf32_word(&utf32("aΩ")) == (Some(1), &utf32("Ω")[..]));
+    check!(utf32_word(&utf32("😀_")) == (None, &utf32("😀_")[..]));
+
+    check!(utf32_exact(&utf32("😀")) == Some(4));
+    check!(utf32_exact(&utf32("Ω")) == Some(3));
+    check!(utf32_exact(&utf32("ΩΩ")) == None);
+}
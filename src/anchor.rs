@@ -0,0 +1,104 @@
+//! Code for [`Anchor`].
+
+/// Where keys must be found in the input for the generated function to match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    /// Match keys at the start of the input. This is the default.
+    ///
+    /// The generated function returns the match, if any, and the remainder of
+    /// the input after the match.
+    Prefix,
+
+    /// Match only if the entire input is a key.
+    ///
+    /// The generated function returns only the match, if any, i.e.
+    /// `Option<{return_type}>`. This is useful as a static lookup table, e.g.
+    /// to map keywords to tokens.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{Anchor, TreeMatcher};
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn match_bytes", "u64")
+    ///     .anchor(Anchor::Exact)
+    ///     .add(b"a", "1")
+    ///     .add(b"abc", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> Option<u64> {
+    ///     match slice {
+    ///         [b'a', ..] => match &slice[1..] {
+    ///             [] => Some(1),
+    ///             [b'b', b'c'] => Some(2),
+    ///             _ => None,
+    ///         }
+    ///         _ => None,
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    Exact,
+
+    /// Match keys at the end of the input.
+    ///
+    /// The generated function returns the match, if any, and the part of the
+    /// input before the match. It is built from a tree of reversed keys, so
+    /// [`Order::Custom`][crate::Order::Custom] will be passed reversed keys.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{Anchor, TreeMatcher};
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn match_bytes", "u64")
+    ///     .anchor(Anchor::Suffix)
+    ///     .add(b"B", "1")
+    ///     .add(b"KiB", "1024")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     match slice {
+    ///         [before_1 @ .., b'B'] => match before_1 {
+    ///             [before_3 @ .., b'K', b'i'] => (Some(1024), before_3),
+    ///             _ => (Some(1), before_1),
+    ///         }
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    Suffix,
+}
+
+impl Default for Anchor {
+    fn default() -> Self {
+        Self::Prefix
+    }
+}
@@ -0,0 +1,169 @@
+//! Code for [`Boundary`].
+
+use crate::Element;
+
+/// Which bytes may follow a key for it to match.
+///
+/// This is useful for matching keywords: `if` should match in `if (`, but not
+/// in `iffy`. If the byte after a key isn’t allowed, the generated function
+/// will fall back to a shorter key, if one matches.
+///
+/// The end of the input is always allowed. With
+/// [`Anchor::Suffix`][crate::Anchor::Suffix], this checks the byte before the
+/// key instead of after it. This is ignored with
+/// [`Anchor::Exact`][crate::Anchor::Exact].
+///
+/// When matching keys regardless of ASCII case, the boundary also ignores ASCII
+/// case, e.g. `Boundary::NotFollowedBy(b"a".to_vec())` forbids both `a` and `A`.
+///
+/// With [`Input::Chars`][crate::Input::Chars], the boundary applies to ASCII
+/// characters as bytes. Other characters are allowed by
+/// [`Boundary::NotFollowedBy`] and forbidden by [`Boundary::FollowedBy`].
+///
+/// # Example
+///
+/// ```rust
+/// use matchgen::{Boundary, TreeMatcher};
+///
+/// let mut out = Vec::new();
+/// TreeMatcher::new("fn match_bytes", "u64")
+///     .boundary(Boundary::NotFollowedBy(b"0123456789".to_vec()))
+///     .add(b"a", "1")
+///     .add(b"a1", "2")
+///     .render(&mut out)
+///     .unwrap();
+///
+/// use bstr::ByteVec;
+/// pretty_assertions::assert_str_eq!(
+///     r#"#[allow(
+///     clippy::match_same_arms,
+///     clippy::missing_const_for_fn,
+///     clippy::single_match_else,
+///     clippy::too_many_lines,
+/// )]
+/// #[must_use]
+/// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+///     match slice {
+///         [b'a', ..] => match &slice[1..] {
+///             [b'1', ..] => match &slice[2..] {
+///                 [b'0'..=b'9', ..] => (None, slice),
+///                 _ => (Some(2), &slice[2..]),
+///             }
+///             [b'0'..=b'9', ..] => (None, slice),
+///             _ => (Some(1), &slice[1..]),
+///         }
+///         _ => (None, slice),
+///     }
+/// }
+/// "#,
+///     out.into_string().unwrap(),
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Boundary {
+    /// Any byte may follow a key. This is the default.
+    Any,
+
+    /// Only these bytes, or the end of the input, may follow a key.
+    FollowedBy(Vec<u8>),
+
+    /// These bytes may not follow a key.
+    NotFollowedBy(Vec<u8>),
+}
+
+impl Default for Boundary {
+    fn default() -> Self {
+        Self::Any
+    }
+}
+
+impl Boundary {
+    /// Keys may not be followed by ASCII alphanumerics or `_`.
+    ///
+    /// This is like `\b` at the end of a regular expression, assuming that all
+    /// keys end with a word character.
+    #[must_use]
+    pub fn word() -> Self {
+        Self::NotFollowedBy(
+            (0..=u8::MAX)
+                .filter(|&b| b.is_ascii_alphanumeric() || b == b'_')
+                .collect(),
+        )
+    }
+
+    /// Check if `element` may follow a key.
+    ///
+    /// If `ignore_ascii_case` is set, bytes are looked up in the set
+    /// regardless of ASCII case.
+    pub(crate) fn allows<E: Element>(
+        &self,
+        element: &E,
+        ignore_ascii_case: bool,
+    ) -> bool {
+        let listed = |bytes: &[u8], b: u8| {
+            bytes.contains(&b)
+                || (ignore_ascii_case
+                    && (bytes.contains(&b.to_ascii_lowercase())
+                        || bytes.contains(&b.to_ascii_uppercase())))
+        };
+        match (self, element.to_byte()) {
+            (Self::Any, _) | (Self::NotFollowedBy(_), None) => true,
+            (Self::FollowedBy(bytes), Some(b)) => listed(bytes, b),
+            (Self::FollowedBy(_), None) => false,
+            (Self::NotFollowedBy(bytes), Some(b)) => !listed(bytes, b),
+        }
+    }
+
+    /// Get a pattern that matches the elements that may not follow a key, or
+    /// `None` if all elements are allowed.
+    ///
+    /// See [`Self::allows()`] for `ignore_ascii_case`.
+    pub(crate) fn forbidden_pattern<E: Element>(
+        &self,
+        ignore_ascii_case: bool,
+    ) -> Option<String> {
+        let forbidden: Vec<u8> = (0..=u8::MAX)
+            .filter(|&b| {
+                E::from_byte(b)
+                    .map_or(false, |e| !self.allows(&e, ignore_ascii_case))
+            })
+            .collect();
+        let mut pattern = fmt_byte_set::<E>(&forbidden);
+        if let (Self::FollowedBy(_), Some(non_ascii)) = (self, E::NON_ASCII) {
+            pattern.push(non_ascii.to_owned());
+        }
+
+        if pattern.is_empty() {
+            None
+        } else {
+            Some(pattern.join(" | "))
+        }
+    }
+}
+
+/// Format a sorted list of bytes as patterns for elements of type `E`, using
+/// ranges where possible, e.g. `b'0'..=b'9'`, `b'_'`.
+fn fmt_byte_set<E: Element>(bytes: &[u8]) -> Vec<String> {
+    // Find runs of consecutive bytes.
+    let mut runs: Vec<(u8, u8)> = Vec::new();
+    for &b in bytes {
+        match runs.last_mut() {
+            Some((_, end)) if end.checked_add(1) == Some(b) => *end = b,
+            _ => runs.push((b, b)),
+        }
+    }
+
+    let fmt = |b| E::from_byte(b).map(|e| e.fmt_literal()).unwrap_or_default();
+    let mut patterns = Vec::new();
+    for (start, end) in runs {
+        match end.saturating_sub(start) {
+            0 => patterns.push(fmt(start)),
+            1 => {
+                patterns.push(fmt(start));
+                patterns.push(fmt(end));
+            }
+            _ => patterns.push(format!("{}..={}", fmt(start), fmt(end))),
+        }
+    }
+    patterns
+}
@@ -0,0 +1,63 @@
+//! Code for [`Canonical`].
+
+/// Which key a reverse function returns when several keys have the same
+/// value.
+///
+/// See [`TreeMatcher::reverse_fn()`][crate::TreeMatcher::reverse_fn] and
+/// [`FlatMatcher::reverse_fn()`][crate::FlatMatcher::reverse_fn].
+///
+/// # Example
+///
+/// ```rust
+/// use matchgen::{Canonical, TreeMatcher};
+///
+/// let mut out = Vec::new();
+/// TreeMatcher::new("fn decode", "&'static str")
+///     .reverse_fn("fn encode")
+///     .canonical(Canonical::Shortest)
+///     .add(b"&lt;", "\"<\"")
+///     .add(b"&LT;", "\"<\"")
+///     .add(b"&lt", "\"<\"")
+///     .render(&mut out)
+///     .unwrap();
+///
+/// assert!(String::from_utf8(out)
+///     .unwrap()
+///     .contains(r#""<" => Some(b"&lt"),"#));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Canonical {
+    /// Return the key that was added first. This is the default.
+    First,
+
+    /// Return the key that was added last.
+    Last,
+
+    /// Return the shortest key. Keys of the same length are chosen in the
+    /// order they were added.
+    Shortest,
+
+    /// Return the longest key. Keys of the same length are chosen in the order
+    /// they were added.
+    Longest,
+}
+
+impl Default for Canonical {
+    fn default() -> Self {
+        Self::First
+    }
+}
+
+impl Canonical {
+    /// Check if a key of length `len` should replace the key of length
+    /// `current` that was chosen earlier.
+    #[must_use]
+    pub const fn replaces(self, current: usize, len: usize) -> bool {
+        match self {
+            Self::First => false,
+            Self::Last => true,
+            Self::Shortest => len < current,
+            Self::Longest => len > current,
+        }
+    }
+}
@@ -0,0 +1,558 @@
+//! Code for [`Element`].
+
+use std::fmt;
+use std::hash::Hash;
+use std::io;
+
+/// A type that a [`TreeNode`][crate::TreeNode] can branch on, i.e. an element
+/// of a key.
+///
+/// This is implemented for:
+///
+///   * `u8`, which is used for most matchers.
+///   * `u16` and `u32`, e.g. for UTF-16 or UTF-32 input. Use
+///     [`TreeMatcher::new_generic()`][crate::TreeMatcher::new_generic] or
+///     [`FlatMatcher::new_generic()`][crate::FlatMatcher::new_generic] to
+///     create a matcher with these keys.
+///   * `char`, which is used with [`Input::Chars`][crate::Input::Chars].
+///   * [`Pattern`], to match elements of any type with Rust patterns.
+///
+/// It can’t be implemented outside of this crate.
+pub trait Element:
+    Clone + Eq + Hash + Ord + fmt::Debug + sealed::Sealed
+{
+}
+
+impl Element for u8 {}
+impl Element for u16 {}
+impl Element for u32 {}
+impl Element for char {}
+impl Element for Pattern {}
+
+/// A Rust pattern that matches a single element of the input, e.g.
+/// `Pattern::from("Tok::Ident")`.
+///
+/// Keys made of patterns can match input of any type, such as a slice of
+/// tokens produced by a lexer. Since the type can’t be determined from the
+/// patterns, it must be set with
+/// [`TreeMatcher::element_type()`][crate::TreeMatcher::element_type] or
+/// [`FlatMatcher::element_type()`][crate::FlatMatcher::element_type].
+///
+/// Patterns are compared as strings, so two patterns that match the same
+/// elements, e.g. `"Tok::A | Tok::B"` and `"Tok::B | Tok::A"`, are treated as
+/// different elements.
+///
+/// The generated code doesn’t backtrack, so patterns that come after the same
+/// prefix in different keys must not overlap. Rendering returns an error if
+/// they might, e.g. for the keys `["Tok::Num(_)", "Tok::RParen"]` and
+/// `["Tok::Num(0)"]`, since `Tok::Num(0)` could match either the first
+/// pattern or the second. Two patterns are known not to overlap if:
+///
+///   * neither has wildcards, bindings, ranges, or alternatives, e.g.
+///     `"Tok::Num(0)"` and `"Tok::Num(1)"`, or
+///   * they start with different paths and have no wildcards, bindings,
+///     ranges, or alternatives outside of brackets, e.g. `"Tok::Num(_)"` and
+///     `"Tok::Ident { .. }"`.
+///
+/// Paths are assumed to name different enum variants or constants with
+/// different values.
+///
+/// A [`Boundary`][crate::Boundary] can’t match patterns, so
+/// [`Boundary::NotFollowedBy`][crate::Boundary::NotFollowedBy] allows any
+/// element to follow a key, and
+/// [`Boundary::FollowedBy`][crate::Boundary::FollowedBy] only allows a key at
+/// the end of the input.
+///
+/// # Example
+///
+/// ```rust
+/// use matchgen::{Pattern, TreeMatcher};
+///
+/// let mut matcher = TreeMatcher::<Pattern>::new_generic("fn rule", "Rule");
+/// matcher
+///     .element_type("Tok")
+///     .add(&["Tok::Ident".into(), "Tok::LParen".into()], "Rule::Call")
+///     .add(&["Tok::Ident".into()], "Rule::Name");
+/// ```
+/// ```rust
+/// use matchgen::{FlatMatcher, Pattern, TreeMatcher};
+///
+/// let keys: [&[Pattern]; 2] = [
+///     &["Tok::Num(_)".into(), "Tok::RParen".into()],
+///     &["Tok::Num(0)".into()],
+/// ];
+///
+/// let mut matcher = TreeMatcher::<Pattern>::new_generic("fn rule", "u8");
+/// matcher.element_type("Tok").add(keys[0], "1").add(keys[1], "2");
+/// assert!(matcher.render(&mut Vec::new()).is_err());
+///
+/// let mut matcher = FlatMatcher::<Pattern>::new_generic("fn rule", "u8");
+/// matcher.element_type("Tok").add(keys[0], "1").add(keys[1], "2");
+/// assert!(matcher.render(&mut Vec::new()).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pattern(pub String);
+
+impl From<&str> for Pattern {
+    fn from(pattern: &str) -> Self {
+        Self(pattern.to_owned())
+    }
+}
+
+impl From<String> for Pattern {
+    fn from(pattern: String) -> Self {
+        Self(pattern)
+    }
+}
+
+/// What [`Sealed::overlaps()`][sealed::Sealed::overlaps] needs to know about
+/// a [`Pattern`].
+struct PatternShape<'a> {
+    /// The path the pattern starts with, e.g. `Tok::Num` for `Tok::Num(_)`, if
+    /// every value the pattern matches starts with it.
+    head: Option<&'a str>,
+
+    /// Whether the pattern matches only one value, i.e. it has no wildcards,
+    /// bindings, ranges, or alternatives.
+    literal: bool,
+}
+
+impl<'a> PatternShape<'a> {
+    /// Scan a pattern. This doesn’t parse Rust, so it can treat patterns that
+    /// don’t overlap as overlapping, but not the other way around.
+    fn new(pattern: &'a str) -> Self {
+        let pattern = pattern.trim();
+        let mut literal = true;
+        // Whether there are wildcards, bindings, ranges, or alternatives
+        // outside of brackets.
+        let mut top_level = false;
+        let mut depth = 0_usize;
+        let mut chars = pattern.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let complex = match c {
+                '"' | '\'' => {
+                    // Skip the rest of the string or char literal.
+                    while let Some((_, d)) = chars.next() {
+                        if d == '\\' {
+                            chars.next();
+                        } else if d == c {
+                            break;
+                        }
+                    }
+                    false
+                }
+                '(' | '[' | '{' => {
+                    depth = depth.saturating_add(1);
+                    false
+                }
+                ')' | ']' | '}' => {
+                    depth = depth.saturating_sub(1);
+                    false
+                }
+                '|' | '@' => true,
+                '.' => chars.next_if(|&(_, d)| d == '.').is_some(),
+                '0'..='9' => {
+                    // Skip the rest of the number, e.g. `0x_ff` or `1u8`.
+                    while chars
+                        .next_if(|&(_, d)| d.is_alphanumeric() || d == '_')
+                        .is_some()
+                    {}
+                    false
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let mut end = pattern.len();
+                    while let Some(&(j, d)) = chars.peek() {
+                        if !(d.is_alphanumeric() || d == '_') {
+                            end = j;
+                            break;
+                        }
+                        chars.next();
+                    }
+                    is_binding(&pattern[..i], &pattern[i..end], &pattern[end..])
+                }
+                _ => false,
+            };
+            if complex {
+                literal = false;
+                if depth == 0 {
+                    top_level = true;
+                }
+            }
+        }
+
+        let head = if top_level {
+            None
+        } else {
+            let end = pattern
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+                .unwrap_or(pattern.len());
+            Some(&pattern[..end]).filter(|head| {
+                head.contains("::")
+                    || head.starts_with(|c: char| c.is_uppercase())
+            })
+        };
+        Self { head, literal }
+    }
+}
+
+/// Check if the identifier `word` in a pattern, between `before` and `after`,
+/// is a wildcard or binds a variable, i.e. if it matches more than one value.
+fn is_binding(before: &str, word: &str, after: &str) -> bool {
+    let after = after.trim_start();
+    if word == "_" {
+        true
+    } else if before.trim_end().ends_with("::")
+        || after.starts_with("::")
+        || word.starts_with(|c: char| c.is_uppercase())
+        || word == "true"
+        || word == "false"
+    {
+        // A path, e.g. a variant or a constant.
+        false
+    } else if matches!(word, "b" | "br" | "r")
+        && after.starts_with(|c| c == '"' || c == '\'' || c == '#')
+    {
+        // The prefix of a literal, e.g. `b'a'`.
+        false
+    } else {
+        // A field name, e.g. `x` in `Point { x: 0, .. }`, is followed by a
+        // colon. Anything else, e.g. a variable, `ref`, or a macro, might
+        // match more than one value.
+        !after.starts_with(':')
+    }
+}
+
+/// Convert a key to bytes so that it can be compared by [`Order`].
+///
+/// See [`Order::Custom`] for how each element type is encoded.
+///
+/// [`Order`]: crate::Order
+/// [`Order::Custom`]: crate::Order::Custom
+pub fn key_bytes<E: Element>(key: &[E]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(key.len());
+    for element in key {
+        element.push_bytes(&mut bytes);
+    }
+    bytes
+}
+
+/// Output bytes as a byte string literal in source code, e.g. `b"if"`.
+pub fn fmt_byte_str(bytes: &[u8]) -> String {
+    let escaped: String = bytes
+        .iter()
+        .flat_map(|&b| std::ascii::escape_default(b))
+        .map(char::from)
+        .collect();
+    format!("b\"{}\"", escaped)
+}
+
+/// Output a key as a slice literal in source code, e.g. `b"if"` for bytes or
+/// `&[0x0069, 0x0066]` for `u16`.
+pub fn fmt_key<E: Element>(key: &[E]) -> String {
+    E::as_bytes(key).map_or_else(
+        || {
+            let elements: Vec<String> =
+                key.iter().map(E::fmt_literal).collect();
+            format!("&[{}]", elements.join(", "))
+        },
+        fmt_byte_str,
+    )
+}
+
+/// Check that the type of elements in generated code is known.
+///
+/// # Errors
+///
+/// Returns [`io::Error`] if `element_type` is empty, which is the default for
+/// [`Pattern`] keys.
+pub fn check_element_type(element_type: &str) -> io::Result<()> {
+    if element_type.is_empty() {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "element_type must be set to use Pattern keys",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Methods used to render elements. These are in a private trait so that they
+/// aren’t part of the public API.
+pub mod sealed {
+    use super::Pattern;
+
+    /// See [`Element`][super::Element].
+    pub trait Sealed {
+        /// The name of the type in generated code, or an empty string if it
+        /// must be set on the matcher.
+        const TYPE: &'static str;
+
+        /// The lifetime parameter needed by [`Self::item()`], including a
+        /// trailing `", "`, or an empty string if it doesn’t need one.
+        const ITEM_LIFETIME: &'static str;
+
+        /// A pattern that matches every value that isn’t handled as a byte, or
+        /// `None` if every value is handled as a byte.
+        const NON_ASCII: Option<&'static str>;
+
+        /// Get the type of the items of iterator input in generated code.
+        fn item(element_type: &str) -> String;
+
+        /// Output the element as a literal in source code.
+        fn fmt_literal(&self) -> String;
+
+        /// Output the element as a pattern in source code.
+        ///
+        /// If `ignore_ascii_case` is set, ASCII letters will be output as a
+        /// pattern that matches both cases, e.g. `b'a' | b'A'`.
+        fn fmt_pattern(&self, ignore_ascii_case: bool) -> String;
+
+        /// Get the element as a byte if it’s handled as a byte by
+        /// [`Boundary`][crate::Boundary].
+        fn to_byte(&self) -> Option<u8>;
+
+        /// Get the element for a byte, if there is one.
+        fn from_byte(b: u8) -> Option<Self>
+        where
+            Self: Sized;
+
+        /// Append the element to `bytes` so that keys can be compared by
+        /// [`Order`][crate::Order].
+        fn push_bytes(&self, bytes: &mut Vec<u8>);
+
+        /// Convert ASCII letters to lowercase.
+        #[must_use]
+        fn to_ascii_lowercase(&self) -> Self;
+
+        /// Get a key as bytes if the element type is `u8`.
+        fn as_bytes(key: &[Self]) -> Option<&[u8]>
+        where
+            Self: Sized;
+
+        /// Check if this element and a different element might both match the
+        /// same input element.
+        fn overlaps(&self, other: &Self) -> bool;
+    }
+
+    impl Sealed for u8 {
+        const TYPE: &'static str = "u8";
+        const ITEM_LIFETIME: &'static str = "'a, ";
+        const NON_ASCII: Option<&'static str> = None;
+
+        fn item(element_type: &str) -> String {
+            format!("&'a {}", element_type)
+        }
+
+        fn fmt_literal(&self) -> String {
+            crate::fmt_byte(*self)
+        }
+
+        fn fmt_pattern(&self, ignore_ascii_case: bool) -> String {
+            crate::fmt_pattern(*self, ignore_ascii_case)
+        }
+
+        fn to_byte(&self) -> Option<u8> {
+            Some(*self)
+        }
+
+        fn from_byte(b: u8) -> Option<Self> {
+            Some(b)
+        }
+
+        fn push_bytes(&self, bytes: &mut Vec<u8>) {
+            bytes.push(*self);
+        }
+
+        fn to_ascii_lowercase(&self) -> Self {
+            Self::to_ascii_lowercase(self)
+        }
+
+        fn as_bytes(key: &[Self]) -> Option<&[u8]> {
+            Some(key)
+        }
+
+        fn overlaps(&self, _other: &Self) -> bool {
+            false
+        }
+    }
+
+    impl Sealed for char {
+        const TYPE: &'static str = "char";
+        const ITEM_LIFETIME: &'static str = "";
+        const NON_ASCII: Option<&'static str> =
+            Some(r"'\u{80}'..='\u{10ffff}'");
+
+        fn item(_element_type: &str) -> String {
+            "char".to_owned()
+        }
+
+        fn fmt_literal(&self) -> String {
+            format!("{:?}", self)
+        }
+
+        fn fmt_pattern(&self, ignore_ascii_case: bool) -> String {
+            if ignore_ascii_case && self.is_ascii_alphabetic() {
+                format!(
+                    "{:?} | {:?}",
+                    Self::to_ascii_lowercase(self),
+                    self.to_ascii_uppercase(),
+                )
+            } else {
+                self.fmt_literal()
+            }
+        }
+
+        fn to_byte(&self) -> Option<u8> {
+            if self.is_ascii() {
+                u8::try_from(u32::from(*self)).ok()
+            } else {
+                None
+            }
+        }
+
+        fn from_byte(b: u8) -> Option<Self> {
+            if b.is_ascii() {
+                Some(Self::from(b))
+            } else {
+                None
+            }
+        }
+
+        fn push_bytes(&self, bytes: &mut Vec<u8>) {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(self.encode_utf8(&mut buffer).as_bytes());
+        }
+
+        fn to_ascii_lowercase(&self) -> Self {
+            Self::to_ascii_lowercase(self)
+        }
+
+        fn as_bytes(_key: &[Self]) -> Option<&[u8]> {
+            None
+        }
+
+        fn overlaps(&self, _other: &Self) -> bool {
+            false
+        }
+    }
+
+    /// Implement [`Sealed`] for a wide integer type.
+    ///
+    /// ASCII values are handled as bytes, and other values are output in
+    /// hexadecimal.
+    macro_rules! impl_wide {
+        ($type:ty, $non_ascii:literal) => {
+            impl Sealed for $type {
+                const TYPE: &'static str = stringify!($type);
+                const ITEM_LIFETIME: &'static str = "'a, ";
+                const NON_ASCII: Option<&'static str> = Some($non_ascii);
+
+                fn item(element_type: &str) -> String {
+                    format!("&'a {}", element_type)
+                }
+
+                fn fmt_literal(&self) -> String {
+                    format!("{:#06x}", self)
+                }
+
+                fn fmt_pattern(&self, ignore_ascii_case: bool) -> String {
+                    match self.to_byte() {
+                        Some(b)
+                            if ignore_ascii_case && b.is_ascii_alphabetic() =>
+                        {
+                            format!(
+                                "{} | {}",
+                                Self::from(b.to_ascii_lowercase())
+                                    .fmt_literal(),
+                                Self::from(b.to_ascii_uppercase())
+                                    .fmt_literal(),
+                            )
+                        }
+                        _ => self.fmt_literal(),
+                    }
+                }
+
+                fn to_byte(&self) -> Option<u8> {
+                    u8::try_from(*self).ok().filter(u8::is_ascii)
+                }
+
+                fn from_byte(b: u8) -> Option<Self> {
+                    if b.is_ascii() {
+                        Some(Self::from(b))
+                    } else {
+                        None
+                    }
+                }
+
+                fn push_bytes(&self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(&self.to_be_bytes());
+                }
+
+                fn to_ascii_lowercase(&self) -> Self {
+                    self.to_byte()
+                        .map_or(*self, |b| Self::from(b.to_ascii_lowercase()))
+                }
+
+                fn as_bytes(_key: &[Self]) -> Option<&[u8]> {
+                    None
+                }
+
+                fn overlaps(&self, _other: &Self) -> bool {
+                    false
+                }
+            }
+        };
+    }
+
+    impl_wide!(u16, "0x0080..=0xffff");
+    impl_wide!(u32, "0x0080..=0xffff_ffff");
+
+    impl Sealed for Pattern {
+        const TYPE: &'static str = "";
+        const ITEM_LIFETIME: &'static str = "'a, ";
+        const NON_ASCII: Option<&'static str> = Some("_");
+
+        fn item(element_type: &str) -> String {
+            format!("&'a {}", element_type)
+        }
+
+        fn fmt_literal(&self) -> String {
+            self.0.clone()
+        }
+
+        fn fmt_pattern(&self, _ignore_ascii_case: bool) -> String {
+            self.fmt_literal()
+        }
+
+        fn to_byte(&self) -> Option<u8> {
+            None
+        }
+
+        fn from_byte(_b: u8) -> Option<Self> {
+            None
+        }
+
+        fn push_bytes(&self, bytes: &mut Vec<u8>) {
+            // Terminate each pattern so that e.g. `["a", "b"]` and `["ab"]`
+            // aren’t encoded the same way.
+            bytes.extend_from_slice(self.0.as_bytes());
+            bytes.push(0);
+        }
+
+        fn to_ascii_lowercase(&self) -> Self {
+            self.clone()
+        }
+
+        fn as_bytes(_key: &[Self]) -> Option<&[u8]> {
+            None
+        }
+
+        fn overlaps(&self, other: &Self) -> bool {
+            let a = super::PatternShape::new(&self.0);
+            let b = super::PatternShape::new(&other.0);
+            let disjoint = (a.literal && b.literal)
+                || matches!((a.head, b.head), (Some(x), Some(y)) if x != y);
+            !disjoint
+        }
+    }
+}
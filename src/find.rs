@@ -0,0 +1,577 @@
+//! Code to render functions that find or replace matches anywhere in the
+//! input, and tokenizers built on them.
+
+use crate::Unmatched;
+use std::io;
+
+/// How a find function calls the matcher function.
+#[derive(Clone, Copy, Debug)]
+pub enum Call {
+    /// The matcher returns `(Option<T>, &[u8])`.
+    Slice,
+
+    /// The matcher returns `(Option<T>, usize)`.
+    Index,
+
+    /// The matcher takes `&mut Iterator` and returns `Option<T>`.
+    Iterator,
+}
+
+/// Renders functions that find matches anywhere in a slice by calling a
+/// matcher at every position where a match might start.
+#[derive(Clone, Debug)]
+pub struct Finder<'a> {
+    /// The name of the matcher function, e.g. `"matcher"`.
+    pub matcher: &'a str,
+
+    /// The return type of the matcher (without the [`Option`]).
+    pub return_type: &'a str,
+
+    /// How to call the matcher.
+    pub call: Call,
+
+    /// A pattern matching the bytes that can start a match, or `None` if a
+    /// match can start anywhere (i.e. there is an empty key).
+    pub first_bytes: Option<String>,
+
+    /// Whether to mark the find function with `#[must_use]`.
+    pub must_use: bool,
+}
+
+impl Finder<'_> {
+    /// Render a function that finds the first match in a slice.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render_find<W: io::Write>(
+        &self,
+        writer: &mut W,
+        fn_name: &str,
+    ) -> io::Result<()> {
+        writeln!(
+            writer,
+            "/// Find the first match of `{}()` in `slice`.\n\
+            ///\n\
+            /// Returns the start and end of the match, and its value.\n\
+            #[allow(clippy::arithmetic_side_effects)]",
+            self.matcher,
+        )?;
+        if self.must_use {
+            writeln!(writer, "#[must_use]")?;
+        }
+
+        let indent = "    "; // Our formatting prevents embedding this.
+        writeln!(
+            writer,
+            "{fn_name}(slice: &[u8]) -> Option<(usize, usize, {return_type})> {{\n\
+            {indent}let mut start = 0;",
+            fn_name = fn_name,
+            return_type = self.return_type,
+            indent = indent,
+        )?;
+        self.render_loop(
+            writer,
+            "    ",
+            &["return Some((start, end, value));"],
+        )?;
+        writeln!(writer, "    None\n}}")
+    }
+
+    /// Render a function that returns an iterator over all non-overlapping
+    /// matches in a slice.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render_find_iter<W: io::Write>(
+        &self,
+        writer: &mut W,
+        fn_name: &str,
+    ) -> io::Result<()> {
+        let indent = "    "; // Our formatting prevents embedding this.
+        writeln!(
+            writer,
+            "/// Find all non-overlapping matches of `{matcher}()` in `slice`.\n\
+            ///\n\
+            /// Yields the start and end of each match, and its value.\n\
+            #[allow(clippy::arithmetic_side_effects)]\n\
+            {fn_name}(\n\
+            {indent}slice: &[u8],\n\
+            ) -> impl core::iter::Iterator<Item = (usize, usize, {return_type})> + '_ {{\n\
+            {indent}let mut start = 0;\n\
+            {indent}core::iter::from_fn(move || {{",
+            matcher = self.matcher,
+            fn_name = fn_name,
+            return_type = self.return_type,
+            indent = indent,
+        )?;
+        self.render_loop(
+            writer,
+            "        ",
+            &[
+                "let found = (start, end, value);",
+                "// Don’t get stuck on an empty match.",
+                "start = if end > start { end } else { end + 1 };",
+                "return Some(found);",
+            ],
+        )?;
+        writeln!(writer, "        None\n    }})\n}}")
+    }
+
+    /// Render a function that replaces every match in a slice with its value,
+    /// returning a [`Cow`][std::borrow::Cow] of bytes.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render_replace<W: io::Write>(
+        &self,
+        writer: &mut W,
+        fn_name: &str,
+    ) -> io::Result<()> {
+        self.render_replace_cow(
+            writer,
+            fn_name,
+            "slice: &[u8]",
+            "[u8]",
+            "Vec<u8>",
+        )
+    }
+
+    /// Render a function that replaces every match in a `&str` with its
+    /// value, returning a [`Cow`][std::borrow::Cow] of `str`.
+    ///
+    /// The keys must all be non-empty UTF-8 so that matches always start and
+    /// end on character boundaries.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render_replace_str<W: io::Write>(
+        &self,
+        writer: &mut W,
+        fn_name: &str,
+    ) -> io::Result<()> {
+        self.render_replace_cow(writer, fn_name, "s: &str", "str", "String")
+    }
+
+    /// Render a function that replaces every match in a slice with its value,
+    /// returning a [`Cow`][std::borrow::Cow] of `target`.
+    fn render_replace_cow<W: io::Write>(
+        &self,
+        writer: &mut W,
+        fn_name: &str,
+        parameter: &str,
+        target: &str,
+        owned: &str,
+    ) -> io::Result<()> {
+        let indent = "    "; // Our formatting prevents embedding this.
+        let (input, push, constructor) = if target == "str" {
+            ("s", "push_str", "String")
+        } else {
+            ("slice", "extend_from_slice", "Vec")
+        };
+
+        writeln!(
+            writer,
+            "/// Replace every match of `{}()` in `{}` with its value.\n\
+            ///\n\
+            /// Returns the input unchanged if nothing matches.\n\
+            #[allow(clippy::arithmetic_side_effects, clippy::useless_asref)]",
+            self.matcher, input,
+        )?;
+        if self.must_use {
+            writeln!(writer, "#[must_use]")?;
+        }
+        writeln!(
+            writer,
+            "{fn_name}({parameter}) -> std::borrow::Cow<'_, {target}> {{",
+            fn_name = fn_name,
+            parameter = parameter,
+            target = target,
+        )?;
+        if target == "str" {
+            writeln!(writer, "{}let slice = s.as_bytes();", indent)?;
+        }
+        writeln!(
+            writer,
+            "{indent}let mut out: Option<{owned}> = None;\n\
+            {indent}let mut copied = 0;\n\
+            {indent}let mut start = 0;",
+            indent = indent,
+            owned = owned,
+        )?;
+        self.render_loop(
+            writer,
+            indent,
+            &[
+                "let buffer = out",
+                &format!(
+                    "    .get_or_insert_with(|| {}::with_capacity(slice.len()));",
+                    constructor,
+                ),
+                &format!("buffer.{}(&{}[copied..start]);", push, input),
+                &format!("buffer.{}(value.as_ref());", push),
+                "copied = end;",
+                "// Don’t get stuck on an empty match.",
+                "start = if end > start { end } else { end + 1 };",
+                "continue;",
+            ],
+        )?;
+        writeln!(
+            writer,
+            "{indent}if let Some(mut out) = out {{\n\
+            {indent}    out.{push}(&{input}[copied..]);\n\
+            {indent}    std::borrow::Cow::Owned(out)\n\
+            {indent}}} else {{\n\
+            {indent}    std::borrow::Cow::Borrowed({input})\n\
+            {indent}}}\n\
+            }}",
+            indent = indent,
+            push = push,
+            input = input,
+        )
+    }
+
+    /// Render a function that writes a slice to an [`io::Write`] with every
+    /// match replaced with its value.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render_replace_write<W: io::Write>(
+        &self,
+        writer: &mut W,
+        fn_name: &str,
+    ) -> io::Result<()> {
+        let indent = "    "; // Our formatting prevents embedding this.
+        writeln!(
+            writer,
+            "/// Write `slice` to `writer` with every match of `{matcher}()`\n\
+            /// replaced with its value.\n\
+            ///\n\
+            /// # Errors\n\
+            ///\n\
+            /// Returns any error from `writer`.\n\
+            #[allow(clippy::arithmetic_side_effects, clippy::useless_asref)]\n\
+            {fn_name}<W: std::io::Write>(\n\
+            {indent}slice: &[u8],\n\
+            {indent}writer: &mut W,\n\
+            ) -> std::io::Result<()> {{\n\
+            {indent}let mut copied = 0;\n\
+            {indent}let mut start = 0;",
+            matcher = self.matcher,
+            fn_name = fn_name,
+            indent = indent,
+        )?;
+        self.render_loop(
+            writer,
+            indent,
+            &[
+                "writer.write_all(&slice[copied..start])?;",
+                "writer.write_all(value.as_ref())?;",
+                "copied = end;",
+                "// Don’t get stuck on an empty match.",
+                "start = if end > start { end } else { end + 1 };",
+                "continue;",
+            ],
+        )?;
+        writeln!(writer, "{}writer.write_all(&slice[copied..])\n}}", indent)
+    }
+
+    /// Render a struct that wraps a slice and iterates over the tokens in it.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render_tokenizer<W: io::Write>(
+        &self,
+        writer: &mut W,
+        struct_name: &str,
+        unmatched: &Unmatched,
+    ) -> io::Result<()> {
+        let name = fn_ident(struct_name);
+        let indent = "    "; // Our formatting prevents embedding this.
+        writeln!(
+            writer,
+            "/// Split a slice into tokens with `{matcher}()`.\n\
+            ///\n\
+            /// Yields each token with its span in the slice.",
+            matcher = self.matcher,
+        )?;
+        match unmatched {
+            Unmatched::Skip => writeln!(
+                writer,
+                "/// Bytes that don’t start a match are skipped.",
+            )?,
+            Unmatched::Token(token) => writeln!(
+                writer,
+                "/// Runs of bytes that don’t start a match are yielded as\n\
+                /// `{}`.",
+                token,
+            )?,
+            Unmatched::Fallback(fallback) => writeln!(
+                writer,
+                "/// If nothing matches, the token is found by `{}()`.",
+                fallback,
+            )?,
+        }
+        writeln!(
+            writer,
+            "#[derive(Clone, Debug)]\n\
+            {struct_name}<'a> {{\n\
+            {indent}/// The input.\n\
+            {indent}slice: &'a [u8],\n\
+            \n\
+            {indent}/// The offset of the next token.\n\
+            {indent}start: usize,\n\
+            }}\n\
+            \n\
+            impl<'a> {name}<'a> {{\n\
+            {indent}/// Create a tokenizer for `slice`.\n\
+            {indent}#[must_use]\n\
+            {indent}pub const fn new(slice: &'a [u8]) -> Self {{\n\
+            {indent}{indent}Self {{ slice, start: 0 }}\n\
+            {indent}}}\n\
+            }}\n\
+            \n\
+            impl Iterator for {name}<'_> {{\n\
+            {indent}type Item = ({return_type}, core::ops::Range<usize>);\n\
+            \n\
+            {indent}#[allow(clippy::arithmetic_side_effects)]\n\
+            {indent}fn next(&mut self) -> Option<Self::Item> {{\n\
+            {indent}{indent}let slice = self.slice;",
+            struct_name = struct_name,
+            name = name,
+            return_type = self.return_type,
+            indent = indent,
+        )?;
+        self.render_tokenizer_next(writer, unmatched)?;
+        writeln!(
+            writer,
+            "    }}\n\
+            }}\n\
+            \n\
+            impl core::iter::FusedIterator for {}<'_> {{}}",
+            name,
+        )
+    }
+
+    /// Render the body of the tokenizer’s `next()` method after `slice` is
+    /// set.
+    fn render_tokenizer_next<W: io::Write>(
+        &self,
+        writer: &mut W,
+        unmatched: &Unmatched,
+    ) -> io::Result<()> {
+        let indent = "        "; // Our formatting prevents embedding this.
+        match unmatched {
+            Unmatched::Skip => {
+                writeln!(writer, "{}let mut start = self.start;", indent)?;
+                self.render_loop(
+                    writer,
+                    indent,
+                    &["self.start = end;", "return Some((value, start..end));"],
+                )?;
+                writeln!(
+                    writer,
+                    "{indent}self.start = slice.len();\n\
+                    {indent}None",
+                    indent = indent,
+                )
+            }
+            Unmatched::Token(token) => {
+                writeln!(
+                    writer,
+                    "{indent}let first = self.start;\n\
+                    {indent}let mut start = first;",
+                    indent = indent,
+                )?;
+                self.render_loop(
+                    writer,
+                    indent,
+                    &[
+                        "if start == first {",
+                        "    self.start = end;",
+                        "    return Some((value, start..end));",
+                        "}",
+                        "// Yield the unmatched bytes before this match first.",
+                        "self.start = start;",
+                        &format!("return Some(({}, first..start));", token),
+                    ],
+                )?;
+                writeln!(
+                    writer,
+                    "{indent}self.start = slice.len();\n\
+                    {indent}if first < slice.len() {{\n\
+                    {indent}    Some(({token}, first..slice.len()))\n\
+                    {indent}}} else {{\n\
+                    {indent}    None\n\
+                    {indent}}}",
+                    indent = indent,
+                    token = token,
+                )
+            }
+            Unmatched::Fallback(fallback) => {
+                writeln!(
+                    writer,
+                    "{indent}let start = self.start;\n\
+                    {indent}if start >= slice.len() {{\n\
+                    {indent}    return None;\n\
+                    {indent}}}",
+                    indent = indent,
+                )?;
+                self.render_call(
+                    writer,
+                    indent,
+                    &["self.start = end;", "return Some((value, start..end));"],
+                )?;
+                writeln!(
+                    writer,
+                    "{indent}let (value, len) = {fallback}(&slice[start..]);\n\
+                    {indent}// Always make progress.\n\
+                    {indent}let end = slice.len().min(start + len.max(1));\n\
+                    {indent}self.start = end;\n\
+                    {indent}Some((value, start..end))",
+                    indent = indent,
+                    fallback = fallback,
+                )
+            }
+        }
+    }
+
+    /// Render a loop that tries to match starting at `start` and every
+    /// position after it. `found` is run for a match, with `start`, `end`, and
+    /// `value` set.
+    fn render_loop<W: io::Write>(
+        &self,
+        writer: &mut W,
+        indent: &str,
+        found: &[&str],
+    ) -> io::Result<()> {
+        if let Some(first_bytes) = &self.first_bytes {
+            // Skip to the next byte that could start a match.
+            writeln!(
+                writer,
+                "{indent}while let Some(offset) = slice[start..]\n\
+                {indent}    .iter()\n\
+                {indent}    .position(|&b| matches!(b, {first_bytes}))\n\
+                {indent}{{\n\
+                {indent}    start += offset;",
+                indent = indent,
+                first_bytes = first_bytes,
+            )?;
+        } else {
+            writeln!(writer, "{}while start <= slice.len() {{", indent)?;
+        }
+
+        self.render_call(writer, &format!("{}    ", indent), found)?;
+
+        writeln!(
+            writer,
+            "{indent}    start += 1;\n\
+            {indent}}}",
+            indent = indent,
+        )
+    }
+
+    /// Render a call to the matcher at `start`. `found` is run for a match,
+    /// with `start`, `end`, and `value` set.
+    fn render_call<W: io::Write>(
+        &self,
+        writer: &mut W,
+        indent: &str,
+        found: &[&str],
+    ) -> io::Result<()> {
+        match self.call {
+            Call::Slice => writeln!(
+                writer,
+                "{indent}if let (Some(value), rest) = {matcher}(&slice[start..]) {{\n\
+                {indent}    let end = slice.len() - rest.len();",
+                indent = indent,
+                matcher = self.matcher,
+            )?,
+            Call::Index => writeln!(
+                writer,
+                "{indent}if let (Some(value), len) = {matcher}(&slice[start..]) {{\n\
+                {indent}    let end = start + len;",
+                indent = indent,
+                matcher = self.matcher,
+            )?,
+            Call::Iterator => writeln!(
+                writer,
+                "{indent}let mut iter = slice[start..].iter();\n\
+                {indent}if let Some(value) = {matcher}(&mut iter) {{\n\
+                {indent}    let end = slice.len() - iter.as_slice().len();",
+                indent = indent,
+                matcher = self.matcher,
+            )?,
+        }
+
+        for line in found {
+            writeln!(writer, "{}    {}", indent, line)?;
+        }
+
+        writeln!(writer, "{}}}", indent)
+    }
+}
+
+/// Get the name of a function or struct from the first part of its
+/// definition, e.g. `"matcher"` from `"pub fn matcher"`.
+pub fn fn_ident(fn_name: &str) -> &str {
+    fn_name
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or(fn_name)
+}
+
+/// Return an error for find or replace functions that can’t be generated.
+pub fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "find and replace functions and tokenizers require Anchor::Prefix and \
+        byte input, and can’t be used with Semantics::All",
+    )
+}
+
+/// Check that keys can be used in a tokenizer.
+///
+/// # Errors
+///
+/// Returns [`io::Error`] if any key is empty, since the tokenizer would never
+/// advance past it.
+pub fn check_tokenizer_keys<'a, I>(mut keys: I) -> io::Result<()>
+where
+    I: Iterator<Item = &'a [u8]>,
+{
+    if keys.any(<[u8]>::is_empty) {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "tokenizer_struct() requires all keys to be non-empty",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Check that keys can be used in a function that replaces matches in a
+/// `&str`.
+///
+/// # Errors
+///
+/// Returns [`io::Error`] if any key is empty or isn’t valid UTF-8, since it
+/// might match in the middle of a character.
+pub fn check_str_keys<'a, I>(mut keys: I) -> io::Result<()>
+where
+    I: Iterator<Item = &'a [u8]>,
+{
+    if keys.any(|key| key.is_empty() || std::str::from_utf8(key).is_err()) {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "replace_str_fn() requires all keys to be non-empty UTF-8",
+        ))
+    } else {
+        Ok(())
+    }
+}
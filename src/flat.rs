@@ -1,7 +1,14 @@
 //! Code for the [`FlatMatcher`].
 
-use std::cmp;
-use std::collections::HashMap;
+use crate::element::{check_element_type, key_bytes};
+use crate::find::{self, Call, Finder};
+use crate::hoist::Hoisted;
+use crate::incomplete::{incomplete_pattern, Incomplete};
+use crate::reverse::Reverse;
+use crate::tables::Tables;
+use crate::{
+    Anchor, Boundary, Canonical, Element, Order, Semantics, TreeNode, Unmatched,
+};
 use std::env;
 use std::fmt;
 use std::fs;
@@ -19,6 +26,9 @@ use std::path::Path;
 ///      * The remainder of the slice (`&[u8]`).
 ///      * The index of the next unmatched byte (`usize`).
 ///
+/// If [`Self::anchor()`] is set to [`Anchor::Exact`], it will only return the
+/// match, if any.
+///
 /// See [`Self::return_index()`] if you need a `const fn` matcher.
 ///
 /// For example, suppose you generate a [matcher for all HTML entities][htmlize]
@@ -29,12 +39,15 @@ use std::path::Path;
 /// ```
 ///
 ///   * The prefixes it checks do not all have to be the same length.
-///   * If more than one prefix matches, it will return the longest one.
+///   * If more than one prefix matches, it will return the longest one (see
+///     [`Self::semantics()`]).
 ///   * If nothing matches, it will return `(None, &input)`.
 ///
 /// Since the matchers only check the start of the input, you will want to
 /// use [`iter().position()`] or the [memchr crate][memchr] to find the
-/// start of a potential match.
+/// start of a potential match, or generate a function that does that with
+/// [`Self::find_fn()`] or [`Self::find_iter_fn()`]. To replace every match
+/// with its value, see [`Self::replace_fn()`].
 ///
 /// # Example build script
 ///
@@ -76,12 +89,17 @@ use std::path::Path;
 /// }
 /// ```
 ///
+/// Keys are bytes by default, but may also be `u16` or `u32`, in which case the
+/// generated function accepts `&[u16]` or `&[u32]`. See
+/// [`Self::new_generic()`].
+///
 /// [build script]: https://doc.rust-lang.org/cargo/reference/build-scripts.html
 /// [`iter().position()`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.position
 /// [memchr]: http://docs.rs/memchr
 /// [htmlize]: https://crates.io/crates/htmlize
 #[derive(Clone, Debug)]
-pub struct FlatMatcher {
+#[allow(clippy::struct_excessive_bools)] // These are independent settings.
+pub struct FlatMatcher<E: Element = u8> {
     /// The first part of the function definition to generate, e.g.
     /// `"pub fn matcher"`.
     pub fn_name: String,
@@ -89,6 +107,13 @@ pub struct FlatMatcher {
     /// The return type (will be wrapped in [`Option`]), e.g. `"&'static str"`.
     pub return_type: String,
 
+    /// The type of the elements of the input in generated code, e.g. `"u16"`.
+    /// Defaults to the name of `E`, or an empty string for
+    /// [`Pattern`][crate::Pattern] keys.
+    ///
+    /// See [`Self::element_type()`].
+    pub element_type: String,
+
     /// Whether to return the remainder as a slice or an index.
     ///
     /// If `false`, the remainder will be returned as the index of the next
@@ -98,6 +123,39 @@ pub struct FlatMatcher {
     /// Defaults to `true`.
     pub return_slice: bool,
 
+    /// Where keys must be found in the input. Defaults to [`Anchor::Prefix`].
+    ///
+    /// See [`Self::anchor()`].
+    pub anchor: Anchor,
+
+    /// Which key to match if more than one matches. Defaults to
+    /// [`Semantics::Longest`].
+    ///
+    /// See [`Self::semantics()`].
+    pub semantics: Semantics,
+
+    /// Which bytes may follow a key. Defaults to [`Boundary::Any`].
+    ///
+    /// See [`Self::boundary()`].
+    pub boundary: Boundary,
+
+    /// The first part of the definition of an enum for a three-state result,
+    /// e.g. `"pub enum EntityMatch"`. Defaults to `None`.
+    ///
+    /// See [`Self::incomplete_enum()`].
+    pub incomplete_enum: Option<String>,
+
+    /// Whether to match keys regardless of ASCII case. Defaults to `false`.
+    ///
+    /// See [`Self::ignore_ascii_case()`].
+    pub ignore_ascii_case: bool,
+
+    /// Whether to output values that would be repeated in the generated code
+    /// once, as constants. Defaults to `false`.
+    ///
+    /// See [`Self::hoist_values()`].
+    pub hoist_values: bool,
+
     /// Whether to prevent Clippy from evaluating the generated code. Defaults
     /// to `false`.
     ///
@@ -107,6 +165,9 @@ pub struct FlatMatcher {
     /// Whether to mark the function with [`#[must_use]`][must_use]. Defaults to
     /// `true`.
     ///
+    /// This is ignored with [`Semantics::All`], since the function doesn’t
+    /// return anything.
+    ///
     /// [must_use]: https://doc.rust-lang.org/reference/attributes/diagnostics.html#the-must_use-attribute
     pub must_use: bool,
 
@@ -115,8 +176,80 @@ pub struct FlatMatcher {
     /// Should not have a trailing newline.
     pub doc: Option<String>,
 
-    /// The arms of the match statement.
-    pub arms: HashMap<Vec<u8>, String>,
+    /// The first part of the definition of a function to find the first match
+    /// anywhere in a slice, e.g. `"pub fn find"`. Defaults to `None`.
+    ///
+    /// See [`Self::find_fn()`].
+    pub find_fn: Option<String>,
+
+    /// The first part of the definition of a function to find all matches in a
+    /// slice, e.g. `"pub fn find_iter"`. Defaults to `None`.
+    ///
+    /// See [`Self::find_iter_fn()`].
+    pub find_iter_fn: Option<String>,
+
+    /// The first part of the definition of a function to replace every match
+    /// in a slice with its value, e.g. `"pub fn replace"`. Defaults to `None`.
+    ///
+    /// See [`Self::replace_fn()`].
+    pub replace_fn: Option<String>,
+
+    /// The first part of the definition of a function to replace every match
+    /// in a `&str` with its value, e.g. `"pub fn replace_str"`. Defaults to
+    /// `None`.
+    ///
+    /// See [`Self::replace_str_fn()`].
+    pub replace_str_fn: Option<String>,
+
+    /// The first part of the definition of a function to write a slice to an
+    /// [`io::Write`] with every match replaced with its value, e.g.
+    /// `"pub fn replace_write"`. Defaults to `None`.
+    ///
+    /// See [`Self::replace_write_fn()`].
+    pub replace_write_fn: Option<String>,
+
+    /// The first part of the definition of a tokenizer struct, e.g.
+    /// `"pub struct Lexer"`. Defaults to `None`.
+    ///
+    /// See [`Self::tokenizer_struct()`].
+    pub tokenizer_struct: Option<String>,
+
+    /// What the tokenizer does with bytes that don’t start a match. Defaults
+    /// to [`Unmatched::Skip`].
+    pub unmatched: Unmatched,
+
+    /// The first part of the definition of a function to map each value back
+    /// to its canonical key, e.g. `"pub fn encode"`. Defaults to `None`.
+    ///
+    /// See [`Self::reverse_fn()`].
+    pub reverse_fn: Option<String>,
+
+    /// Which key the reverse function returns when several keys have the same
+    /// value. Defaults to [`Canonical::First`].
+    pub canonical: Canonical,
+
+    /// Patterns to match values with in the reverse function, for values that
+    /// aren’t valid patterns.
+    ///
+    /// See [`Self::value_pattern()`].
+    pub value_patterns: Vec<(String, String)>,
+
+    /// The first part of the definitions of constants that list the keys and
+    /// values, e.g. `"pub const ENTITY_"`. Defaults to `None`.
+    ///
+    /// See [`Self::tables()`].
+    pub tables: Option<String>,
+
+    /// The order of arms with keys of the same length. Defaults to
+    /// [`Order::Bytes`].
+    ///
+    /// Longer keys are always output first so that they take precedence.
+    pub order: Order,
+
+    /// The arms of the match statement, in the order they were added.
+    ///
+    /// If a key appears more than once, the last value is used.
+    pub arms: Vec<(Vec<E>, String)>,
 }
 
 impl FlatMatcher {
@@ -127,8 +260,47 @@ impl FlatMatcher {
     /// [`Self::extend()`], then turn it into code with [`Self::render()`].
     ///
     /// See the [struct documentation][FlatMatcher] for a complete example.
-    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
     pub fn new<N, R>(fn_name: N, return_type: R) -> Self
+    where
+        N: ToString,
+        R: ToString,
+    {
+        Self::new_generic(fn_name, return_type)
+    }
+}
+
+impl<E: Element> FlatMatcher<E> {
+    /// Create a new matcher with keys made of `E`, e.g. `u16`.
+    ///
+    /// This works like [`FlatMatcher::new()`], but the generated function
+    /// accepts `&[E]`, e.g. `&[u16]` for UTF-16. Find and replace functions
+    /// and tokenizers (e.g. [`Self::find_fn()`]) require `u8`.
+    ///
+    /// ```rust
+    /// use matchgen::FlatMatcher;
+    ///
+    /// let mut out = Vec::new();
+    /// FlatMatcher::<u16>::new_generic("fn match_utf16", "u64")
+    ///     .add(&[0x41, 0x3a9], "1")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[must_use]
+    /// fn match_utf16(slice: &[u16]) -> (Option<u64>, &[u16]) {
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
+    ///     match slice {
+    ///         [0x0041, 0x03a9, ..] => (Some(1), &slice[2..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn new_generic<N, R>(fn_name: N, return_type: R) -> Self
     where
         N: ToString,
         R: ToString,
@@ -136,11 +308,30 @@ impl FlatMatcher {
         Self {
             fn_name: fn_name.to_string(),
             return_type: return_type.to_string(),
+            element_type: E::TYPE.to_owned(),
             return_slice: true,
+            anchor: Anchor::default(),
+            semantics: Semantics::default(),
+            boundary: Boundary::default(),
+            incomplete_enum: None,
+            ignore_ascii_case: false,
+            hoist_values: false,
             disable_clippy: false,
             must_use: true,
             doc: None,
-            arms: HashMap::default(),
+            find_fn: None,
+            find_iter_fn: None,
+            replace_fn: None,
+            replace_str_fn: None,
+            replace_write_fn: None,
+            tokenizer_struct: None,
+            unmatched: Unmatched::default(),
+            reverse_fn: None,
+            canonical: Canonical::default(),
+            value_patterns: Vec::new(),
+            tables: None,
+            order: Order::default(),
+            arms: Vec::new(),
         }
     }
 
@@ -152,11 +343,52 @@ impl FlatMatcher {
     /// ```
     pub fn add<'a, K, V>(&mut self, key: K, value: V) -> &mut Self
     where
-        K: IntoIterator<Item = &'a u8>,
+        K: IntoIterator<Item = &'a E>,
+        E: 'a,
         V: Into<String>,
     {
         self.arms
-            .insert(key.into_iter().copied().collect(), value.into());
+            .push((key.into_iter().cloned().collect(), value.into()));
+        self
+    }
+
+    /// Set the order of arms with keys of the same length.
+    ///
+    /// Longer keys are always output first so that they take precedence over
+    /// shorter keys. This sets the order of keys with the same length, which
+    /// doesn’t change what the generated function matches, but does ensure
+    /// that the generated code is the same every time. See [`Order`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::FlatMatcher::new("fn match_bytes", "u64")
+    ///     .order(matchgen::Order::Insertion)
+    ///     .add(b"b", "1")
+    ///     .add(b"ab", "2")
+    ///     .add(b"a", "3")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
+    ///     match slice {
+    ///         [b'a', b'b', ..] => (Some(2), &slice[2..]),
+    ///         [b'b', ..] => (Some(1), &slice[1..]),
+    ///         [b'a', ..] => (Some(3), &slice[1..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    pub fn order(&mut self, order: Order) -> &mut Self {
+        self.order = order;
         self
     }
 
@@ -198,6 +430,277 @@ impl FlatMatcher {
         self
     }
 
+    /// Set the type of the elements of the input in generated code.
+    ///
+    /// This must be set for keys made of [`Pattern`][crate::Pattern]s, which
+    /// can match input of any type. The generated function will accept
+    /// `&[{element_type}]`.
+    /// See [`TreeMatcher::element_type()`][crate::TreeMatcher::element_type]
+    /// for an example.
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn element_type<N: ToString>(&mut self, element_type: N) -> &mut Self {
+        self.element_type = element_type.to_string();
+        self
+    }
+
+    /// Set where keys must be found in the input.
+    ///
+    /// This can be either:
+    ///
+    ///   * [`Anchor::Prefix`] to match keys at the start of the input
+    ///   * [`Anchor::Exact`] to match only if the entire input is a key
+    ///   * [`Anchor::Suffix`] to match keys at the end of the input
+    ///
+    /// With [`Anchor::Exact`], the generated function only returns the match
+    /// (`Option<{return_type}>`). With [`Anchor::Suffix`], it returns the part
+    /// of the input before the match, or its length if [`Self::return_index()`]
+    /// is set. Neither needs to slice the input, so the generated function will
+    /// always be `const`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::FlatMatcher::new("fn match_bytes", "u64")
+    ///     .anchor(matchgen::Anchor::Exact)
+    ///     .add(b"a", "1")
+    ///     .add(b"abc", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[must_use]
+    /// const fn match_bytes(slice: &[u8]) -> Option<u64> {
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
+    ///     match slice {
+    ///         [b'a', b'b', b'c'] => Some(2),
+    ///         [b'a'] => Some(1),
+    ///         _ => None,
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    pub fn anchor(&mut self, anchor: Anchor) -> &mut Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Set which key to match if more than one matches.
+    ///
+    /// See [`Semantics`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::FlatMatcher::new("fn match_bytes", "u64")
+    ///     .semantics(matchgen::Semantics::Priority)
+    ///     .add(b"ab", "1")
+    ///     .add(b"a", "2")
+    ///     .add(b"abc", "3")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
+    ///     match slice {
+    ///         [b'a', b'b', ..] => (Some(1), &slice[2..]),
+    ///         [b'a', ..] => (Some(2), &slice[1..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    pub fn semantics(&mut self, semantics: Semantics) -> &mut Self {
+        self.semantics = semantics;
+        self
+    }
+
+    /// Set which bytes may follow a key for it to match.
+    ///
+    /// See [`Boundary`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::FlatMatcher::new("fn match_bytes", "u64")
+    ///     .boundary(matchgen::Boundary::word())
+    ///     .add(b"in", "1")
+    ///     .add(b"int", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
+    ///     match slice {
+    ///         [b'i', b'n', b't', after @ ..] if !matches!(after, [b'0'..=b'9' | b'A'..=b'Z' | b'_' | b'a'..=b'z', ..]) => (Some(2), &slice[3..]),
+    ///         [b'i', b'n', after @ ..] if !matches!(after, [b'0'..=b'9' | b'A'..=b'Z' | b'_' | b'a'..=b'z', ..]) => (Some(1), &slice[2..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    pub fn boundary(&mut self, boundary: Boundary) -> &mut Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Make the generated function report when the input ends inside a
+    /// possible key, e.g. `b"&am"` with the key `b"&amp;"`, so that callers can
+    /// wait for more input.
+    ///
+    /// `enum_name` is the first part of the definition of an enum to generate
+    /// with the function, e.g. `"pub enum EntityMatch"`. The function returns
+    /// `EntityMatch<'_, {return_type}>`, which is one of:
+    ///
+    ///   * `Matched(value, rest)` if a key matched, with the rest of the input.
+    ///   * `NoMatch` if no key matches, no matter what input follows.
+    ///   * `Incomplete(len)` if more input is needed to tell. `len` is the
+    ///     number of elements seen, i.e. the length of the input.
+    ///
+    /// The input is incomplete if it’s a strict prefix of a key that could
+    /// match, or if it ends with a key that must be checked against
+    /// [`Self::boundary()`]. This is true even if a shorter key matches, since
+    /// a longer key might match once more input arrives. At the end of the
+    /// input, use a matcher generated without this option to get the final
+    /// result.
+    ///
+    /// If you generate more than one function with this option in the same
+    /// module, give each enum a different name.
+    ///
+    /// This requires [`Anchor::Prefix`], and can’t be used with
+    /// [`Semantics::All`] or with find and replace functions and tokenizers
+    /// (e.g. [`Self::find_fn()`]).
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::FlatMatcher;
+    ///
+    /// let mut out = Vec::new();
+    /// FlatMatcher::new("fn match_bytes", "u64")
+    ///     .incomplete_enum("enum Partial")
+    ///     .add(b"<", "1")
+    ///     .add(b"<=", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"/// The result of `match_bytes()`.
+    /// #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    /// enum Partial<'a, T> {
+    ///     /// A key matched. Contains its value and the rest of the input.
+    ///     Matched(T, &'a [u8]),
+    ///
+    ///     /// No key matches the start of the input.
+    ///     NoMatch,
+    ///
+    ///     /// The input ended inside a possible key, so more input is needed
+    ///     /// to tell if it matches. Contains the number of elements seen.
+    ///     Incomplete(usize),
+    /// }
+    ///
+    /// #[allow(
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::unnested_or_patterns,
+    /// )]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> Partial<'_, u64> {
+    ///     #[must_use]
+    ///     fn matcher(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///         #[allow(unreachable_patterns, clippy::match_same_arms)]
+    ///         match slice {
+    ///             [b'<', b'=', ..] => (Some(2), &slice[2..]),
+    ///             [b'<', ..] => (Some(1), &slice[1..]),
+    ///             _ => (None, slice),
+    ///         }
+    ///     }
+    ///
+    ///     if matches!(slice, [] | [b'<']) {
+    ///         return Partial::Incomplete(slice.len());
+    ///     }
+    ///     match matcher(slice) {
+    ///         (Some(value), rest) => Partial::Matched(value, rest),
+    ///         (None, _) => Partial::NoMatch,
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn incomplete_enum<N: ToString>(&mut self, enum_name: N) -> &mut Self {
+        self.incomplete_enum = Some(enum_name.to_string());
+        self
+    }
+
+    /// Set whether to match keys regardless of ASCII case.
+    ///
+    /// If set to `true`, ASCII letters in keys will match either their upper or
+    /// lower case versions. Keys that differ only by ASCII case are treated as
+    /// the same key, so the value of the last one added is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::FlatMatcher::new("fn match_bytes", "u64")
+    ///     .ignore_ascii_case(true)
+    ///     .add(b"ab", "1")
+    ///     .add(b"AB", "2")
+    ///     .add(b"a-", "3")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
+    ///     match slice {
+    ///         [b'a' | b'A', b'-', ..] => (Some(3), &slice[2..]),
+    ///         [b'a' | b'A', b'b' | b'B', ..] => (Some(2), &slice[2..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    pub fn ignore_ascii_case(&mut self, ignore: bool) -> &mut Self {
+        self.ignore_ascii_case = ignore;
+        self
+    }
+
+    /// Set whether to output values that would be repeated in the generated
+    /// code once, as constants.
+    ///
+    /// Values of more than one key are declared once at the start of the
+    /// function, e.g. `const V_0: &'static str = "<";`, and the arms refer to
+    /// the constants. This makes the output smaller when many keys share long
+    /// values. Values must be constant expressions.
+    ///
+    /// Defaults to `false`.
+    pub fn hoist_values(&mut self, hoist: bool) -> &mut Self {
+        self.hoist_values = hoist;
+        self
+    }
+
     /// Set whether or not to prevent [Clippy] from evaluating the generated
     /// code.
     ///
@@ -231,7 +734,7 @@ impl FlatMatcher {
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
     ///     r#"fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
-    ///     #[allow(unreachable_patterns)]
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
     ///     match slice {
     ///         [b'a', ..] => (Some(1), &slice[1..]),
     ///         _ => (None, slice),
@@ -266,7 +769,7 @@ impl FlatMatcher {
     /// pretty_assertions::assert_str_eq!(
     ///     r#"#[must_use]
     /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
-    ///     #[allow(unreachable_patterns)]
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
     ///     match slice {
     ///         [b'a', ..] => (Some(1), &slice[1..]),
     ///         _ => (None, slice),
@@ -309,7 +812,7 @@ impl FlatMatcher {
     ///     r#"#[doc = "Match the first bytes of a slice.\n\n        Matches only the string `\"a\"` and returns `1`."]
     /// #[must_use]
     /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
-    ///     #[allow(unreachable_patterns)]
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
     ///     match slice {
     ///         [b'a', ..] => (Some(1), &slice[1..]),
     ///         _ => (None, slice),
@@ -349,7 +852,7 @@ impl FlatMatcher {
     ///     r#"#[doc = include_str!("match_bytes.md")]
     /// #[must_use]
     /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
-    ///     #[allow(unreachable_patterns)]
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
     ///     match slice {
     ///         [b'a', ..] => (Some(1), &slice[1..]),
     ///         _ => (None, slice),
@@ -388,7 +891,7 @@ impl FlatMatcher {
     ///     r#"#[doc(hidden)]
     /// #[must_use]
     /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
-    ///     #[allow(unreachable_patterns)]
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
     ///     match slice {
     ///         _ => (None, slice),
     ///     }
@@ -404,6 +907,294 @@ impl FlatMatcher {
         self
     }
 
+    /// Also generate a function that finds the first match anywhere in a slice.
+    ///
+    /// The generated function accepts `&[u8]` and returns the start and end of
+    /// the leftmost match, and its value, i.e.
+    /// `Option<(usize, usize, {return_type})>`. It calls the matcher at each
+    /// byte that could start a match.
+    ///
+    /// This requires [`Anchor::Prefix`], and can’t be used with
+    /// [`Semantics::All`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::FlatMatcher::new("fn match_bytes", "u64")
+    ///     .return_index()
+    ///     .find_fn("fn find")
+    ///     .add(b"a", "1")
+    ///     .add(b"bc", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[must_use]
+    /// const fn match_bytes(slice: &[u8]) -> (Option<u64>, usize) {
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
+    ///     match slice {
+    ///         [b'b', b'c', ..] => (Some(2), 2),
+    ///         [b'a', ..] => (Some(1), 1),
+    ///         _ => (None, 0),
+    ///     }
+    /// }
+    ///
+    /// /// Find the first match of `match_bytes()` in `slice`.
+    /// ///
+    /// /// Returns the start and end of the match, and its value.
+    /// #[allow(clippy::arithmetic_side_effects)]
+    /// #[must_use]
+    /// fn find(slice: &[u8]) -> Option<(usize, usize, u64)> {
+    ///     let mut start = 0;
+    ///     while let Some(offset) = slice[start..]
+    ///         .iter()
+    ///         .position(|&b| matches!(b, b'a' | b'b'))
+    ///     {
+    ///         start += offset;
+    ///         if let (Some(value), len) = match_bytes(&slice[start..]) {
+    ///             let end = start + len;
+    ///             return Some((start, end, value));
+    ///         }
+    ///         start += 1;
+    ///     }
+    ///     None
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn find_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.find_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Also generate a function that finds all matches in a slice.
+    ///
+    /// The generated function accepts `&[u8]` and returns an iterator over
+    /// the start and end of every non-overlapping match, and its value, i.e.
+    /// `impl Iterator<Item = (usize, usize, {return_type})>`. Like the
+    /// matcher, it prefers the leftmost match, then the one chosen by
+    /// [`Self::semantics()`].
+    ///
+    /// This requires [`Anchor::Prefix`], and can’t be used with
+    /// [`Semantics::All`].
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn find_iter_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.find_iter_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Also generate a function that replaces every match in a slice with its
+    /// value.
+    ///
+    /// The generated function accepts `&[u8]` and returns
+    /// `Cow<'_, [u8]>`. It replaces the same matches that
+    /// [`Self::find_iter_fn()`] would find, and returns the input borrowed if
+    /// there are none. Values must implement `AsRef<[u8]>`, so `return_type`
+    /// will typically be `&'static str` or `&'static [u8]`.
+    ///
+    /// This requires [`Anchor::Prefix`], and can’t be used with
+    /// [`Semantics::All`].
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn replace_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.replace_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Also generate a function that replaces every match in a `&str` with its
+    /// value.
+    ///
+    /// This is like [`Self::replace_fn()`], except that the generated function
+    /// accepts `&str` and returns `Cow<'_, str>`. Values must implement
+    /// `AsRef<str>`.
+    ///
+    /// All keys must be non-empty UTF-8, so that a match can’t start or end in
+    /// the middle of a character.
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn replace_str_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.replace_str_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Also generate a function that writes a slice to an [`io::Write`] with
+    /// every match replaced with its value.
+    ///
+    /// This is like [`Self::replace_fn()`], except that the generated function
+    /// is generic over `W: std::io::Write`, accepts `(&[u8], &mut W)`, and
+    /// returns `std::io::Result<()>`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::FlatMatcher::new("fn match_bytes", "&'static [u8]")
+    ///     .return_index()
+    ///     .replace_write_fn("fn decode")
+    ///     .add(b"&lt;", "b\"<\"")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[must_use]
+    /// const fn match_bytes(slice: &[u8]) -> (Option<&'static [u8]>, usize) {
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
+    ///     match slice {
+    ///         [b'&', b'l', b't', b';', ..] => (Some(b"<"), 4),
+    ///         _ => (None, 0),
+    ///     }
+    /// }
+    ///
+    /// /// Write `slice` to `writer` with every match of `match_bytes()`
+    /// /// replaced with its value.
+    /// ///
+    /// /// # Errors
+    /// ///
+    /// /// Returns any error from `writer`.
+    /// #[allow(clippy::arithmetic_side_effects, clippy::useless_asref)]
+    /// fn decode<W: std::io::Write>(
+    ///     slice: &[u8],
+    ///     writer: &mut W,
+    /// ) -> std::io::Result<()> {
+    ///     let mut copied = 0;
+    ///     let mut start = 0;
+    ///     while let Some(offset) = slice[start..]
+    ///         .iter()
+    ///         .position(|&b| matches!(b, b'&'))
+    ///     {
+    ///         start += offset;
+    ///         if let (Some(value), len) = match_bytes(&slice[start..]) {
+    ///             let end = start + len;
+    ///             writer.write_all(&slice[copied..start])?;
+    ///             writer.write_all(value.as_ref())?;
+    ///             copied = end;
+    ///             // Don’t get stuck on an empty match.
+    ///             start = if end > start { end } else { end + 1 };
+    ///             continue;
+    ///         }
+    ///         start += 1;
+    ///     }
+    ///     writer.write_all(&slice[copied..])
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn replace_write_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.replace_write_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Also generate a tokenizer struct that splits a slice into tokens.
+    ///
+    /// `struct_name` is the first part of the definition, e.g.
+    /// `"pub struct Lexer"`. The struct wraps a `&[u8]` and implements
+    /// `Iterator<Item = ({return_type}, Range<usize>)>`, yielding each token
+    /// with its span in the slice. It calls the matcher repeatedly, and
+    /// handles bytes that don’t start a match as configured by
+    /// [`Self::unmatched()`].
+    ///
+    /// All keys must be non-empty. This requires [`Anchor::Prefix`], and can’t
+    /// be used with [`Semantics::All`].
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn tokenizer_struct<N: ToString>(
+        &mut self,
+        struct_name: N,
+    ) -> &mut Self {
+        self.tokenizer_struct = Some(struct_name.to_string());
+        self
+    }
+
+    /// Set what the tokenizer does with bytes that don’t start a match.
+    /// Defaults to [`Unmatched::Skip`].
+    ///
+    /// See [`Self::tokenizer_struct()`].
+    pub fn unmatched(&mut self, unmatched: Unmatched) -> &mut Self {
+        self.unmatched = unmatched;
+        self
+    }
+
+    /// Also generate a function that maps each value back to its canonical key.
+    ///
+    /// The generated function accepts `{return_type}` and returns the key for
+    /// the value as `Option<&'static [{element_type}]>`, or `None` if no key
+    /// has the value. This is useful to pair a decoder with an encoder, e.g.
+    /// to re-encode HTML entities.
+    ///
+    /// Each value is used as a pattern in a `match` statement, so values must
+    /// be valid patterns, such as literals or paths to enum variants. Use
+    /// [`Self::value_pattern()`] to supply a pattern for a value that isn’t
+    /// one. If several keys have the same value, [`Self::canonical()`] chooses
+    /// which one to return.
+    ///
+    /// This can’t be used with [`Pattern`][crate::Pattern] keys.
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn reverse_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.reverse_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Set which key the reverse function returns when several keys have the
+    /// same value. Defaults to [`Canonical::First`].
+    ///
+    /// See [`Self::reverse_fn()`].
+    pub fn canonical(&mut self, canonical: Canonical) -> &mut Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Set the pattern the reverse function uses to match a value.
+    ///
+    /// By default each value is used as its own pattern, which doesn’t work
+    /// for values that are expressions but not patterns, e.g. function calls.
+    ///
+    /// ```rust
+    /// let mut matcher = matchgen::FlatMatcher::new("fn matcher", "u32");
+    /// matcher
+    ///     .reverse_fn("fn reverse")
+    ///     .add(b"&lt;", "u32::from(b'<')")
+    ///     .value_pattern("u32::from(b'<')", "0x3c");
+    /// ```
+    ///
+    /// See [`Self::reverse_fn()`].
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn value_pattern<V: ToString, P: ToString>(
+        &mut self,
+        value: V,
+        pattern: P,
+    ) -> &mut Self {
+        self.value_patterns
+            .push((value.to_string(), pattern.to_string()));
+        self
+    }
+
+    /// Also generate constants that list the keys and values.
+    ///
+    /// `prefix` is the first part of each definition, e.g. `"pub const "` or
+    /// `"pub const ENTITY_"`. It’s followed by:
+    ///
+    ///   * `KEYS`, a `&[&[{element_type}]]` of every key.
+    ///   * `ENTRIES`, a `&[(&[{element_type}], {return_type})]` of every key
+    ///     and its value. Values must be constant expressions.
+    ///   * `MIN_KEY_LEN` and `MAX_KEY_LEN`, the lengths of the shortest and
+    ///     longest keys as `usize`, or 0 if there are no keys.
+    ///
+    /// This is useful to iterate over the keys, e.g. for autocompletion or
+    /// tests, or to size a lookahead buffer. Keys are listed as they were
+    /// added, in the order set by [`Self::order()`]. If a key was added more
+    /// than once, only the last instance is listed.
+    ///
+    /// This can’t be used with [`Pattern`][crate::Pattern] keys.
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn tables<N: ToString>(&mut self, prefix: N) -> &mut Self {
+        self.tables = Some(prefix.to_string());
+        self
+    }
+
     /// Write the matcher as a Rust source file in `$OUT_DIR`.
     ///
     /// This is what you want if you’re using this in `build.rs` as intended.
@@ -487,7 +1278,7 @@ impl FlatMatcher {
     ///     r#"#[cfg(not(clippy))]
     /// #[must_use]
     /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
-    ///     #[allow(unreachable_patterns)]
+    ///     #[allow(unreachable_patterns, clippy::match_same_arms)]
     ///     match slice {
     ///         [b'a', ..] => (Some(1), &slice[1..]),
     ///         _ => (None, slice),
@@ -506,8 +1297,31 @@ impl FlatMatcher {
     ///
     /// # Errors
     ///
-    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    /// This can return [`io::Error`] if there is a problem writing to `writer`,
+    /// or if [`Self::find_fn()`], [`Self::replace_fn()`],
+    /// [`Self::tokenizer_struct()`], [`Self::incomplete_enum()`], or a related
+    /// function is set with incompatible settings, or if
+    /// [`Pattern`][crate::Pattern] keys might overlap.
     pub fn render<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        check_element_type(&self.element_type)?;
+        self.tree().check_overlap()?;
+        let finder = self.finder()?;
+        if let Some(enum_name) = &self.incomplete_enum {
+            if self.anchor != Anchor::Prefix
+                || self.visits_all()
+                || finder.is_some()
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "incomplete_enum() requires Anchor::Prefix, and can’t be \
+                    used with Semantics::All or find and replace functions and \
+                    tokenizers",
+                ));
+            }
+            self.incomplete(enum_name).render_enum(writer)?;
+            writeln!(writer)?;
+        }
+
         if self.disable_clippy {
             writeln!(writer, "#[cfg(not(clippy))]")?;
         }
@@ -520,9 +1334,142 @@ impl FlatMatcher {
             self.render_stub(writer)?;
         }
 
+        if let Some(finder) = finder {
+            if let Some(fn_name) = &self.find_fn {
+                writeln!(writer)?;
+                finder.render_find(writer, fn_name)?;
+            }
+
+            if let Some(fn_name) = &self.find_iter_fn {
+                writeln!(writer)?;
+                finder.render_find_iter(writer, fn_name)?;
+            }
+
+            if let Some(fn_name) = &self.replace_fn {
+                writeln!(writer)?;
+                finder.render_replace(writer, fn_name)?;
+            }
+
+            if let Some(fn_name) = &self.replace_str_fn {
+                writeln!(writer)?;
+                finder.render_replace_str(writer, fn_name)?;
+            }
+
+            if let Some(fn_name) = &self.replace_write_fn {
+                writeln!(writer)?;
+                finder.render_replace_write(writer, fn_name)?;
+            }
+
+            if let Some(struct_name) = &self.tokenizer_struct {
+                writeln!(writer)?;
+                finder.render_tokenizer(
+                    writer,
+                    struct_name,
+                    &self.unmatched,
+                )?;
+            }
+        }
+
+        if let Some(fn_name) = &self.reverse_fn {
+            writeln!(writer)?;
+            self.reverse().render(
+                writer,
+                fn_name,
+                &self.arms,
+                self.ignore_ascii_case,
+            )?;
+        }
+
+        if let Some(prefix) = &self.tables {
+            writeln!(writer)?;
+            self.key_tables().render(
+                writer,
+                prefix,
+                &self.arms,
+                self.ignore_ascii_case,
+            )?;
+        }
+
         Ok(())
     }
 
+    /// Get the renderer for find and replace functions and tokenizers, if they
+    /// were requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::Error`] if find or replace functions or tokenizers can’t
+    /// be generated with the current settings.
+    fn finder(&self) -> io::Result<Option<Finder<'_>>> {
+        if self.find_fn.is_none()
+            && self.find_iter_fn.is_none()
+            && self.replace_fn.is_none()
+            && self.replace_str_fn.is_none()
+            && self.replace_write_fn.is_none()
+            && self.tokenizer_struct.is_none()
+        {
+            return Ok(None);
+        }
+
+        let keys: Option<Vec<&[u8]>> =
+            self.arms.iter().map(|(key, _)| E::as_bytes(key)).collect();
+        let keys = match keys {
+            Some(keys)
+                if self.anchor == Anchor::Prefix && !self.visits_all() =>
+            {
+                keys
+            }
+            _ => return Err(find::unsupported()),
+        };
+
+        if self.replace_str_fn.is_some() {
+            find::check_str_keys(keys.iter().copied())?;
+        }
+
+        if self.tokenizer_struct.is_some() {
+            find::check_tokenizer_keys(keys.iter().copied())?;
+        }
+
+        let entries = self.entries();
+        let mut first_bytes: Vec<E> = Vec::new();
+        for (key, _) in &entries {
+            if let Some(b) = key.first() {
+                if !first_bytes.contains(b) {
+                    first_bytes.push(b.clone());
+                }
+            }
+        }
+        first_bytes.sort_by(|a, b| {
+            self.compare(std::slice::from_ref(a), std::slice::from_ref(b))
+        });
+
+        let first_bytes = if first_bytes.is_empty()
+            || entries.iter().any(|(key, _)| key.is_empty())
+        {
+            None
+        } else {
+            Some(
+                first_bytes
+                    .iter()
+                    .map(|b| b.fmt_pattern(self.ignore_ascii_case))
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+            )
+        };
+
+        Ok(Some(Finder {
+            matcher: find::fn_ident(&self.fn_name),
+            return_type: &self.return_type,
+            call: if self.return_slice {
+                Call::Slice
+            } else {
+                Call::Index
+            },
+            first_bytes,
+            must_use: self.must_use,
+        }))
+    }
+
     /// Render the function that does the matching.
     ///
     /// Generally you should use [`Self::render()`] instead of this.
@@ -533,52 +1480,182 @@ impl FlatMatcher {
     pub fn render_func<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
         let indent = "    "; // Our formatting prevents embedding this.
 
-        self.render_fn_start(writer, "slice")?;
+        if self.visits_all() {
+            return self.render_all(writer);
+        } else if let Some(enum_name) = &self.incomplete_enum {
+            return self.render_incomplete(writer, enum_name);
+        }
+
+        let entries = self.entries();
+        let hoisted = self.hoisted(&entries);
+        self.render_fn_start(writer, "slice", &hoisted)?;
 
         write!(
             writer,
-            "{indent}#[allow(unreachable_patterns)]\n\
+            "{indent}#[allow(unreachable_patterns, clippy::match_same_arms)]\n\
             {indent}match slice {{\n",
             indent = indent,
         )?;
 
-        // Output entries in longest to shortest order.
-        let mut entries: Vec<_> = self.arms.iter().collect();
-        entries.sort_by_key(|(key, _)| cmp::Reverse(key.len()));
+        // Bytes that may not follow a key; see `Self::boundary()`.
+        let forbidden = if self.anchor == Anchor::Exact {
+            None
+        } else {
+            self.boundary.forbidden_pattern::<E>(self.ignore_ascii_case)
+        };
+
         for (key, value) in entries {
+            let value = hoisted.replace(value);
             let count = key.len();
-            writeln!(
-                writer,
-                "{indent}    [{prefix}..] => (Some({value}), {remainder}),",
-                indent = indent,
-                prefix = if count == 0 {
-                    String::new()
-                } else {
-                    key.iter()
-                        .map(|&b| crate::fmt_byte(b) + ", ")
-                        .collect::<String>()
-                },
-                value = value,
-                remainder = if self.return_slice {
-                    format!("&slice[{}..]", count)
-                } else {
-                    count.to_string()
-                },
-            )?;
+            let patterns =
+                key.iter().map(|b| b.fmt_pattern(self.ignore_ascii_case));
+
+            if self.anchor == Anchor::Exact {
+                writeln!(
+                    writer,
+                    "{indent}    [{patterns}] => Some({value}),",
+                    indent = indent,
+                    patterns = patterns.collect::<Vec<_>>().join(", "),
+                    value = value,
+                )?;
+            } else if self.anchor == Anchor::Suffix {
+                // Keys are reversed; see `Self::entries()`.
+                let mut patterns: Vec<_> = patterns.collect();
+                patterns.reverse();
+                patterns.insert(0, "before @ ..".to_owned());
+                writeln!(
+                    writer,
+                    "{indent}    {pattern}{guard} => (Some({value}), {before}),",
+                    indent = indent,
+                    pattern = fmt_slice_pattern(&patterns),
+                    guard = forbidden.as_ref().map_or_else(String::new, |f| {
+                        format!(" if !matches!(before, [.., {}])", f)
+                    }),
+                    value = value,
+                    before = if self.return_slice {
+                        "before"
+                    } else {
+                        "before.len()"
+                    },
+                )?;
+            } else {
+                let (rest, guard) = forbidden.as_ref().map_or_else(
+                    || ("..", String::new()),
+                    |f| {
+                        (
+                            "after @ ..",
+                            format!(" if !matches!(after, [{}, ..])", f),
+                        )
+                    },
+                );
+                let mut patterns: Vec<_> = patterns.collect();
+                patterns.push(rest.to_owned());
+                writeln!(
+                    writer,
+                    "{indent}    {pattern}{guard} => (Some({value}), {remainder}),",
+                    indent = indent,
+                    pattern = fmt_slice_pattern(&patterns),
+                    guard = guard,
+                    value = value,
+                    remainder = if self.return_slice {
+                        format!("&slice[{}..]", count)
+                    } else {
+                        count.to_string()
+                    },
+                )?;
+            }
         }
 
         write!(
             writer,
-            "{indent}    _ => (None, {remainder}),\n\
+            "{indent}    _ => {no_match},\n\
             {indent}}}\n\
             }}\n",
             indent = indent,
-            remainder = if self.return_slice { "slice" } else { "0" },
+            no_match = self.no_match(),
         )?;
 
         Ok(())
     }
 
+    /// Render the function that calls a visitor for every key that matches.
+    /// See [`Semantics::All`].
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_all<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let entries = self.entries();
+        let hoisted = self.hoisted(&entries);
+        self.render_fn_start(
+            writer,
+            if entries.is_empty() { "_" } else { "slice" },
+            &hoisted,
+        )?;
+
+        let suffix = self.anchor == Anchor::Suffix;
+        let forbidden =
+            self.boundary.forbidden_pattern::<E>(self.ignore_ascii_case);
+        for (key, value) in entries {
+            let value = hoisted.replace(value);
+            // The part of the input the boundary applies to, if any.
+            let (rest, boundary) = if suffix {
+                ("before", forbidden.as_ref().map(|f| format!("[.., {}]", f)))
+            } else {
+                ("after", forbidden.as_ref().map(|f| format!("[{}, ..]", f)))
+            };
+
+            let mut indent = "    ".to_owned();
+            let mut closing = Vec::new();
+            if !key.is_empty() {
+                let mut patterns: Vec<_> = key
+                    .iter()
+                    .map(|b| b.fmt_pattern(self.ignore_ascii_case))
+                    .collect();
+                let binding = if boundary.is_some() {
+                    format!("{} @ ..", rest)
+                } else {
+                    "..".to_owned()
+                };
+                if suffix {
+                    // Keys are reversed; see `Self::entries()`.
+                    patterns.reverse();
+                    patterns.insert(0, binding);
+                } else {
+                    patterns.push(binding);
+                }
+
+                writeln!(
+                    writer,
+                    "{}if let [{}] = slice {{",
+                    indent,
+                    patterns.join(", ")
+                )?;
+                closing.push(indent.clone());
+                indent.push_str("    ");
+            }
+
+            if let Some(boundary) = boundary {
+                writeln!(
+                    writer,
+                    "{}if !matches!({}, {}) {{",
+                    indent,
+                    if key.is_empty() { "slice" } else { rest },
+                    boundary,
+                )?;
+                closing.push(indent.clone());
+                indent.push_str("    ");
+            }
+
+            writeln!(writer, "{}visit({}, {});", indent, value, key.len())?;
+            for indent in closing.iter().rev() {
+                writeln!(writer, "{}}}", indent)?;
+            }
+        }
+
+        writeln!(writer, "}}")
+    }
+
     /// Render a stub that does nothing.
     ///
     /// Generally you should use [`Self::render()`] instead of this.
@@ -589,27 +1666,200 @@ impl FlatMatcher {
     pub fn render_stub<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
         let indent = "    "; // Our formatting prevents embedding this.
 
+        if self.visits_all() {
+            self.render_fn_start(writer, "_", &Hoisted::default())?;
+            return writeln!(writer, "}}");
+        } else if let Some(enum_name) = &self.incomplete_enum {
+            writeln!(writer, "#[allow(clippy::missing_const_for_fn)]")?;
+            self.render_attributes(writer)?;
+            return self.incomplete(enum_name).render_stub(writer);
+        }
+
         self.render_fn_start(
             writer,
-            if self.return_slice { "slice" } else { "_" },
+            match self.anchor {
+                Anchor::Prefix if self.return_slice => "slice",
+                Anchor::Suffix => "slice",
+                _ => "_",
+            },
+            &Hoisted::default(),
         )?;
 
         write!(
             writer,
-            "{indent}(None, {remainder})\n\
+            "{indent}{no_match}\n\
             }}\n",
             indent = indent,
-            remainder = if self.return_slice { "slice" } else { "0" },
+            no_match = self.no_match(),
         )?;
 
         Ok(())
     }
 
+    /// Render a function that wraps the matcher and reports when the input
+    /// ends inside a possible key. See [`Self::incomplete_enum()`].
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_incomplete<W: io::Write>(
+        &self,
+        writer: &mut W,
+        enum_name: &str,
+    ) -> io::Result<()> {
+        let mut matcher = self.clone();
+        matcher.fn_name = "fn matcher".to_owned();
+        matcher.incomplete_enum = None;
+        matcher.doc = None;
+        matcher.must_use = true;
+
+        let mut inner = Vec::new();
+        matcher.render_func(&mut inner)?;
+
+        if !self.disable_clippy {
+            writeln!(
+                writer,
+                "#[allow(\n    \
+                clippy::missing_const_for_fn,\n    \
+                clippy::unnested_or_patterns,\n\
+                )]",
+            )?;
+        }
+        self.render_attributes(writer)?;
+        let pattern = incomplete_pattern(
+            &self.tree(),
+            &self.boundary,
+            self.ignore_ascii_case,
+        );
+        self.incomplete(enum_name).render_fn(
+            writer,
+            &inner,
+            !self.return_slice,
+            pattern.as_deref(),
+        )
+    }
+
+    /// Get the renderer for a function that maps values back to keys.
+    fn reverse(&self) -> Reverse<'_> {
+        Reverse {
+            matcher: find::fn_ident(&self.fn_name),
+            return_type: &self.return_type,
+            element_type: &self.element_type,
+            canonical: self.canonical,
+            value_patterns: &self.value_patterns,
+            order: self.order,
+            must_use: self.must_use,
+        }
+    }
+
+    /// Get the renderer for constants that list the keys and values.
+    fn key_tables(&self) -> Tables<'_> {
+        Tables {
+            matcher: find::fn_ident(&self.fn_name),
+            return_type: &self.return_type,
+            element_type: &self.element_type,
+            order: self.order,
+        }
+    }
+
+    /// Get the renderer for a function that reports incomplete input.
+    fn incomplete<'a>(&'a self, enum_name: &'a str) -> Incomplete<'a> {
+        Incomplete {
+            enum_name,
+            fn_name: &self.fn_name,
+            return_type: &self.return_type,
+            element_type: &self.element_type,
+        }
+    }
+
+    /// Get the value the generated function returns when nothing matches.
+    const fn no_match(&self) -> &'static str {
+        match self.anchor {
+            Anchor::Exact => "None",
+            _ if self.return_slice => "(None, slice)",
+            Anchor::Prefix => "(None, 0)",
+            Anchor::Suffix => "(None, slice.len())",
+        }
+    }
+
+    /// Get the arms to output, without duplicates or keys that can’t match, in
+    /// the order they should be output: longest to shortest (or shortest to
+    /// longest with [`Semantics::All`]), then by [`Self::order`].
+    ///
+    /// With [`Anchor::Suffix`], the keys will be reversed.
+    fn entries(&self) -> Vec<(Vec<E>, &str)> {
+        let mut entries =
+            crate::normalize_entries(&self.arms, self.ignore_ascii_case);
+        if self.anchor == Anchor::Suffix {
+            // Suffixes are matched from the end of the input, so reverse them
+            // to find which ones are suffixes of others.
+            for (key, _) in &mut entries {
+                key.reverse();
+            }
+        }
+
+        if self.anchor != Anchor::Exact {
+            entries = self.semantics.filter(
+                entries,
+                &self.boundary,
+                self.ignore_ascii_case,
+            );
+        }
+
+        if self.visits_all() {
+            // Visit matches from shortest to longest.
+            entries.sort_by(|(a, _), (b, _)| {
+                a.len().cmp(&b.len()).then_with(|| self.compare(a, b))
+            });
+        } else {
+            entries.sort_by(|(a, _), (b, _)| {
+                b.len().cmp(&a.len()).then_with(|| self.compare(a, b))
+            });
+        }
+        entries
+    }
+
+    /// Build a tree of the keys from [`Self::entries()`], with branches sorted
+    /// by [`Self::order`].
+    fn tree(&self) -> TreeNode<E> {
+        let mut root: TreeNode<E> = self
+            .entries()
+            .iter()
+            .map(|(key, value)| (key, *value))
+            .collect();
+        root.sort(self.order);
+        root
+    }
+
+    /// Get the values of `entries` to declare as constants, if any. See
+    /// [`Self::hoist_values()`].
+    fn hoisted(&self, entries: &[(Vec<E>, &str)]) -> Hoisted {
+        if self.hoist_values {
+            Hoisted::new(entries.iter().map(|(_, value)| *value))
+        } else {
+            Hoisted::default()
+        }
+    }
+
+    /// Compare two keys according to [`Self::order`].
+    fn compare(&self, a: &[E], b: &[E]) -> std::cmp::Ordering {
+        self.order.compare(&key_bytes(a), &key_bytes(b))
+    }
+
+    /// Check if the generated function should call a visitor for every match.
+    /// See [`Semantics::All`].
+    fn visits_all(&self) -> bool {
+        self.semantics == Semantics::All && self.anchor != Anchor::Exact
+    }
+
     /// Get the function name with it modifiers.
     ///
     /// This will add `const` in the appropriate place if necessary.
     fn fn_name(&self) -> String {
-        if self.return_slice {
+        if self.visits_all()
+            || (self.return_slice && self.anchor == Anchor::Prefix)
+        {
+            // Calling a closure isn’t allowed in a `const fn`.
             self.fn_name.clone()
         } else if self.fn_name.starts_with("fn ") {
             format!("const {}", self.fn_name)
@@ -624,35 +1874,83 @@ impl FlatMatcher {
     /// # Errors
     ///
     /// This can return [`io::Error`] if there is a problem writing to `writer`.
-    fn render_fn_start<W: io::Write>(
+    fn render_attributes<W: io::Write>(
         &self,
         writer: &mut W,
-        parameter: &str,
     ) -> io::Result<()> {
         if let Some(doc) = &self.doc {
             writeln!(writer, "{}", doc)?;
         }
 
-        if self.must_use {
+        if self.must_use && !self.visits_all() {
             writeln!(writer, "#[must_use]")?;
         }
 
-        writeln!(
-            writer,
-            "{fn_name}({parameter}: &[u8]) -> (Option<{return_type}>, {remainder_type}) {{",
-            fn_name = self.fn_name(),
-            parameter = parameter,
-            return_type = &self.return_type,
-            remainder_type = if self.return_slice { "&[u8]" } else { "usize" },
-        )?;
-
         Ok(())
     }
+
+    /// Render attributes and the signature for the function or stub, followed
+    /// by constants for the `hoisted` values.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_fn_start<W: io::Write>(
+        &self,
+        writer: &mut W,
+        parameter: &str,
+        hoisted: &Hoisted,
+    ) -> io::Result<()> {
+        self.render_attributes(writer)?;
+
+        if self.visits_all() {
+            let indent = "    "; // Our formatting prevents embedding this.
+            write!(
+                writer,
+                "{fn_name}<F>({parameter}: &[{element}], {visit}: F)\n\
+                where\n\
+                {indent}F: FnMut({return_type}, usize),\n\
+                {{\n",
+                fn_name = self.fn_name(),
+                element = self.element_type,
+                parameter = parameter,
+                visit = if parameter == "_" { "_" } else { "mut visit" },
+                return_type = &self.return_type,
+                indent = indent,
+            )?;
+        } else if self.anchor == Anchor::Exact {
+            writeln!(
+                writer,
+                "{fn_name}({parameter}: &[{element}]) -> Option<{return_type}> {{",
+                fn_name = self.fn_name(),
+                element = self.element_type,
+                parameter = parameter,
+                return_type = &self.return_type,
+            )?;
+        } else {
+            writeln!(
+                writer,
+                "{fn_name}({parameter}: &[{element}]) -> (Option<{return_type}>, {remainder_type}) {{",
+                fn_name = self.fn_name(),
+                element = self.element_type,
+                parameter = parameter,
+                return_type = &self.return_type,
+                remainder_type = if self.return_slice {
+                    format!("&[{}]", self.element_type)
+                } else {
+                    "usize".to_owned()
+                },
+            )?;
+        }
+
+        hoisted.render(writer, &self.return_type)
+    }
 }
 
-impl<'a, K, V> Extend<(K, V)> for FlatMatcher
+impl<'a, E, K, V> Extend<(K, V)> for FlatMatcher<E>
 where
-    K: IntoIterator<Item = &'a u8>,
+    E: Element + 'a,
+    K: IntoIterator<Item = &'a E>,
     V: Into<String>,
 {
     fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
@@ -661,3 +1959,16 @@ where
         });
     }
 }
+
+/// Output a slice pattern, e.g. `[b'a', after @ ..]`.
+///
+/// A pattern that only binds the whole slice is output as just the binding,
+/// e.g. `after`, since clippy considers `[after @ ..]` redundant.
+fn fmt_slice_pattern(patterns: &[String]) -> String {
+    match patterns {
+        [only] if only.ends_with(" @ ..") => {
+            only.trim_end_matches(" @ ..").to_owned()
+        }
+        _ => format!("[{}]", patterns.join(", ")),
+    }
+}
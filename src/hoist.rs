@@ -0,0 +1,87 @@
+//! Code for [`TreeMatcher::hoist_values()`] and
+//! [`FlatMatcher::hoist_values()`].
+//!
+//! [`TreeMatcher::hoist_values()`]: crate::TreeMatcher::hoist_values
+//! [`FlatMatcher::hoist_values()`]: crate::FlatMatcher::hoist_values
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io;
+
+/// Values to declare once as constants.
+#[derive(Clone, Debug, Default)]
+pub struct Hoisted {
+    /// The values, in the order they first appear.
+    values: Vec<String>,
+
+    /// The index of each value in `values`.
+    indexes: HashMap<String, usize>,
+}
+
+impl Hoisted {
+    /// Find values that appear more than once, in the order they first appear.
+    pub fn new<'a, I: IntoIterator<Item = &'a str>>(values: I) -> Self {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        let mut order = Vec::new();
+        for value in values {
+            let count = counts.entry(value).or_insert(0);
+            if *count == 0 {
+                order.push(value);
+            }
+            *count = count.saturating_add(1);
+        }
+
+        let values: Vec<String> = order
+            .into_iter()
+            .filter(|value| counts[value] > 1)
+            .map(str::to_owned)
+            .collect();
+        let indexes = values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| (value.clone(), i))
+            .collect();
+        Self { values, indexes }
+    }
+
+    /// Get the name of the constant for `value` if it was hoisted, or `value`
+    /// otherwise.
+    pub fn replace<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        self.indexes
+            .get(value)
+            .map_or(Cow::Borrowed(value), |&i| Cow::Owned(const_name(i)))
+    }
+
+    /// Render the constants, to go at the start of the body of a function.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render<W: io::Write>(
+        &self,
+        writer: &mut W,
+        return_type: &str,
+    ) -> io::Result<()> {
+        for (i, value) in self.values.iter().enumerate() {
+            if return_type.contains("'static") {
+                writeln!(
+                    writer,
+                    "    #[allow(clippy::redundant_static_lifetimes)]",
+                )?;
+            }
+            writeln!(
+                writer,
+                "    const {}: {} = {};",
+                const_name(i),
+                return_type,
+                value,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Get the name of the constant for the `i`th hoisted value.
+fn const_name(i: usize) -> String {
+    format!("V_{}", i)
+}
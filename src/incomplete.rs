@@ -0,0 +1,179 @@
+//! Code for [`TreeMatcher::incomplete_enum()`] and
+//! [`FlatMatcher::incomplete_enum()`].
+//!
+//! [`TreeMatcher::incomplete_enum()`]: crate::TreeMatcher::incomplete_enum
+//! [`FlatMatcher::incomplete_enum()`]: crate::FlatMatcher::incomplete_enum
+
+use crate::find::fn_ident;
+use crate::{Boundary, Element, TreeNode};
+use std::io;
+
+/// Settings for a function that reports when the input ends inside a possible
+/// key.
+#[derive(Clone, Debug)]
+pub struct Incomplete<'a> {
+    /// The first part of the definition of the result enum, e.g.
+    /// `"pub enum EntityMatch"`.
+    pub enum_name: &'a str,
+
+    /// The first part of the function definition, e.g. `"pub fn matcher"`.
+    pub fn_name: &'a str,
+
+    /// The type of values, e.g. `"&'static str"`.
+    pub return_type: &'a str,
+
+    /// The type of the elements of the input, e.g. `"u8"`.
+    pub element_type: &'a str,
+}
+
+impl Incomplete<'_> {
+    /// Render the result enum.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render_enum<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let indent = "    "; // Our formatting prevents embedding this.
+        writeln!(
+            writer,
+            "/// The result of `{matcher}()`.\n\
+            #[derive(Clone, Copy, Debug, PartialEq, Eq)]\n\
+            {enum_name}<'a, T> {{\n\
+            {indent}/// A key matched. Contains its value and the rest of the input.\n\
+            {indent}Matched(T, &'a [{element_type}]),\n\
+            \n\
+            {indent}/// No key matches the start of the input.\n\
+            {indent}NoMatch,\n\
+            \n\
+            {indent}/// The input ended inside a possible key, so more input is needed\n\
+            {indent}/// to tell if it matches. Contains the number of elements seen.\n\
+            {indent}Incomplete(usize),\n\
+            }}",
+            matcher = fn_ident(self.fn_name),
+            enum_name = self.enum_name,
+            element_type = self.element_type,
+            indent = indent,
+        )
+    }
+
+    /// Render the body of the function, starting with its signature.
+    ///
+    /// `inner` is the rendered matcher, which must be named `matcher`. If
+    /// `index` is set, it returns the length of the match rather than the rest
+    /// of the input. `pattern` matches input that ends inside a possible key;
+    /// see [`incomplete_pattern()`].
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render_fn<W: io::Write>(
+        &self,
+        writer: &mut W,
+        inner: &[u8],
+        index: bool,
+        pattern: Option<&str>,
+    ) -> io::Result<()> {
+        let indent = "    "; // Our formatting prevents embedding this.
+        writeln!(writer, "{} {{", self.signature("slice"))?;
+        crate::write_indented(writer, inner)?;
+        writeln!(writer)?;
+
+        let result = fn_ident(self.enum_name);
+        if let Some(pattern) = pattern {
+            writeln!(
+                writer,
+                "{indent}if matches!(slice, {pattern}) {{\n\
+                {indent}{indent}return {result}::Incomplete(slice.len());\n\
+                {indent}}}",
+                indent = indent,
+                pattern = pattern,
+                result = result,
+            )?;
+        }
+        writeln!(
+            writer,
+            "{indent}match matcher(slice) {{\n\
+            {indent}{indent}(Some(value), {rest}) => {result}::Matched(value, {remainder}),\n\
+            {indent}{indent}(None, _) => {result}::NoMatch,\n\
+            {indent}}}\n\
+            }}",
+            indent = indent,
+            rest = if index { "len" } else { "rest" },
+            remainder = if index { "&slice[len..]" } else { "rest" },
+            result = result,
+        )
+    }
+
+    /// Render the body of a stub that never matches, starting with its
+    /// signature.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render_stub<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "{} {{\n    {}::NoMatch\n}}",
+            self.signature("_slice"),
+            fn_ident(self.enum_name),
+        )
+    }
+
+    /// Get the function signature.
+    fn signature(&self, parameter: &str) -> String {
+        format!(
+            "{}({}: &[{}]) -> {}<'_, {}>",
+            self.fn_name,
+            parameter,
+            self.element_type,
+            fn_ident(self.enum_name),
+            self.return_type,
+        )
+    }
+}
+
+/// Get a pattern that matches input that ends inside a possible key, or
+/// `None` if there isn’t any.
+///
+/// That is, the input leads to a node in `root` that has branches, or to a key
+/// that must be checked against the [`Boundary`].
+pub fn incomplete_pattern<E: Element>(
+    root: &TreeNode<E>,
+    boundary: &Boundary,
+    ignore_ascii_case: bool,
+) -> Option<String> {
+    /// Collect patterns for `node` and its descendants.
+    fn internal<E: Element>(
+        node: &TreeNode<E>,
+        boundary: &Boundary,
+        ignore_ascii_case: bool,
+        prefix: &mut Vec<String>,
+        patterns: &mut Vec<String>,
+    ) {
+        if !node.branch.is_empty()
+            || (node.leaf.is_some() && *boundary != Boundary::Any)
+        {
+            patterns.push(format!("[{}]", prefix.join(", ")));
+        }
+
+        for (element, child) in &node.branch {
+            prefix.push(element.fmt_pattern(ignore_ascii_case));
+            internal(child, boundary, ignore_ascii_case, prefix, patterns);
+            prefix.pop();
+        }
+    }
+
+    let mut patterns = Vec::new();
+    internal(
+        root,
+        boundary,
+        ignore_ascii_case,
+        &mut Vec::new(),
+        &mut patterns,
+    );
+    if patterns.is_empty() {
+        None
+    } else {
+        Some(patterns.join(" | "))
+    }
+}
@@ -0,0 +1,525 @@
+//! Code for the [`KeywordEnum`].
+
+use crate::element::fmt_byte_str;
+use crate::find::fn_ident;
+use crate::{Anchor, Boundary, TreeMatcher};
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Build an enum with a variant for each key, along with functions to convert
+/// between the variants and their keys.
+///
+/// This keeps a set of keywords and the enum that represents them in one
+/// place, rather than writing the enum by hand and adding each variant to a
+/// [`TreeMatcher`]. The generated code includes:
+///
+///   * The enum, which derives `Clone`, `Copy`, `Debug`, `PartialEq`, `Eq`,
+///     and `Hash`.
+///   * `as_bytes()` to get the key for a variant.
+///   * `from_bytes()` to get the variant for an entire slice. This is a
+///     `const fn`.
+///   * `parse_prefix()` to match a key at the start of a slice, and return the
+///     variant and the rest of the slice. See [`Self::boundary()`].
+///   * An implementation of `TryFrom<&[u8]>` backed by `from_bytes()`, with
+///     an error type named `Parse{Name}Error` that has the same visibility as
+///     the enum.
+///
+/// If every key is valid UTF-8, it also includes `as_str()`, and
+/// implementations of `Display` and `FromStr`.
+///
+/// # Example
+///
+/// ```rust
+/// let mut out = Vec::new();
+/// matchgen::KeywordEnum::new("pub enum Keyword")
+///     .add(b"if", "If")
+///     .add(b"in", "In")
+///     .render(&mut out)
+///     .unwrap();
+///
+/// use bstr::ByteVec;
+/// pretty_assertions::assert_str_eq!(
+///     r#"#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// pub enum Keyword {
+///     /// `if`
+///     If,
+///     /// `in`
+///     In,
+/// }
+///
+/// impl Keyword {
+///     /// Get the key for this variant as bytes.
+///     #[must_use]
+///     pub const fn as_bytes(self) -> &'static [u8] {
+///         match self {
+///             Self::If => b"if",
+///             Self::In => b"in",
+///         }
+///     }
+///
+///     /// Get the key for this variant as a string.
+///     #[must_use]
+///     pub const fn as_str(self) -> &'static str {
+///         match self {
+///             Self::If => "if",
+///             Self::In => "in",
+///         }
+///     }
+///
+///     /// Get the variant for an entire slice, or `None` if it isn’t a key.
+///     #[allow(
+///         clippy::collapsible_match,
+///         clippy::match_same_arms,
+///         clippy::missing_const_for_fn,
+///         clippy::single_match_else,
+///         clippy::too_many_lines,
+///     )]
+///     #[must_use]
+///     pub const fn from_bytes(slice: &[u8]) -> Option<Self> {
+///         match slice {
+///             [b'i', after_1 @ ..] => match after_1 {
+///                 [b'f'] => Some(Self::If),
+///                 [b'n'] => Some(Self::In),
+///                 _ => None,
+///             }
+///             _ => None,
+///         }
+///     }
+///
+///     /// Parse a key at the start of a slice.
+///     ///
+///     /// Returns the variant, if any, and the rest of the slice.
+///     #[allow(
+///         clippy::match_same_arms,
+///         clippy::missing_const_for_fn,
+///         clippy::single_match_else,
+///         clippy::too_many_lines,
+///     )]
+///     #[must_use]
+///     pub fn parse_prefix(slice: &[u8]) -> (Option<Self>, &[u8]) {
+///         match slice {
+///             [b'i', ..] => match &slice[1..] {
+///                 [b'f', ..] => (Some(Self::If), &slice[2..]),
+///                 [b'n', ..] => (Some(Self::In), &slice[2..]),
+///                 _ => (None, slice),
+///             }
+///             _ => (None, slice),
+///         }
+///     }
+/// }
+///
+/// impl core::fmt::Display for Keyword {
+///     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+///         f.write_str(self.as_str())
+///     }
+/// }
+///
+/// /// The error returned when parsing a `Keyword` fails.
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// pub struct ParseKeywordError;
+///
+/// impl core::fmt::Display for ParseKeywordError {
+///     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+///         f.write_str("invalid Keyword")
+///     }
+/// }
+///
+/// impl std::error::Error for ParseKeywordError {}
+///
+/// impl core::str::FromStr for Keyword {
+///     type Err = ParseKeywordError;
+///
+///     fn from_str(s: &str) -> Result<Self, Self::Err> {
+///         Self::from_bytes(s.as_bytes()).ok_or(ParseKeywordError)
+///     }
+/// }
+///
+/// impl core::convert::TryFrom<&[u8]> for Keyword {
+///     type Error = ParseKeywordError;
+///
+///     fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {
+///         Self::from_bytes(slice).ok_or(ParseKeywordError)
+///     }
+/// }
+/// "#,
+///     out.into_string().unwrap(),
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct KeywordEnum {
+    /// The first part of the enum definition to generate, e.g.
+    /// `"pub enum Keyword"`.
+    pub enum_name: String,
+
+    /// Which bytes may follow a key for `parse_prefix()` to match it. Defaults
+    /// to [`Boundary::Any`].
+    ///
+    /// See [`Self::boundary()`].
+    pub boundary: Boundary,
+
+    /// Whether to parse keys regardless of ASCII case. Defaults to `false`.
+    ///
+    /// See [`Self::ignore_ascii_case()`].
+    pub ignore_ascii_case: bool,
+
+    /// Doc attribute, e.g. `#[doc = "Documentation"]`, to add to the enum.
+    ///
+    /// Should not have a trailing newline.
+    pub doc: Option<String>,
+
+    /// The keys and the names of their variants, in the order they were added.
+    pub entries: Vec<(Vec<u8>, String)>,
+}
+
+impl KeywordEnum {
+    /// Create a new enum builder.
+    ///
+    /// `enum_name` is the first part of the enum definition, e.g.
+    /// `"pub enum Keyword"`.
+    ///
+    /// ```rust
+    /// let mut keywords = matchgen::KeywordEnum::new("pub enum Keyword");
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn new<N: ToString>(enum_name: N) -> Self {
+        Self {
+            enum_name: enum_name.to_string(),
+            boundary: Boundary::default(),
+            ignore_ascii_case: false,
+            doc: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Add a key and the name of its variant, e.g. `add(b"if", "If")`.
+    ///
+    /// ```rust
+    /// let mut keywords = matchgen::KeywordEnum::new("pub enum Keyword");
+    /// keywords.add(b"if", "If");
+    /// ```
+    pub fn add<'a, K, V>(&mut self, key: K, variant: V) -> &mut Self
+    where
+        K: IntoIterator<Item = &'a u8>,
+        V: Into<String>,
+    {
+        self.entries
+            .push((key.into_iter().copied().collect(), variant.into()));
+        self
+    }
+
+    /// Set which bytes may follow a key for `parse_prefix()` to match it, e.g.
+    /// [`Boundary::word()`] so that `iffy` isn’t parsed as `if`.
+    ///
+    /// This doesn’t affect `from_bytes()`, since it matches the entire input.
+    /// See [`Boundary`].
+    pub fn boundary(&mut self, boundary: Boundary) -> &mut Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Set whether to parse keys regardless of ASCII case.
+    ///
+    /// `as_bytes()` and `as_str()` still return keys as they were added.
+    pub fn ignore_ascii_case(&mut self, ignore: bool) -> &mut Self {
+        self.ignore_ascii_case = ignore;
+        self
+    }
+
+    /// Set documentation for the enum.
+    ///
+    /// The `doc` argument should produce a Rust string literal when rendered
+    /// with [`fmt::Debug`]. A normal [`String`] or [`str`] will work. See
+    /// [`TreeMatcher::doc()`].
+    pub fn doc<S: fmt::Debug>(&mut self, doc: S) -> &mut Self {
+        self.doc = Some(format!("#[doc = {:?}]", doc));
+        self
+    }
+
+    /// Write the enum as a Rust source file in `$OUT_DIR`.
+    ///
+    /// This will overwrite the file if it already exists, or create a new file
+    /// if it does not. See [`TreeMatcher::write_to_out_dir()`].
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing the file, if
+    /// `$OUT_DIR` isn’t set to a UTF-8 string, or if the keys are invalid; see
+    /// [`Self::render()`].
+    pub fn write_to_out_dir<P: AsRef<Path>>(
+        &self,
+        sub_path: P,
+    ) -> io::Result<()> {
+        let out_dir = &env::var("OUT_DIR")
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        self.write_to_path(Path::new(out_dir).join(sub_path))
+    }
+
+    /// Write the enum as a Rust source file at `path`.
+    ///
+    /// This will overwrite the file if it already exists, or create a new file
+    /// if it does not.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `path`,
+    /// or if the keys are invalid; see [`Self::render()`].
+    pub fn write_to_path<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = io::BufWriter::new(fs::File::create(path)?);
+        self.render(&mut out)?;
+        out.flush()
+    }
+
+    /// Render the enum and its functions into Rust code.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`,
+    /// or if a key or variant name was added more than once.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::KeywordEnum::new("pub(in crate::enums) enum Keyword")
+    ///     .add(b"if", "If")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// let out = String::from_utf8(out).unwrap();
+    /// assert!(out.contains("pub(in crate::enums) enum Keyword {"));
+    /// assert!(out.contains("pub(in crate::enums) struct ParseKeywordError;"));
+    /// ```
+    #[allow(clippy::too_many_lines)]
+    pub fn render<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.check_entries()?;
+
+        let name = fn_ident(&self.enum_name);
+        let error = format!("Parse{}Error", name);
+        // Give the error the same visibility as the enum, e.g. `pub(crate) `.
+        let visibility = self
+            .enum_name
+            .rsplit_once(char::is_whitespace)
+            .and_then(|(prefix, _)| prefix.trim_end().strip_suffix("enum"))
+            .unwrap_or_default();
+        let error_name = format!("{}struct {}", visibility, error);
+        let strs: Option<Vec<&str>> = self
+            .entries
+            .iter()
+            .map(|(key, _)| std::str::from_utf8(key).ok())
+            .collect();
+        let indent = "    "; // Our formatting prevents embedding this.
+
+        if let Some(doc) = &self.doc {
+            writeln!(writer, "{}", doc)?;
+        }
+        writeln!(writer, "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]")?;
+        writeln!(writer, "{} {{", self.enum_name)?;
+        for (key, variant) in &self.entries {
+            writeln!(
+                writer,
+                "{indent}/// `{key}`\n{indent}{variant},",
+                indent = indent,
+                key = fmt_doc_key(key),
+                variant = variant,
+            )?;
+        }
+        writeln!(writer, "}}")?;
+        writeln!(writer)?;
+
+        writeln!(writer, "impl {} {{", name)?;
+        writeln!(
+            writer,
+            "{indent}/// Get the key for this variant as bytes.\n\
+            {indent}#[must_use]\n\
+            {indent}pub const fn as_bytes(self) -> &'static [u8] {{\n\
+            {indent}{indent}match self {{",
+            indent = indent,
+        )?;
+        for (key, variant) in &self.entries {
+            writeln!(
+                writer,
+                "{indent}{indent}{indent}Self::{variant} => {key},",
+                indent = indent,
+                variant = variant,
+                key = fmt_byte_str(key),
+            )?;
+        }
+        writeln!(writer, "{indent}{indent}}}\n{indent}}}", indent = indent)?;
+
+        if let Some(strs) = &strs {
+            writeln!(
+                writer,
+                "\n\
+                {indent}/// Get the key for this variant as a string.\n\
+                {indent}#[must_use]\n\
+                {indent}pub const fn as_str(self) -> &'static str {{\n\
+                {indent}{indent}match self {{",
+                indent = indent,
+            )?;
+            for (key, (_, variant)) in strs.iter().zip(&self.entries) {
+                writeln!(
+                    writer,
+                    "{indent}{indent}{indent}Self::{variant} => {key:?},",
+                    indent = indent,
+                    variant = variant,
+                    key = key,
+                )?;
+            }
+            writeln!(
+                writer,
+                "{indent}{indent}}}\n{indent}}}",
+                indent = indent
+            )?;
+        }
+
+        let mut matcher = TreeMatcher::new("pub fn from_bytes", "Self");
+        matcher.ignore_ascii_case = self.ignore_ascii_case;
+        for (key, variant) in &self.entries {
+            matcher.add(key, format!("Self::{}", variant));
+        }
+        // Write the docs here rather than setting `matcher.doc`, so that they
+        // come before the attributes `matcher` adds.
+        let mut rendered = Vec::new();
+        writeln!(
+            rendered,
+            "/// Get the variant for an entire slice, or `None` if it isn’t a key.",
+        )?;
+        matcher.anchor(Anchor::Exact).return_index();
+        matcher.render(&mut rendered)?;
+        writeln!(writer)?;
+        crate::write_indented(writer, &rendered)?;
+
+        matcher.fn_name = "pub fn parse_prefix".to_owned();
+        matcher
+            .anchor(Anchor::Prefix)
+            .return_slice()
+            .boundary(self.boundary.clone());
+        rendered.clear();
+        writeln!(
+            rendered,
+            "/// Parse a key at the start of a slice.\n\
+            ///\n\
+            /// Returns the variant, if any, and the rest of the slice.",
+        )?;
+        matcher.render(&mut rendered)?;
+        writeln!(writer)?;
+        crate::write_indented(writer, &rendered)?;
+        writeln!(writer, "}}")?;
+
+        if strs.is_some() {
+            writeln!(
+                writer,
+                "\n\
+                impl core::fmt::Display for {name} {{\n\
+                {indent}fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{\n\
+                {indent}{indent}f.write_str(self.as_str())\n\
+                {indent}}}\n\
+                }}",
+                name = name,
+                indent = indent,
+            )?;
+        }
+
+        writeln!(
+            writer,
+            "\n\
+            /// The error returned when parsing a `{name}` fails.\n\
+            #[derive(Clone, Copy, Debug, PartialEq, Eq)]\n\
+            {error_name};\n\
+            \n\
+            impl core::fmt::Display for {error} {{\n\
+            {indent}fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{\n\
+            {indent}{indent}f.write_str(\"invalid {name}\")\n\
+            {indent}}}\n\
+            }}\n\
+            \n\
+            impl std::error::Error for {error} {{}}",
+            name = name,
+            error = error,
+            error_name = error_name,
+            indent = indent,
+        )?;
+
+        if strs.is_some() {
+            writeln!(
+                writer,
+                "\n\
+                impl core::str::FromStr for {name} {{\n\
+                {indent}type Err = {error};\n\
+                \n\
+                {indent}fn from_str(s: &str) -> Result<Self, Self::Err> {{\n\
+                {indent}{indent}Self::from_bytes(s.as_bytes()).ok_or({error})\n\
+                {indent}}}\n\
+                }}",
+                name = name,
+                error = error,
+                indent = indent,
+            )?;
+        }
+
+        writeln!(
+            writer,
+            "\n\
+            impl core::convert::TryFrom<&[u8]> for {name} {{\n\
+            {indent}type Error = {error};\n\
+            \n\
+            {indent}fn try_from(slice: &[u8]) -> Result<Self, Self::Error> {{\n\
+            {indent}{indent}Self::from_bytes(slice).ok_or({error})\n\
+            {indent}}}\n\
+            }}",
+            name = name,
+            error = error,
+            indent = indent,
+        )
+    }
+
+    /// Check that no key or variant name was added more than once.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::Error`] if there is a duplicate. Keys are compared
+    /// regardless of ASCII case if [`Self::ignore_ascii_case`] is set.
+    fn check_entries(&self) -> io::Result<()> {
+        let mut keys = HashSet::new();
+        let mut variants = HashSet::new();
+        for (key, variant) in &self.entries {
+            let folded = if self.ignore_ascii_case {
+                key.to_ascii_lowercase()
+            } else {
+                key.clone()
+            };
+            if !keys.insert(folded) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "key {:?} added more than once",
+                        String::from_utf8_lossy(key),
+                    ),
+                ));
+            }
+            if !variants.insert(variant) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("variant {} added more than once", variant),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Format a key to show in the doc comment for its variant.
+///
+/// Keys that aren’t UTF-8 or that contain control characters (which could end
+/// the comment, e.g. `\n`) are escaped.
+fn fmt_doc_key(key: &[u8]) -> String {
+    match std::str::from_utf8(key) {
+        Ok(key) if key.chars().any(char::is_control) => format!("{:?}", key),
+        Ok(key) => key.to_owned(),
+        Err(_) => fmt_byte_str(key),
+    }
+}
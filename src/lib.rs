@@ -6,10 +6,11 @@
 //! [`FlatMatcher`] generates simpler but often slower code. See their
 //! documentation for example usage.
 //!
-//! If you need a `const fn` matcher, then your only option is to use
-//! [`FlatMatcher`] with [`FlatMatcher::return_index()`], which causes the
-//! generated function to return the index of the next unmatched byte instead of
-//! a slice. That allows the function to be `const`.
+//! If you need a `const fn` matcher, use [`TreeMatcher::return_index()`] or
+//! [`FlatMatcher::return_index()`], which cause the generated function to
+//! return the index of the next unmatched byte instead of a slice. That allows
+//! the function to be `const`. [`FlatMatcher`] with [`Anchor::Exact`] is also
+//! `const`, since it only checks if the entire input is a key.
 //!
 //! # Minimum supported Rust version
 //!
@@ -20,23 +21,135 @@
 // Lint configuration in Cargo.toml isn’t supported by cargo-geiger.
 #![forbid(unsafe_code)]
 
+mod anchor;
+mod boundary;
+mod canonical;
+mod element;
+mod find;
 mod flat;
+mod hoist;
+mod incomplete;
+mod keyword;
+mod order;
+mod reverse;
+mod semantics;
+mod state_machine;
+mod tables;
 mod tree;
+mod unmatched;
 
+pub use anchor::*;
+pub use boundary::*;
+pub use canonical::*;
+pub use element::{Element, Pattern};
 pub use flat::*;
+pub use keyword::*;
+pub use order::*;
+pub use semantics::*;
 pub use tree::*;
+pub use unmatched::*;
+
+use std::collections::HashMap;
+use std::io;
+
+/// Prepare keys and values added to a matcher for rendering.
+///
+/// This folds the case of keys if `ignore_ascii_case` is set, and removes
+/// duplicate keys. The result is in the order keys were first added, but
+/// duplicate keys get the value of the last instance.
+fn normalize_entries<E: Element>(
+    entries: &[(Vec<E>, String)],
+    ignore_ascii_case: bool,
+) -> Vec<(Vec<E>, &str)> {
+    let mut positions: HashMap<Vec<E>, usize> = HashMap::new();
+    let mut normalized: Vec<(Vec<E>, &str)> = Vec::new();
+    for (key, value) in entries {
+        let key = if ignore_ascii_case {
+            key.iter().map(E::to_ascii_lowercase).collect()
+        } else {
+            key.clone()
+        };
+
+        if let Some(&i) = positions.get(&key) {
+            normalized[i].1 = value;
+        } else {
+            positions.insert(key.clone(), normalized.len());
+            normalized.push((key, value));
+        }
+    }
+    normalized
+}
+
+/// Get the distinct keys added to a matcher and their values.
+///
+/// This is like [`normalize_entries()`], except that it keeps the case of keys
+/// as they were added. If a key appears more than once (regardless of case if
+/// `ignore_ascii_case` is set), the last instance and its value are used.
+fn distinct_entries<E: Element>(
+    entries: &[(Vec<E>, String)],
+    ignore_ascii_case: bool,
+) -> Vec<(&[E], &str)> {
+    let mut positions: HashMap<Vec<E>, usize> = HashMap::new();
+    let mut distinct: Vec<(&[E], &str)> = Vec::new();
+    for (key, value) in entries {
+        let normalized = if ignore_ascii_case {
+            key.iter().map(E::to_ascii_lowercase).collect()
+        } else {
+            key.clone()
+        };
+
+        if let Some(&i) = positions.get(&normalized) {
+            distinct[i] = (key, value);
+        } else {
+            positions.insert(normalized, distinct.len());
+            distinct.push((key, value));
+        }
+    }
+    distinct
+}
+
+/// Write rendered code indented by one level, e.g. to nest a function inside
+/// another. Empty lines are left empty.
+///
+/// # Errors
+///
+/// This can return [`io::Error`] if there is a problem writing to `writer`.
+fn write_indented<W: io::Write>(writer: &mut W, code: &[u8]) -> io::Result<()> {
+    for line in String::from_utf8_lossy(code).lines() {
+        if line.is_empty() {
+            writeln!(writer)?;
+        } else {
+            writeln!(writer, "    {}", line)?;
+        }
+    }
+    Ok(())
+}
+
+/// Output a byte as a pattern in source code.
+///
+/// If `ignore_ascii_case` is set, ASCII letters will be output as a pattern
+/// that matches both cases, e.g. `b'a' | b'A'`.
+fn fmt_pattern(b: u8, ignore_ascii_case: bool) -> String {
+    if ignore_ascii_case && b.is_ascii_alphabetic() {
+        format!(
+            "{} | {}",
+            fmt_byte(b.to_ascii_lowercase()),
+            fmt_byte(b.to_ascii_uppercase()),
+        )
+    } else {
+        fmt_byte(b)
+    }
+}
 
 /// Output a byte to source code
 fn fmt_byte(b: u8) -> String {
-    if b.is_ascii_graphic() || b == b' ' {
-        format!("b'{}'", b as char)
-    } else if b == b'\n' {
-        r"\n".to_owned()
-    } else if b == b'\r' {
-        r"\r".to_owned()
-    } else if b == b'\t' {
-        r"\t".to_owned()
-    } else {
-        b.to_string()
+    match b {
+        b'\'' => r"b'\''".to_owned(),
+        b'\\' => r"b'\\'".to_owned(),
+        b'\n' => r"b'\n'".to_owned(),
+        b'\r' => r"b'\r'".to_owned(),
+        b'\t' => r"b'\t'".to_owned(),
+        _ if b.is_ascii_graphic() || b == b' ' => format!("b'{}'", b as char),
+        _ => b.to_string(),
     }
 }
@@ -0,0 +1,99 @@
+//! Code for [`Order`].
+
+use std::cmp;
+
+/// The order in which to output the arms of generated `match` statements.
+///
+/// This never changes what the generated function matches; it only makes the
+/// output stable so that builds are reproducible and generated code can be
+/// compared against snapshots. (Rendering fails if [`Pattern`][crate::Pattern]
+/// keys might overlap, since the first arm that matches would win.)
+///
+/// # Example
+///
+/// ```rust
+/// use matchgen::{Order, TreeMatcher};
+///
+/// let mut out = Vec::new();
+/// TreeMatcher::new("fn match_bytes", "u64")
+///     .order(Order::Insertion)
+///     .add(b"b", "1")
+///     .add(b"a", "2")
+///     .render(&mut out)
+///     .unwrap();
+///
+/// use bstr::ByteVec;
+/// pretty_assertions::assert_str_eq!(
+///     r#"#[allow(
+///     clippy::match_same_arms,
+///     clippy::missing_const_for_fn,
+///     clippy::single_match_else,
+///     clippy::too_many_lines,
+/// )]
+/// #[must_use]
+/// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+///     match slice {
+///         [b'b', ..] => (Some(1), &slice[1..]),
+///         [b'a', ..] => (Some(2), &slice[1..]),
+///         _ => (None, slice),
+///     }
+/// }
+/// "#,
+///     out.into_string().unwrap(),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub enum Order {
+    /// Sort arms by their bytes. This is the default.
+    Bytes,
+
+    /// Output arms in the order their keys were added.
+    ///
+    /// For [`TreeMatcher`][crate::TreeMatcher], each arm of a nested `match`
+    /// is output in the order that its prefix was first seen.
+    Insertion,
+
+    /// Sort arms with a custom comparison function.
+    ///
+    /// The function is passed two keys. For
+    /// [`TreeMatcher`][crate::TreeMatcher], the arms of each `match` are
+    /// prefixes of keys, so the function is passed the prefixes up to and
+    /// including the element being matched.
+    ///
+    /// Keys are passed as bytes, encoded according to their element type:
+    ///
+    ///   * `u8` keys are passed as they are.
+    ///   * `char` keys are encoded as UTF-8.
+    ///   * `u16` and `u32` keys are encoded as big-endian bytes, e.g. the
+    ///     `u16` key `[0x0069, 0x2248]` is passed as
+    ///     `[0x00, 0x69, 0x22, 0x48]`.
+    ///   * [`Pattern`][crate::Pattern] keys are passed as the text of each
+    ///     pattern followed by a zero byte, e.g. `b"Tok::A\0Tok::B\0"`.
+    ///
+    /// In every case, comparing the bytes with `Ord` sorts keys the same way
+    /// as comparing their elements.
+    Custom(fn(&[u8], &[u8]) -> cmp::Ordering),
+}
+
+impl Default for Order {
+    fn default() -> Self {
+        Self::Bytes
+    }
+}
+
+impl Order {
+    /// Compare two keys (or key prefixes) according to this order.
+    ///
+    /// The keys are encoded as bytes as described for [`Order::Custom`].
+    ///
+    /// [`Order::Insertion`] treats all keys as equal, so that a stable sort
+    /// will leave them in their original order.
+    #[must_use]
+    pub fn compare(&self, a: &[u8], b: &[u8]) -> cmp::Ordering {
+        match self {
+            Self::Bytes => a.cmp(b),
+            Self::Insertion => cmp::Ordering::Equal,
+            Self::Custom(compare) => compare(a, b),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Code for [`TreeMatcher::reverse_fn()`] and [`FlatMatcher::reverse_fn()`].
+//!
+//! [`TreeMatcher::reverse_fn()`]: crate::TreeMatcher::reverse_fn
+//! [`FlatMatcher::reverse_fn()`]: crate::FlatMatcher::reverse_fn
+
+use crate::element::{fmt_key, key_bytes};
+use crate::{Canonical, Element, Order};
+use std::collections::HashMap;
+use std::io;
+
+/// Settings for a function that maps each value back to its canonical key.
+#[derive(Clone, Debug)]
+pub struct Reverse<'a> {
+    /// The name of the matcher function, e.g. `"matcher"`.
+    pub matcher: &'a str,
+
+    /// The type of values, e.g. `"&'static str"`.
+    pub return_type: &'a str,
+
+    /// The type of the elements of keys, e.g. `"u8"`.
+    pub element_type: &'a str,
+
+    /// Which key to return when several keys have the same value.
+    pub canonical: Canonical,
+
+    /// Patterns to match values with, for values that aren’t patterns.
+    pub value_patterns: &'a [(String, String)],
+
+    /// The order of the arms, by their keys.
+    pub order: Order,
+
+    /// Whether to mark the function with `#[must_use]`.
+    pub must_use: bool,
+}
+
+impl Reverse<'_> {
+    /// Render a function that returns the canonical key for a value.
+    ///
+    /// `entries` are the keys and values in the order they were added. If a
+    /// key appears more than once (regardless of case if `ignore_ascii_case`
+    /// is set), the last value is used.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`,
+    /// or if the keys are made of [`Pattern`][crate::Pattern]s.
+    pub fn render<E: Element, W: io::Write>(
+        &self,
+        writer: &mut W,
+        fn_name: &str,
+        entries: &[(Vec<E>, String)],
+        ignore_ascii_case: bool,
+    ) -> io::Result<()> {
+        if E::TYPE.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "reverse_fn() can’t be used with Pattern keys",
+            ));
+        }
+
+        let arms = self.arms(entries, ignore_ascii_case);
+        writeln!(
+            writer,
+            "/// Get the canonical key for a value returned by `{}()`, or `None`\n\
+            /// if no key has the value.\n\
+            #[allow(clippy::missing_const_for_fn, clippy::too_many_lines)]",
+            self.matcher,
+        )?;
+        if self.must_use {
+            writeln!(writer, "#[must_use]")?;
+        }
+
+        let signature = format!(
+            "{}({}: {}) -> Option<&'static [{}]> {{",
+            fn_name,
+            if arms.is_empty() { "_value" } else { "value" },
+            self.return_type,
+            self.element_type,
+        );
+        if arms.is_empty() {
+            return writeln!(writer, "{}\n    None\n}}", signature);
+        }
+
+        writeln!(
+            writer,
+            "{}\n    #[allow(unreachable_patterns)]\n    match value {{",
+            signature,
+        )?;
+        for (key, value) in arms {
+            let pattern = self
+                .value_patterns
+                .iter()
+                .rev()
+                .find(|(v, _)| v == value)
+                .map_or(value, |(_, pattern)| pattern);
+            writeln!(writer, "        {} => Some({}),", pattern, fmt_key(key))?;
+        }
+        writeln!(writer, "        _ => None,\n    }}\n}}")
+    }
+
+    /// Choose the canonical key for each value, and sort them by key.
+    fn arms<'e, E: Element>(
+        &self,
+        entries: &'e [(Vec<E>, String)],
+        ignore_ascii_case: bool,
+    ) -> Vec<(&'e [E], &'e str)> {
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        let mut arms: Vec<(&[E], &str)> = Vec::new();
+        for (key, value) in crate::distinct_entries(entries, ignore_ascii_case)
+        {
+            if let Some(&i) = positions.get(value) {
+                if self.canonical.replaces(arms[i].0.len(), key.len()) {
+                    arms[i].0 = key;
+                }
+            } else {
+                positions.insert(value, arms.len());
+                arms.push((key, value));
+            }
+        }
+
+        arms.sort_by(|(a, _), (b, _)| {
+            self.order.compare(&key_bytes(a), &key_bytes(b))
+        });
+        arms
+    }
+}
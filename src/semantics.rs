@@ -0,0 +1,172 @@
+//! Code for [`Semantics`].
+
+use crate::{Boundary, Element};
+use std::collections::HashMap;
+
+/// Which key to match when more than one key matches the input.
+///
+/// This only matters when one key is a prefix of another, e.g. `"&amp"` and
+/// `"&amp;"`, since only then can both keys match the same input.
+///
+/// This is ignored with [`Anchor::Exact`][crate::Anchor::Exact], since at most
+/// one key can match the entire input.
+///
+/// # Example
+///
+/// ```rust
+/// use matchgen::{Semantics, TreeMatcher};
+///
+/// let mut out = Vec::new();
+/// TreeMatcher::new("fn match_bytes", "u64")
+///     .semantics(Semantics::Shortest)
+///     .add(b"a", "1")
+///     .add(b"ab", "2")
+///     .add(b"bc", "3")
+///     .render(&mut out)
+///     .unwrap();
+///
+/// use bstr::ByteVec;
+/// pretty_assertions::assert_str_eq!(
+///     r#"#[allow(
+///     clippy::match_same_arms,
+///     clippy::missing_const_for_fn,
+///     clippy::single_match_else,
+///     clippy::too_many_lines,
+/// )]
+/// #[must_use]
+/// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+///     match slice {
+///         [b'a', ..] => (Some(1), &slice[1..]),
+///         [b'b', b'c', ..] => (Some(3), &slice[2..]),
+///         _ => (None, slice),
+///     }
+/// }
+/// "#,
+///     out.into_string().unwrap(),
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Semantics {
+    /// Match the longest key. This is the default.
+    Longest,
+
+    /// Match the shortest key, i.e. stop at the first key that completes.
+    Shortest,
+
+    /// Match the key that was added first.
+    ///
+    /// This allows explicit priority, like the ordered choice in a parsing
+    /// expression grammar: add keys in order of decreasing priority.
+    Priority,
+
+    /// Match every key, not just one.
+    ///
+    /// Rather than returning a match, the generated function calls a visitor
+    /// with the value and length of every key that matches, from shortest to
+    /// longest. The visitor is passed as the second parameter, and must
+    /// implement `FnMut({return_type}, usize)`.
+    ///
+    /// This is useful for dictionary-based segmentation, where a later pass
+    /// chooses among overlapping candidates.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{Semantics, TreeMatcher};
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn match_bytes", "u64")
+    ///     .semantics(Semantics::All)
+    ///     .add(b"a", "1")
+    ///     .add(b"abc", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// fn match_bytes<F>(slice: &[u8], mut visit: F)
+    /// where
+    ///     F: FnMut(u64, usize),
+    /// {
+    ///     match slice {
+    ///         [b'a', ..] => {
+    ///             visit(1, 1);
+    ///             match &slice[1..] {
+    ///                 [b'b', b'c', ..] => visit(2, 3),
+    ///                 _ => {}
+    ///             }
+    ///         }
+    ///         _ => {}
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    All,
+}
+
+impl Default for Semantics {
+    fn default() -> Self {
+        Self::Longest
+    }
+}
+
+impl Semantics {
+    /// Remove keys that can never be matched because of these semantics.
+    ///
+    /// `entries` must not contain duplicate keys, and must be in the order they
+    /// were added.
+    ///
+    /// Matching the longest key among what remains produces the same results
+    /// as these semantics would among all of `entries`, provided that the
+    /// matcher checks `boundary` the same way for every key, with the same
+    /// `ignore_ascii_case`.
+    pub(crate) fn filter<'a, E: Element>(
+        self,
+        entries: Vec<(Vec<E>, &'a str)>,
+        boundary: &Boundary,
+        ignore_ascii_case: bool,
+    ) -> Vec<(Vec<E>, &'a str)> {
+        if self == Self::Longest || self == Self::All {
+            return entries;
+        }
+
+        // Map each key to its rank, i.e. the order it was added.
+        let ranks: HashMap<&[E], usize> = entries
+            .iter()
+            .enumerate()
+            .map(|(rank, (key, _))| (key.as_slice(), rank))
+            .collect();
+
+        // A key can be matched only if none of the keys that are prefixes of
+        // it take precedence. (If a key matches, all of its prefixes match,
+        // unless the next byte in the key isn’t allowed by `boundary`.)
+        let keep: Vec<bool> = entries
+            .iter()
+            .enumerate()
+            .map(|(rank, (key, _))| {
+                (0..key.len()).all(|len| match ranks.get(&key[..len]) {
+                    Some(&prefix_rank) => {
+                        (self == Self::Priority && prefix_rank > rank)
+                            || !boundary.allows(&key[len], ignore_ascii_case)
+                    }
+                    None => true,
+                })
+            })
+            .collect();
+
+        entries
+            .into_iter()
+            .zip(keep)
+            .filter_map(|(entry, keep)| if keep { Some(entry) } else { None })
+            .collect()
+    }
+}
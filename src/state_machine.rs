@@ -0,0 +1,325 @@
+//! Code for [`TreeMatcher::state_machine_struct()`].
+
+use crate::find::fn_ident;
+use crate::{Boundary, Element, TreeMatcher, TreeNode};
+use std::io;
+
+/// Render a struct that matches the start of input fed to it in chunks.
+///
+/// Each node in `root` is a state, numbered in breadth-first order so that the
+/// root is state 0. `root` must have been built with the settings in
+/// `matcher`.
+///
+/// # Errors
+///
+/// This can return [`io::Error`] if there is a problem writing to `writer`.
+#[allow(clippy::too_many_lines)]
+pub fn render_state_machine<E: Element, W: io::Write>(
+    writer: &mut W,
+    matcher: &TreeMatcher<E>,
+    root: &TreeNode<E>,
+    struct_name: &str,
+) -> io::Result<()> {
+    let name = fn_ident(struct_name);
+    let result = format!("{}Result", name);
+    let result_name = format!(
+        "{}Result",
+        struct_name.replacen("struct", "enum", 1).trim_end(),
+    );
+    let output = format!("{}<{}>", result, matcher.return_type);
+    let indent = "    "; // Our formatting prevents embedding this.
+
+    // Number the states and collect the transitions between them.
+    let mut states = vec![root];
+    let mut transitions = Vec::new();
+    let mut i = 0;
+    while let Some(&node) = states.get(i) {
+        for (element, child) in &node.branch {
+            transitions.push((i, element, states.len()));
+            states.push(child);
+        }
+        i = i.checked_add(1).unwrap();
+    }
+    let keys: Vec<(usize, &String)> = states
+        .iter()
+        .enumerate()
+        .filter_map(|(i, node)| node.leaf.as_ref().map(|value| (i, value)))
+        .collect();
+
+    writeln!(
+        writer,
+        "/// The result of feeding input to `{name}`.\n\
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]\n\
+        {result_name}<T> {{\n\
+        {indent}/// A key matched. Contains its value and its length.\n\
+        {indent}Matched(T, usize),\n\
+        \n\
+        {indent}/// No key matches the start of the input.\n\
+        {indent}NoMatch,\n\
+        \n\
+        {indent}/// The input so far could be the start of a key. Feed more input, or\n\
+        {indent}/// call `finish()` at the end of the input.\n\
+        {indent}NeedMoreInput,\n\
+        }}\n\
+        \n\
+        /// Match the start of input that arrives in chunks, like `{matcher}()`.\n\
+        ///\n\
+        /// The state is kept between calls to `feed()`, so a key may be split\n\
+        /// across chunks.\n\
+        #[derive(Clone, Debug, Default)]\n\
+        {struct_name} {{\n\
+        {indent}/// The current node in the trie.\n\
+        {indent}state: usize,\n\
+        \n\
+        {indent}/// The number of elements fed since the start of the input.\n\
+        {indent}len: usize,\n\
+        \n\
+        {indent}/// The state and length of the longest key found so far.\n\
+        {indent}matched: Option<(usize, usize)>,\n\
+        }}\n\
+        \n\
+        impl {name} {{\n\
+        {indent}/// Create a matcher at the start of the input.\n\
+        {indent}#[must_use]\n\
+        {indent}pub const fn new() -> Self {{\n\
+        {indent}{indent}Self {{ state: 0, len: 0, matched: None }}\n\
+        {indent}}}\n\
+        \n\
+        {indent}/// Feed the next chunk of input.\n\
+        {indent}///\n\
+        {indent}/// Returns `NeedMoreInput` if the input so far could still be the start\n\
+        {indent}/// of a key. Otherwise, returns the longest key that matches, or\n\
+        {indent}/// `NoMatch`, and resets to the start of the input. The length of a match\n\
+        {indent}/// counts from the start of the input, which may be in an earlier chunk.\n\
+        {indent}/// Input after the match was not consumed, and should be fed again.\n\
+        {indent}#[allow(clippy::arithmetic_side_effects)]\n\
+        {indent}pub fn feed(&mut self, chunk: &[{element_type}]) -> {output} {{\n\
+        {indent}{indent}for element in chunk {{",
+        name = name,
+        result_name = result_name,
+        matcher = fn_ident(&matcher.fn_name),
+        struct_name = struct_name,
+        element_type = matcher.element_type,
+        output = output,
+        indent = indent,
+    )?;
+
+    let indent3 = "            "; // Our formatting prevents embedding this.
+    match matcher
+        .boundary
+        .forbidden_pattern::<E>(matcher.ignore_ascii_case)
+    {
+        Some(forbidden) => writeln!(
+            writer,
+            "{indent3}if Self::is_key(self.state)\n\
+            {indent3}    && !matches!(element, {forbidden})\n\
+            {indent3}{{",
+            indent3 = indent3,
+            forbidden = forbidden,
+        )?,
+        None => writeln!(writer, "{}if Self::is_key(self.state) {{", indent3)?,
+    }
+    writeln!(
+        writer,
+        "{indent3}    self.matched = Some((self.state, self.len));\n\
+        {indent3}}}\n\
+        {indent3}match Self::next_state(self.state, element) {{\n\
+        {indent3}    Some(state) => {{\n\
+        {indent3}        self.state = state;\n\
+        {indent3}        self.len += 1;\n\
+        {indent3}    }}\n\
+        {indent3}    None => return self.end(),\n\
+        {indent3}}}",
+        indent3 = indent3,
+    )?;
+    if matcher.boundary == Boundary::Any {
+        writeln!(
+            writer,
+            "{indent3}if Self::is_final(self.state) {{\n\
+            {indent3}    self.matched = Some((self.state, self.len));\n\
+            {indent3}    return self.end();\n\
+            {indent3}}}",
+            indent3 = indent3,
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "{indent}{indent}}}\n\
+        {indent}{indent}{result}::NeedMoreInput\n\
+        {indent}}}\n\
+        \n\
+        {indent}/// Finish the input, e.g. at the end of a stream.\n\
+        {indent}///\n\
+        {indent}/// Returns the longest key that matches, or `NoMatch`, and resets to the\n\
+        {indent}/// start of the input.\n\
+        {indent}pub fn finish(&mut self) -> {output} {{\n\
+        {indent}{indent}if Self::is_key(self.state) {{\n\
+        {indent}{indent}{indent}self.matched = Some((self.state, self.len));\n\
+        {indent}{indent}}}\n\
+        {indent}{indent}self.end()\n\
+        {indent}}}\n\
+        \n\
+        {indent}/// Reset to the start of the input.\n\
+        {indent}pub fn reset(&mut self) {{\n\
+        {indent}{indent}*self = Self::new();\n\
+        {indent}}}\n\
+        \n\
+        {indent}/// Reset, and return the longest key found.\n\
+        {indent}fn end(&mut self) -> {output} {{\n\
+        {indent}{indent}let matched = self.matched;\n\
+        {indent}{indent}self.reset();\n\
+        {indent}{indent}if let Some((state, len)) = matched {{\n\
+        {indent}{indent}{indent}if let Some(value) = Self::value(state) {{\n\
+        {indent}{indent}{indent}{indent}return {result}::Matched(value, len);\n\
+        {indent}{indent}{indent}}}\n\
+        {indent}{indent}}}\n\
+        {indent}{indent}{result}::NoMatch\n\
+        {indent}}}\n\
+        \n\
+        {indent}/// Get the state after `state` if the next element is `element`, or\n\
+        {indent}/// `None` if no key continues with it.\n\
+        {indent}#[allow(\n\
+        {indent}{indent}clippy::match_same_arms,\n\
+        {indent}{indent}clippy::trivially_copy_pass_by_ref,\n\
+        {indent})]\n\
+        {indent}const fn next_state({state}: usize, {element}: &{element_type}) -> Option<usize> {{",
+        result = result,
+        output = output,
+        element_type = matcher.element_type,
+        state = unused_prefix(!transitions.is_empty(), "state"),
+        element = unused_prefix(!transitions.is_empty(), "element"),
+        indent = indent,
+    )?;
+    if transitions.is_empty() {
+        writeln!(writer, "{indent}{indent}None", indent = indent)?;
+    } else {
+        writeln!(
+            writer,
+            "{indent}{indent}match (state, element) {{",
+            indent = indent
+        )?;
+        for (from, element, to) in &transitions {
+            writeln!(
+                writer,
+                "{indent}{indent}{indent}({from}, {pattern}) => Some({to}),",
+                indent = indent,
+                from = from,
+                pattern = element.fmt_pattern(matcher.ignore_ascii_case),
+                to = to,
+            )?;
+        }
+        writeln!(
+            writer,
+            "{indent}{indent}{indent}_ => None,\n\
+            {indent}{indent}}}",
+            indent = indent,
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "{indent}}}\n\
+        \n\
+        {indent}/// Check if a key ends at `state`.\n\
+        {indent}const fn is_key({state}: usize) -> bool {{\n\
+        {indent}{indent}{is_key}\n\
+        {indent}}}",
+        state = unused_prefix(!keys.is_empty(), "state"),
+        is_key = matches_states(keys.iter().map(|(i, _)| *i)),
+        indent = indent,
+    )?;
+
+    if matcher.boundary == Boundary::Any {
+        let finals: Vec<usize> = states
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(_, node)| node.branch.is_empty())
+            .map(|(i, _)| i)
+            .collect();
+        writeln!(
+            writer,
+            "\n\
+            {indent}/// Check if no key continues past `state`.\n\
+            {indent}const fn is_final({state}: usize) -> bool {{\n\
+            {indent}{indent}{is_final}\n\
+            {indent}}}",
+            state = unused_prefix(!finals.is_empty(), "state"),
+            is_final = matches_states(finals.iter().copied()),
+            indent = indent,
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "\n\
+        {indent}/// Get the value of the key that ends at `state`.\n\
+        {indent}#[allow(clippy::match_same_arms, clippy::missing_const_for_fn)]\n\
+        {indent}fn value({state}: usize) -> Option<{return_type}> {{",
+        state = unused_prefix(!keys.is_empty(), "state"),
+        return_type = matcher.return_type,
+        indent = indent,
+    )?;
+    if keys.is_empty() {
+        writeln!(writer, "{indent}{indent}None", indent = indent)?;
+    } else {
+        writeln!(writer, "{indent}{indent}match state {{", indent = indent)?;
+        for (i, value) in &keys {
+            writeln!(
+                writer,
+                "{indent}{indent}{indent}{i} => Some({value}),",
+                indent = indent,
+                i = i,
+                value = value,
+            )?;
+        }
+        writeln!(
+            writer,
+            "{indent}{indent}{indent}_ => None,\n\
+            {indent}{indent}}}",
+            indent = indent,
+        )?;
+    }
+    writeln!(writer, "{}}}\n}}", indent)
+}
+
+/// Get a parameter name, prefixed with `_` if it isn’t `used`.
+fn unused_prefix(used: bool, name: &str) -> String {
+    if used {
+        name.to_owned()
+    } else {
+        format!("_{}", name)
+    }
+}
+
+/// Render an expression that checks if `state` is one of `states`, using
+/// ranges where possible, e.g. `matches!(state, 1 | 3..=5)`.
+fn matches_states<I: Iterator<Item = usize>>(states: I) -> String {
+    // Find runs of consecutive states.
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for i in states {
+        match runs.last_mut() {
+            Some((_, end)) if end.checked_add(1) == Some(i) => *end = i,
+            _ => runs.push((i, i)),
+        }
+    }
+
+    let mut patterns = Vec::new();
+    for (start, end) in runs {
+        match end.saturating_sub(start) {
+            0 => patterns.push(start.to_string()),
+            1 => {
+                patterns.push(start.to_string());
+                patterns.push(end.to_string());
+            }
+            _ => patterns.push(format!("{}..={}", start, end)),
+        }
+    }
+
+    if patterns.is_empty() {
+        "false".to_owned()
+    } else {
+        format!("matches!(state, {})", patterns.join(" | "))
+    }
+}
@@ -0,0 +1,101 @@
+//! Code for [`TreeMatcher::tables()`] and [`FlatMatcher::tables()`].
+//!
+//! [`TreeMatcher::tables()`]: crate::TreeMatcher::tables
+//! [`FlatMatcher::tables()`]: crate::FlatMatcher::tables
+
+use crate::element::{fmt_key, key_bytes};
+use crate::{Element, Order};
+use std::io;
+
+/// Settings for constants that list the keys and values of a matcher.
+#[derive(Clone, Debug)]
+pub struct Tables<'a> {
+    /// The name of the matcher function, e.g. `"matcher"`.
+    pub matcher: &'a str,
+
+    /// The type of values, e.g. `"&'static str"`.
+    pub return_type: &'a str,
+
+    /// The type of the elements of keys, e.g. `"u8"`.
+    pub element_type: &'a str,
+
+    /// The order of the keys in the tables.
+    pub order: Order,
+}
+
+impl Tables<'_> {
+    /// Render `{prefix}KEYS`, `{prefix}ENTRIES`, `{prefix}MIN_KEY_LEN`, and
+    /// `{prefix}MAX_KEY_LEN`.
+    ///
+    /// `entries` are the keys and values in the order they were added. If a
+    /// key appears more than once (regardless of case if `ignore_ascii_case`
+    /// is set), the last instance is used.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`,
+    /// or if the keys are made of [`Pattern`][crate::Pattern]s.
+    pub fn render<E: Element, W: io::Write>(
+        &self,
+        writer: &mut W,
+        prefix: &str,
+        entries: &[(Vec<E>, String)],
+        ignore_ascii_case: bool,
+    ) -> io::Result<()> {
+        if E::TYPE.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "tables() can’t be used with Pattern keys",
+            ));
+        }
+
+        let mut entries = crate::distinct_entries(entries, ignore_ascii_case);
+        entries.sort_by(|(a, _), (b, _)| {
+            self.order.compare(&key_bytes(a), &key_bytes(b))
+        });
+
+        let keys = entries.iter().map(|(key, _)| fmt_key(key));
+        let values = entries
+            .iter()
+            .map(|(key, value)| format!("({}, {})", fmt_key(key), value));
+        let lengths = entries.iter().map(|(key, _)| key.len());
+        writeln!(
+            writer,
+            "/// The keys matched by `{matcher}()`.\n\
+            {prefix}KEYS: &[&[{element_type}]] = {keys};\n\
+            \n\
+            /// The keys matched by `{matcher}()` and their values.\n\
+            #[allow(clippy::redundant_static_lifetimes)]\n\
+            {prefix}ENTRIES: &[(&[{element_type}], {return_type})] = {values};\n\
+            \n\
+            /// The length of the shortest key matched by `{matcher}()`.\n\
+            {prefix}MIN_KEY_LEN: usize = {min};\n\
+            \n\
+            /// The length of the longest key matched by `{matcher}()`.\n\
+            {prefix}MAX_KEY_LEN: usize = {max};",
+            matcher = self.matcher,
+            prefix = prefix,
+            element_type = self.element_type,
+            return_type = self.return_type,
+            keys = fmt_array(keys),
+            values = fmt_array(values),
+            min = lengths.clone().min().unwrap_or(0),
+            max = lengths.max().unwrap_or(0),
+        )
+    }
+}
+
+/// Output an array literal with one item per line, or `&[]` if it’s empty.
+fn fmt_array<I: Iterator<Item = String>>(items: I) -> String {
+    let mut array = "&[".to_owned();
+    for item in items {
+        array.push_str("\n    ");
+        array.push_str(&item);
+        array.push(',');
+    }
+    if array.len() > 2 {
+        array.push('\n');
+    }
+    array.push(']');
+    array
+}
@@ -1,5 +1,15 @@
 //! Code for the [`TreeMatcher`].
 
+use crate::element::check_element_type;
+use crate::find::{self, Call, Finder};
+use crate::hoist::Hoisted;
+use crate::incomplete::{incomplete_pattern, Incomplete};
+use crate::reverse::Reverse;
+use crate::state_machine::render_state_machine;
+use crate::tables::Tables;
+use crate::{
+    Anchor, Boundary, Canonical, Element, Order, Semantics, Unmatched,
+};
 use std::collections::HashMap;
 use std::env;
 use std::fmt;
@@ -11,7 +21,12 @@ use std::path::Path;
 /// to values.
 ///
 /// See the variants of [`Input`] for the details about what the generated
-/// function will accept and what it returns.
+/// function will accept and what it returns. See [`Self::return_index()`] if
+/// you need a `const fn` matcher.
+///
+/// Keys are bytes by default, but may also be `u16` or `u32`, in which case the
+/// generated function accepts `&[u16]` or an iterator over `&u16`, etc. See
+/// [`Self::new_generic()`].
 ///
 /// # Example build script
 ///
@@ -46,7 +61,8 @@ use std::path::Path;
 /// }
 /// ```
 #[derive(Clone, Debug)]
-pub struct TreeMatcher {
+#[allow(clippy::struct_excessive_bools)] // These are independent settings.
+pub struct TreeMatcher<E: Element = u8> {
     /// The first part of the function definition to generate, e.g.
     /// `"pub fn matcher"`.
     pub fn_name: String,
@@ -54,17 +70,64 @@ pub struct TreeMatcher {
     /// The return type (will be wrapped in [`Option`]), e.g. `"&'static str"`.
     pub return_type: String,
 
+    /// The type of the elements of the input in generated code, e.g. `"u16"`.
+    /// Defaults to the name of `E`, or an empty string for
+    /// [`Pattern`][crate::Pattern] keys.
+    ///
+    /// See [`Self::element_type()`].
+    pub element_type: String,
+
     /// The type of input to accept. Defaults to [`Input::Slice`].
     pub input_type: Input,
 
+    /// Whether to return the remainder as a slice or an index.
+    ///
+    /// If `false`, the remainder will be returned as the index of the next
+    /// unmatched byte (which might be past the end of the input), and the
+    /// generated matching function will be `const`.
+    ///
+    /// Only applies when [`Self::input_type`] is [`Input::Slice`] or
+    /// [`Input::Str`]. Defaults to `true`.
+    ///
+    /// See [`Self::return_index()`].
+    pub return_slice: bool,
+
+    /// Where keys must be found in the input. Defaults to [`Anchor::Prefix`].
+    pub anchor: Anchor,
+
+    /// Which key to match if more than one matches. Defaults to
+    /// [`Semantics::Longest`].
+    pub semantics: Semantics,
+
+    /// Which bytes may follow a key. Defaults to [`Boundary::Any`].
+    pub boundary: Boundary,
+
+    /// The first part of the definition of an enum for a three-state result,
+    /// e.g. `"pub enum EntityMatch"`. Defaults to `None`.
+    ///
+    /// See [`Self::incomplete_enum()`].
+    pub incomplete_enum: Option<String>,
+
     /// Whether to collapse nested single arm `match` statements.
     ///
     /// This will use a flat slice `match` arm instead of a series of single arm
     /// nested `match` statements.
     ///
-    /// Only applies when [`Self::input_type`] is [`Input::Slice`].
+    /// Only applies when [`Self::input_type`] is [`Input::Slice`] or
+    /// [`Input::Str`].
     pub collapse_nested_single_arms: bool,
 
+    /// Whether to match keys regardless of ASCII case. Defaults to `false`.
+    ///
+    /// See [`Self::ignore_ascii_case()`].
+    pub ignore_ascii_case: bool,
+
+    /// Whether to output values that would be repeated in the generated code
+    /// once, as constants. Defaults to `false`.
+    ///
+    /// See [`Self::hoist_values()`].
+    pub hoist_values: bool,
+
     /// Whether to prevent Clippy from evaluating the generated code. Defaults
     /// to `false`.
     ///
@@ -74,6 +137,9 @@ pub struct TreeMatcher {
     /// Whether to mark the function with [`#[must_use]`][must_use]. Defaults to
     /// `true`.
     ///
+    /// This is ignored with [`Semantics::All`], since the function doesn’t
+    /// return anything.
+    ///
     /// [must_use]: https://doc.rust-lang.org/reference/attributes/diagnostics.html#the-must_use-attribute
     pub must_use: bool,
 
@@ -82,8 +148,95 @@ pub struct TreeMatcher {
     /// Should not have a trailing newline.
     pub doc: Option<String>,
 
-    /// The root of the matcher node tree.
-    pub root: TreeNode,
+    /// The first part of the definition of a function to find the first match
+    /// anywhere in a slice, e.g. `"pub fn find"`. Defaults to `None`.
+    ///
+    /// See [`Self::find_fn()`].
+    pub find_fn: Option<String>,
+
+    /// The first part of the definition of a function to find all matches in a
+    /// slice, e.g. `"pub fn find_iter"`. Defaults to `None`.
+    ///
+    /// See [`Self::find_iter_fn()`].
+    pub find_iter_fn: Option<String>,
+
+    /// The first part of the definition of a function to replace every match
+    /// in a slice with its value, e.g. `"pub fn replace"`. Defaults to `None`.
+    ///
+    /// See [`Self::replace_fn()`].
+    pub replace_fn: Option<String>,
+
+    /// The first part of the definition of a function to replace every match
+    /// in a `&str` with its value, e.g. `"pub fn replace_str"`. Defaults to
+    /// `None`.
+    ///
+    /// See [`Self::replace_str_fn()`].
+    pub replace_str_fn: Option<String>,
+
+    /// The first part of the definition of a function to write a slice to an
+    /// [`io::Write`] with every match replaced with its value, e.g.
+    /// `"pub fn replace_write"`. Defaults to `None`.
+    ///
+    /// See [`Self::replace_write_fn()`].
+    pub replace_write_fn: Option<String>,
+
+    /// The first part of the definition of a tokenizer struct, e.g.
+    /// `"pub struct Lexer"`. Defaults to `None`.
+    ///
+    /// See [`Self::tokenizer_struct()`].
+    pub tokenizer_struct: Option<String>,
+
+    /// What the tokenizer does with bytes that don’t start a match. Defaults
+    /// to [`Unmatched::Skip`].
+    pub unmatched: Unmatched,
+
+    /// The first part of the definition of a resumable matcher struct, e.g.
+    /// `"pub struct EntityState"`. Defaults to `None`.
+    ///
+    /// See [`Self::state_machine_struct()`].
+    pub state_machine_struct: Option<String>,
+
+    /// The first part of the definition of a function to map each value back
+    /// to its canonical key, e.g. `"pub fn encode"`. Defaults to `None`.
+    ///
+    /// See [`Self::reverse_fn()`].
+    pub reverse_fn: Option<String>,
+
+    /// Which key the reverse function returns when several keys have the same
+    /// value. Defaults to [`Canonical::First`].
+    pub canonical: Canonical,
+
+    /// Patterns to match values with in the reverse function, for values that
+    /// aren’t valid patterns.
+    ///
+    /// See [`Self::value_pattern()`].
+    pub value_patterns: Vec<(String, String)>,
+
+    /// The first part of the definitions of constants that list the keys and
+    /// values, e.g. `"pub const ENTITY_"`. Defaults to `None`.
+    ///
+    /// See [`Self::tables()`].
+    pub tables: Option<String>,
+
+    /// The first part of the definition of a function that returns the index
+    /// of a value in [`Self::values_static`] rather than the value itself,
+    /// e.g. `"pub fn entity_index"`. Defaults to `None`.
+    ///
+    /// See [`Self::index_fn()`].
+    pub index_fn: Option<String>,
+
+    /// The first part of the definition of the array of values used with
+    /// [`Self::index_fn()`]. Defaults to `"static VALUES"`.
+    pub values_static: String,
+
+    /// The order of arms in the generated `match` statements. Defaults to
+    /// [`Order::Bytes`].
+    pub order: Order,
+
+    /// The keys and values to match, in the order they were added.
+    ///
+    /// If a key appears more than once, the last value is used.
+    pub entries: Vec<(Vec<E>, String)>,
 }
 
 impl TreeMatcher {
@@ -94,8 +247,60 @@ impl TreeMatcher {
     /// [`Self::extend()`], then turn it into code with [`Self::render()`].
     ///
     /// See the [struct documentation][TreeMatcher] for a complete example.
-    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
     pub fn new<N, R>(fn_name: N, return_type: R) -> Self
+    where
+        N: ToString,
+        R: ToString,
+    {
+        Self::new_generic(fn_name, return_type)
+    }
+}
+
+impl<E: Element> TreeMatcher<E> {
+    /// Create a new matcher with keys made of `E`, e.g. `u16`.
+    ///
+    /// This works like [`TreeMatcher::new()`], but the generated function
+    /// accepts `&[E]` (with [`Input::Slice`]) or an iterator over `&E` (with
+    /// [`Input::Iterator`]), e.g. `&[u16]` for UTF-16.
+    ///
+    /// [`Input::Str`], [`Input::Chars`], and find and replace functions and
+    /// tokenizers (e.g. [`Self::find_fn()`]) require `u8`.
+    ///
+    /// ```rust
+    /// use matchgen::TreeMatcher;
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::<u16>::new_generic("fn match_utf16", "u64")
+    ///     .add(&[0x41, 0x3a9], "1")
+    ///     .add(&[0x41, 0x3c9], "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_utf16(slice: &[u16]) -> (Option<u64>, &[u16]) {
+    ///     match slice {
+    ///         [0x0041, ..] => match &slice[1..] {
+    ///             [0x03a9, ..] => (Some(1), &slice[2..]),
+    ///             [0x03c9, ..] => (Some(2), &slice[2..]),
+    ///             _ => (None, slice),
+    ///         }
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn new_generic<N, R>(fn_name: N, return_type: R) -> Self
     where
         N: ToString,
         R: ToString,
@@ -103,12 +308,35 @@ impl TreeMatcher {
         Self {
             fn_name: fn_name.to_string(),
             return_type: return_type.to_string(),
+            element_type: E::TYPE.to_owned(),
             input_type: Input::Slice,
+            return_slice: true,
+            anchor: Anchor::default(),
+            semantics: Semantics::default(),
+            boundary: Boundary::default(),
+            incomplete_enum: None,
             collapse_nested_single_arms: true,
+            ignore_ascii_case: false,
+            hoist_values: false,
             disable_clippy: false,
             must_use: true,
             doc: None,
-            root: TreeNode::default(),
+            find_fn: None,
+            find_iter_fn: None,
+            replace_fn: None,
+            replace_str_fn: None,
+            replace_write_fn: None,
+            tokenizer_struct: None,
+            unmatched: Unmatched::default(),
+            state_machine_struct: None,
+            reverse_fn: None,
+            canonical: Canonical::default(),
+            value_patterns: Vec::new(),
+            tables: None,
+            index_fn: None,
+            values_static: "static VALUES".to_owned(),
+            order: Order::default(),
+            entries: Vec::new(),
         }
     }
 
@@ -120,10 +348,12 @@ impl TreeMatcher {
     /// ```
     pub fn add<'a, K, V>(&mut self, key: K, value: V) -> &mut Self
     where
-        K: IntoIterator<Item = &'a u8>,
+        K: IntoIterator<Item = &'a E>,
+        E: 'a,
         V: Into<String>,
     {
-        self.root.add(key, value);
+        self.entries
+            .push((key.into_iter().cloned().collect(), value.into()));
         self
     }
 
@@ -145,6 +375,7 @@ impl TreeMatcher {
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
     ///     r#"#[allow(
+    ///     clippy::match_same_arms,
     ///     clippy::missing_const_for_fn,
     ///     clippy::single_match_else,
     ///     clippy::too_many_lines,
@@ -174,6 +405,7 @@ impl TreeMatcher {
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
     ///     r#"#[allow(
+    ///     clippy::match_same_arms,
     ///     clippy::missing_const_for_fn,
     ///     clippy::single_match_else,
     ///     clippy::too_many_lines,
@@ -200,6 +432,103 @@ impl TreeMatcher {
         self
     }
 
+    /// Set whether to match keys regardless of ASCII case.
+    ///
+    /// If set to `true`, ASCII letters in keys will match either their upper or
+    /// lower case versions. Keys that differ only by ASCII case are treated as
+    /// the same key, so the value of the last one added is used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
+    ///     .ignore_ascii_case(true)
+    ///     .add("a1".as_bytes(), "1")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     match slice {
+    ///         [b'a' | b'A', b'1', ..] => (Some(1), &slice[2..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    pub fn ignore_ascii_case(&mut self, ignore: bool) -> &mut Self {
+        self.ignore_ascii_case = ignore;
+        self
+    }
+
+    /// Set whether to output values that would be repeated in the generated
+    /// code once, as constants.
+    ///
+    /// Values of more than one key, and values of keys that are a prefix of a
+    /// longer key (since they’re repeated as the fallback in nested `match`
+    /// statements), are declared once at the start of the function, e.g.
+    /// `const V_0: &'static str = "<";`, and the arms refer to the constants.
+    /// This makes the output smaller when many keys share long values. Values
+    /// must be constant expressions.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::TreeMatcher::new("fn decode", "&'static str")
+    ///     .hoist_values(true)
+    ///     .add(b"&lt;", r#""<""#)
+    ///     .add(b"&lt", r#""<""#)
+    ///     .add(b"&gt;", r#"">""#)
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn decode(slice: &[u8]) -> (Option<&'static str>, &[u8]) {
+    ///     #[allow(clippy::redundant_static_lifetimes)]
+    ///     const V_0: &'static str = "<";
+    ///     match slice {
+    ///         [b'&', ..] => match &slice[1..] {
+    ///             [b'g', b't', b';', ..] => (Some(">"), &slice[4..]),
+    ///             [b'l', b't', ..] => match &slice[3..] {
+    ///                 [b';', ..] => (Some(V_0), &slice[4..]),
+    ///                 _ => (Some(V_0), &slice[3..]),
+    ///             }
+    ///             _ => (None, slice),
+    ///         }
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    pub fn hoist_values(&mut self, hoist: bool) -> &mut Self {
+        self.hoist_values = hoist;
+        self
+    }
+
     /// Set whether or not to prevent [Clippy] from evaluating the generated
     /// code.
     ///
@@ -233,6 +562,7 @@ impl TreeMatcher {
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
     ///     r#"#[allow(
+    ///     clippy::match_same_arms,
     ///     clippy::missing_const_for_fn,
     ///     clippy::single_match_else,
     ///     clippy::too_many_lines,
@@ -254,42 +584,74 @@ impl TreeMatcher {
         self
     }
 
+    /// Set the order of arms in the generated `match` statements.
+    ///
+    /// This doesn’t change what the generated function matches, but it does
+    /// ensure that the generated code is the same every time. See [`Order`].
+    pub fn order(&mut self, order: Order) -> &mut Self {
+        self.order = order;
+        self
+    }
+
     /// Set what kind of input the matcher should accept.
     ///
     /// This can be either:
     ///
     ///   * [`Input::Slice`] to accept `&[u8]`
     ///   * [`Input::Iterator`] to accept `core::iter::Iterator<Item = &'a u8>`
+    ///   * [`Input::Str`] to accept `&str`
+    ///   * [`Input::Chars`] to accept `core::iter::Iterator<Item = char>`
+    ///   * [`Input::StrChars`] to accept `&str` and match it on `char`
+    ///   * [`Input::BufRead`] to accept `std::io::BufRead`
+    ///   * [`Input::OwnedIterator`] to accept `core::iter::Iterator<Item = u8>`
+    ///     that isn’t `Clone`
+    ///   * [`Input::InPlace`] to accept `&mut &[u8]` and advance it past the
+    ///     match
+    ///   * [`Input::Cursor`] to accept `&[u8]` and advance a `&mut usize`
+    ///     position past the match
     pub fn input_type(&mut self, input_type: Input) -> &mut Self {
         self.input_type = input_type;
         self
     }
 
-    /// Don’t include documentation for the matcher.
+    /// Set the type of the elements of the input in generated code.
     ///
-    /// This is the default behavior.
+    /// This must be set for keys made of [`Pattern`][crate::Pattern]s, which
+    /// can match input of any type. The generated function will accept
+    /// `&[{element_type}]` (with [`Input::Slice`]) or an iterator over
+    /// `&{element_type}` (with [`Input::Iterator`]). It can also be set to an
+    /// alias for the default.
     ///
     /// # Example
     ///
     /// ```rust
+    /// use matchgen::{Pattern, TreeMatcher};
+    ///
     /// let mut out = Vec::new();
-    /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
-    ///     .remove_doc()
-    ///     .add("a".as_bytes(), "1")
+    /// TreeMatcher::<Pattern>::new_generic("fn match_tokens", "Rule")
+    ///     .element_type("Tok")
+    ///     .add(&["Tok::Ident".into(), "Tok::LParen".into()], "Rule::Call")
+    ///     .add(&["Tok::Ident".into()], "Rule::Name")
+    ///     .add(&["Tok::Num(_)".into()], "Rule::Num")
     ///     .render(&mut out)
     ///     .unwrap();
     ///
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
     ///     r#"#[allow(
+    ///     clippy::match_same_arms,
     ///     clippy::missing_const_for_fn,
     ///     clippy::single_match_else,
     ///     clippy::too_many_lines,
     /// )]
     /// #[must_use]
-    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    /// fn match_tokens(slice: &[Tok]) -> (Option<Rule>, &[Tok]) {
     ///     match slice {
-    ///         [b'a', ..] => (Some(1), &slice[1..]),
+    ///         [Tok::Ident, ..] => match &slice[1..] {
+    ///             [Tok::LParen, ..] => (Some(Rule::Call), &slice[2..]),
+    ///             _ => (Some(Rule::Name), &slice[1..]),
+    ///         }
+    ///         [Tok::Num(_), ..] => (Some(Rule::Num), &slice[1..]),
     ///         _ => (None, slice),
     ///     }
     /// }
@@ -297,224 +659,376 @@ impl TreeMatcher {
     ///     out.into_string().unwrap(),
     /// );
     /// ```
-    pub fn remove_doc(&mut self) -> &mut Self {
-        self.doc = None;
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn element_type<N: ToString>(&mut self, element_type: N) -> &mut Self {
+        self.element_type = element_type.to_string();
         self
     }
 
-    /// Set documentation for the matcher to a string.
+    /// Set the function to return the remainder as a slice.
     ///
-    /// This is normally what you want. Just pass a string with the
-    /// documentation for your matcher, and it will produce an [attribute][]
-    /// like `#[doc = "My function documentation..."]`.
+    /// This is the default, and the opposite of [`Self::return_index()`].
+    pub fn return_slice(&mut self) -> &mut Self {
+        self.return_slice = true;
+        self
+    }
+
+    /// Set the function to return the remainder as an index.
     ///
-    /// The `doc` argument should produce a Rust string literal when rendered
-    /// with [`fmt::Debug`]. A normal [`String`] or [`str`] will work.
+    /// That is, the signature of the generated function will look something
+    /// like:
+    ///
+    /// ```rust,ignore
+    /// const fn matcher(slice: &[u8]) -> (Option<ReturnType>, usize) {
+    /// #     (ReturnType, slice)
+    /// # }
+    /// ```
+    ///
+    /// This is the opposite of [`Self::return_slice()`].
+    ///
+    /// The generated function will be `const`. Rather than slicing the input
+    /// with `&slice[n..]`, which isn’t allowed in a `const fn`, nested `match`
+    /// statements bind the rest of the input in slice patterns.
+    ///
+    /// With [`Anchor::Suffix`], the index is the start of the match, i.e. the
+    /// length of the input before it. With [`Anchor::Exact`], the function
+    /// still returns only the value, but it is `const`.
+    ///
+    /// This only applies with [`Input::Slice`], [`Input::Str`] (where the
+    /// index is in bytes), and [`Input::Cursor`] (which advances a position
+    /// rather than returning an index, and requires Rust 1.83 or later to
+    /// compile as a `const fn`).
     ///
     /// # Example
     ///
     /// ```rust
     /// let mut out = Vec::new();
-    /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
-    ///     .doc(
-    ///         "Match the first bytes of a slice.
-    ///
-    ///         Matches only the string `\"a\"` and returns `1`."
-    ///     )
-    ///     .add("a".as_bytes(), "1")
+    /// matchgen::TreeMatcher::new("fn match_op", "u64")
+    ///     .add(b"<", "1")
+    ///     .add(b"<=", "2")
+    ///     .add(b"<<=", "3")
+    ///     .return_index()
     ///     .render(&mut out)
     ///     .unwrap();
     ///
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
     ///     r#"#[allow(
+    ///     clippy::collapsible_match,
+    ///     clippy::match_same_arms,
     ///     clippy::missing_const_for_fn,
     ///     clippy::single_match_else,
     ///     clippy::too_many_lines,
     /// )]
-    /// #[doc = "Match the first bytes of a slice.\n\n        Matches only the string `\"a\"` and returns `1`."]
     /// #[must_use]
-    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    /// const fn match_op(slice: &[u8]) -> (Option<u64>, usize) {
     ///     match slice {
-    ///         [b'a', ..] => (Some(1), &slice[1..]),
-    ///         _ => (None, slice),
+    ///         [b'<', after_1 @ ..] => match after_1 {
+    ///             [b'<', b'=', ..] => (Some(3), 3),
+    ///             [b'=', ..] => (Some(2), 2),
+    ///             _ => (Some(1), 1),
+    ///         }
+    ///         _ => (None, 0),
     ///     }
     /// }
     /// "#,
     ///     out.into_string().unwrap(),
     /// );
     /// ```
-    ///
-    /// [attribute]: https://doc.rust-lang.org/rustdoc/write-documentation/the-doc-attribute.html
-    pub fn doc<S: fmt::Debug>(&mut self, doc: S) -> &mut Self {
-        self.doc = Some(format!("#[doc = {:?}]", doc));
+    pub fn return_index(&mut self) -> &mut Self {
+        self.return_slice = false;
         self
     }
 
-    /// Set documentation for the matcher to a Rust expression.
+    /// Set where keys must be found in the input.
     ///
-    /// Generally you want [`Self::doc()`], not this. This can produce an
-    /// [attribute][] like `#[doc = include_str!("my_func.md")]`.
+    /// This can be either:
     ///
-    /// The `doc` argument should produce a Rust expression when rendered with
-    /// [`fmt::Display`]. A normal [`String`] or [`str`] will work.
+    ///   * [`Anchor::Prefix`] to match keys at the start of the input
+    ///   * [`Anchor::Exact`] to match only if the entire input is a key
+    ///   * [`Anchor::Suffix`] to match keys at the end of the input
+    ///
+    /// With [`Anchor::Exact`] and [`Input::Iterator`], the generated function
+    /// accepts `core::iter::IntoIterator<Item = &'a u8>` and consumes it.
+    ///
+    /// With [`Anchor::Suffix`] and [`Input::Iterator`], the generated function
+    /// accepts `core::iter::DoubleEndedIterator<Item = &'a u8>` and consumes
+    /// the match from the back.
+    ///
+    /// [`Input::Chars`] works the same way, but with `char` items.
     ///
     /// # Example
     ///
     /// ```rust
+    /// use matchgen::{Anchor, Input, TreeMatcher};
+    ///
     /// let mut out = Vec::new();
-    /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
-    ///     .doc_raw(r#"include_str!("match_bytes.md")"#)
-    ///     .add("a".as_bytes(), "1")
+    /// TreeMatcher::new("fn match_bytes", "u64")
+    ///     .anchor(Anchor::Exact)
+    ///     .input_type(Input::Iterator)
+    ///     .add(b"a", "1")
     ///     .render(&mut out)
     ///     .unwrap();
     ///
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
     ///     r#"#[allow(
+    ///     clippy::match_same_arms,
     ///     clippy::missing_const_for_fn,
     ///     clippy::single_match_else,
     ///     clippy::too_many_lines,
     /// )]
-    /// #[doc = include_str!("match_bytes.md")]
     /// #[must_use]
-    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
-    ///     match slice {
-    ///         [b'a', ..] => (Some(1), &slice[1..]),
-    ///         _ => (None, slice),
+    /// fn match_bytes<'a, I>(iter: I) -> Option<u64>
+    /// where
+    ///     I: core::iter::IntoIterator<Item = &'a u8>,
+    /// {
+    ///     let mut iter = iter.into_iter();
+    ///     match iter.next() {
+    ///         Some(b'a') => match iter.next() {
+    ///             None => Some(1),
+    ///             _ => None,
+    ///         }
+    ///         _ => None,
     ///     }
     /// }
     /// "#,
     ///     out.into_string().unwrap(),
     /// );
     /// ```
+    pub fn anchor(&mut self, anchor: Anchor) -> &mut Self {
+        self.anchor = anchor;
+        self
+    }
+
+    /// Set which key to match if more than one matches.
     ///
-    /// [attribute]: https://doc.rust-lang.org/rustdoc/write-documentation/the-doc-attribute.html
-    pub fn doc_raw<S: fmt::Display>(&mut self, doc: S) -> &mut Self {
-        self.doc = Some(format!("#[doc = {}]", doc));
+    /// See [`Semantics`] for an example.
+    pub fn semantics(&mut self, semantics: Semantics) -> &mut Self {
+        self.semantics = semantics;
         self
     }
 
-    /// Set documentation for the matcher to an option.
+    /// Set which bytes may follow a key for it to match.
     ///
-    /// Generally you want [`Self::doc()`], not this. This can produce an
-    /// [attribute][] like `#[doc(hidden)]`.
+    /// See [`Boundary`] for an example.
+    pub fn boundary(&mut self, boundary: Boundary) -> &mut Self {
+        self.boundary = boundary;
+        self
+    }
+
+    /// Make the generated function report when the input ends inside a
+    /// possible key, e.g. `b"&am"` with the key `b"&amp;"`, so that callers can
+    /// wait for more input.
     ///
-    /// The `doc` argument should produce the options when rendered with
-    /// [`fmt::Display`]. A normal [`String`] or [`str`] will work.
+    /// `enum_name` is the first part of the definition of an enum to generate
+    /// with the function, e.g. `"pub enum EntityMatch"`. The function returns
+    /// `EntityMatch<'_, {return_type}>`, which is one of:
+    ///
+    ///   * `Matched(value, rest)` if a key matched, with the rest of the input.
+    ///   * `NoMatch` if no key matches, no matter what input follows.
+    ///   * `Incomplete(len)` if more input is needed to tell. `len` is the
+    ///     number of elements seen, i.e. the length of the input.
+    ///
+    /// The input is incomplete if it’s a strict prefix of a key that could
+    /// match, or if it ends with a key that must be checked against
+    /// [`Self::boundary()`]. This is true even if a shorter key matches, since
+    /// a longer key might match once more input arrives. At the end of the
+    /// input, use a matcher generated without this option to get the final
+    /// result.
+    ///
+    /// If you generate more than one function with this option in the same
+    /// module, give each enum a different name.
+    ///
+    /// This requires [`Anchor::Prefix`] and [`Input::Slice`], and can’t be used with
+    /// [`Semantics::All`] or with find and replace functions and tokenizers
+    /// (e.g. [`Self::find_fn()`]).
     ///
     /// # Example
     ///
     /// ```rust
+    /// use matchgen::TreeMatcher;
+    ///
     /// let mut out = Vec::new();
-    /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
-    ///     .doc_option("hidden")
+    /// TreeMatcher::new("fn match_bytes", "u64")
+    ///     .incomplete_enum("enum Partial")
+    ///     .add(b"<", "1")
+    ///     .add(b"<=", "2")
     ///     .render(&mut out)
     ///     .unwrap();
     ///
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
-    ///     r#"#[allow(
+    ///     r#"/// The result of `match_bytes()`.
+    /// #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    /// enum Partial<'a, T> {
+    ///     /// A key matched. Contains its value and the rest of the input.
+    ///     Matched(T, &'a [u8]),
+    ///
+    ///     /// No key matches the start of the input.
+    ///     NoMatch,
+    ///
+    ///     /// The input ended inside a possible key, so more input is needed
+    ///     /// to tell if it matches. Contains the number of elements seen.
+    ///     Incomplete(usize),
+    /// }
+    ///
+    /// #[allow(
     ///     clippy::missing_const_for_fn,
-    ///     clippy::single_match_else,
-    ///     clippy::too_many_lines,
+    ///     clippy::unnested_or_patterns,
     /// )]
-    /// #[doc(hidden)]
     /// #[must_use]
-    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
-    ///     (None, slice)
+    /// fn match_bytes(slice: &[u8]) -> Partial<'_, u64> {
+    ///     #[allow(
+    ///         clippy::match_same_arms,
+    ///         clippy::missing_const_for_fn,
+    ///         clippy::single_match_else,
+    ///         clippy::too_many_lines,
+    ///     )]
+    ///     #[must_use]
+    ///     fn matcher(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///         match slice {
+    ///             [b'<', ..] => match &slice[1..] {
+    ///                 [b'=', ..] => (Some(2), &slice[2..]),
+    ///                 _ => (Some(1), &slice[1..]),
+    ///             }
+    ///             _ => (None, slice),
+    ///         }
+    ///     }
+    ///
+    ///     if matches!(slice, [] | [b'<']) {
+    ///         return Partial::Incomplete(slice.len());
+    ///     }
+    ///     match matcher(slice) {
+    ///         (Some(value), rest) => Partial::Matched(value, rest),
+    ///         (None, _) => Partial::NoMatch,
+    ///     }
     /// }
     /// "#,
     ///     out.into_string().unwrap(),
     /// );
     /// ```
-    ///
-    /// [attribute]: https://doc.rust-lang.org/rustdoc/write-documentation/the-doc-attribute.html
-    pub fn doc_option<S: fmt::Display>(&mut self, doc: S) -> &mut Self {
-        self.doc = Some(format!("#[doc({})]", doc));
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn incomplete_enum<N: ToString>(&mut self, enum_name: N) -> &mut Self {
+        self.incomplete_enum = Some(enum_name.to_string());
         self
     }
 
-    /// Write the matcher as a Rust source file in `$OUT_DIR`.
-    ///
-    /// This is what you want if you’re using this in `build.rs` as intended.
-    /// See the [`TreeMatcher`] for a full example.
+    /// Don’t include documentation for the matcher.
     ///
-    /// This will overwrite the file if it already exists, or create a new file
-    /// if it does not.
+    /// This is the default behavior.
     ///
     /// # Example
     ///
     /// ```rust
-    /// use std::env::var;
-    /// use std::path::Path;
-    /// use std::fs;
-    ///
-    /// # let tmp_dir = temp_dir::TempDir::new().unwrap();
-    /// # std::env::set_var("OUT_DIR", tmp_dir.path());
+    /// let mut out = Vec::new();
     /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
+    ///     .remove_doc()
     ///     .add("a".as_bytes(), "1")
-    ///     .write_to_out_dir("matcher.rs")
+    ///     .render(&mut out)
     ///     .unwrap();
     ///
-    /// let path = Path::new(&var("OUT_DIR").unwrap()).join("matcher.rs");
-    /// assert!(fs::read_to_string(path).unwrap().contains("fn match_bytes"));
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     match slice {
+    ///         [b'a', ..] => (Some(1), &slice[1..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
     /// ```
-    ///
-    /// # Errors
-    ///
-    /// This can return [`io::Error`] if there is a problem writing the file, or
-    /// if `$OUT_DIR` isn’t set to a UTF-8 string.
-    pub fn write_to_out_dir<P: AsRef<Path>>(
-        &self,
-        sub_path: P,
-    ) -> io::Result<()> {
-        let out_dir = &env::var("OUT_DIR")
-            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
-        self.write_to_path(Path::new(out_dir).join(sub_path))
+    pub fn remove_doc(&mut self) -> &mut Self {
+        self.doc = None;
+        self
     }
 
-    /// Write the matcher as a Rust source file at `path`.
+    /// Set documentation for the matcher to a string.
     ///
-    /// This will overwrite the file if it already exists, or create a new file
-    /// if it does not.
+    /// This is normally what you want. Just pass a string with the
+    /// documentation for your matcher, and it will produce an [attribute][]
+    /// like `#[doc = "My function documentation..."]`.
+    ///
+    /// The `doc` argument should produce a Rust string literal when rendered
+    /// with [`fmt::Debug`]. A normal [`String`] or [`str`] will work.
     ///
     /// # Example
     ///
     /// ```rust
-    /// # let tmp_dir = temp_dir::TempDir::new().unwrap();
-    /// # let path = tmp_dir.child("test.rs");
+    /// let mut out = Vec::new();
     /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
+    ///     .doc(
+    ///         "Match the first bytes of a slice.
+    ///
+    ///         Matches only the string `\"a\"` and returns `1`."
+    ///     )
     ///     .add("a".as_bytes(), "1")
-    ///     .write_to_path(&path)
+    ///     .render(&mut out)
     ///     .unwrap();
-    /// ```
     ///
-    /// # Errors
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[doc = "Match the first bytes of a slice.\n\n        Matches only the string `\"a\"` and returns `1`."]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     match slice {
+    ///         [b'a', ..] => (Some(1), &slice[1..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
     ///
-    /// This can return [`io::Error`] if there is a problem writing to `path`.
-    pub fn write_to_path<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
-        let mut out = io::BufWriter::new(fs::File::create(path)?);
-        self.render(&mut out)?;
-        out.flush()
+    /// [attribute]: https://doc.rust-lang.org/rustdoc/write-documentation/the-doc-attribute.html
+    pub fn doc<S: fmt::Debug>(&mut self, doc: S) -> &mut Self {
+        self.doc = Some(format!("#[doc = {:?}]", doc));
+        self
     }
 
-    /// Render the matcher into Rust code.
+    /// Set documentation for the matcher to a Rust expression.
+    ///
+    /// Generally you want [`Self::doc()`], not this. This can produce an
+    /// [attribute][] like `#[doc = include_str!("my_func.md")]`.
+    ///
+    /// The `doc` argument should produce a Rust expression when rendered with
+    /// [`fmt::Display`]. A normal [`String`] or [`str`] will work.
     ///
     /// # Example
     ///
     /// ```rust
     /// let mut out = Vec::new();
-    /// let mut matcher = matchgen::TreeMatcher::new("fn match_bytes", "u64");
-    /// matcher
-    ///     .disable_clippy(true)
+    /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
+    ///     .doc_raw(r#"include_str!("match_bytes.md")"#)
     ///     .add("a".as_bytes(), "1")
     ///     .render(&mut out)
     ///     .unwrap();
     ///
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
-    ///     r#"#[cfg(not(clippy))]
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[doc = include_str!("match_bytes.md")]
     /// #[must_use]
     /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
     ///     match slice {
@@ -522,133 +1036,83 @@ impl TreeMatcher {
     ///         _ => (None, slice),
     ///     }
     /// }
-    ///
-    /// #[cfg(clippy)]
-    /// #[allow(clippy::missing_const_for_fn)]
-    /// #[must_use]
-    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
-    ///     (None, slice)
-    /// }
     /// "#,
     ///     out.into_string().unwrap(),
     /// );
     /// ```
     ///
-    /// # Errors
-    ///
-    /// This can return [`io::Error`] if there is a problem writing to `writer`.
-    pub fn render<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
-        if self.disable_clippy {
-            writeln!(writer, "#[cfg(not(clippy))]")?;
-        }
-
-        self.render_func(writer)?;
-
-        if self.disable_clippy {
-            writeln!(writer)?;
-            writeln!(writer, "#[cfg(clippy)]")?;
-            self.render_stub(writer)?;
-        }
-
-        Ok(())
+    /// [attribute]: https://doc.rust-lang.org/rustdoc/write-documentation/the-doc-attribute.html
+    pub fn doc_raw<S: fmt::Display>(&mut self, doc: S) -> &mut Self {
+        self.doc = Some(format!("#[doc = {}]", doc));
+        self
     }
 
-    /// Render the function that does the matching.
+    /// Set documentation for the matcher to an option.
     ///
-    /// # Errors
+    /// Generally you want [`Self::doc()`], not this. This can produce an
+    /// [attribute][] like `#[doc(hidden)]`.
     ///
-    /// This can return [`io::Error`] if there is a problem writing to `writer`.
-    fn render_func<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
-        if !self.disable_clippy {
-            writeln!(writer, "#[allow(")?;
-            writeln!(writer, "    clippy::missing_const_for_fn,")?;
-            writeln!(writer, "    clippy::single_match_else,")?;
-            writeln!(writer, "    clippy::too_many_lines,")?;
-            writeln!(writer, ")]")?;
-        }
-
-        self.render_attributes(writer)?;
-
-        match self.input_type {
-            Input::Slice => self.root.render_slice(
-                writer,
-                &self.fn_name,
-                &self.return_type,
-                self.collapse_nested_single_arms,
-            ),
-            Input::Iterator => {
-                self.root
-                    .render_iter(writer, &self.fn_name, &self.return_type)
-            }
-        }
-    }
-
-    /// Render a stub that does nothing.
+    /// The `doc` argument should produce the options when rendered with
+    /// [`fmt::Display`]. A normal [`String`] or [`str`] will work.
     ///
-    /// # Errors
+    /// # Example
     ///
-    /// This can return [`io::Error`] if there is a problem writing to `writer`.
-    fn render_stub<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
-        writeln!(writer, "#[allow(clippy::missing_const_for_fn)]")?;
-
-        self.render_attributes(writer)?;
-
-        match self.input_type {
-            Input::Slice => TreeNode::default().render_slice(
-                writer,
-                &self.fn_name,
-                &self.return_type,
-                self.collapse_nested_single_arms,
-            ),
-            Input::Iterator => TreeNode::default().render_iter(
-                writer,
-                &self.fn_name,
-                &self.return_type,
-            ),
-        }
-    }
-
-    /// Render attributes for the function or stub.
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
+    ///     .doc_option("hidden")
+    ///     .render(&mut out)
+    ///     .unwrap();
     ///
-    /// # Errors
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[doc(hidden)]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     (None, slice)
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
     ///
-    /// This can return [`io::Error`] if there is a problem writing to `writer`.
-    fn render_attributes<W: io::Write>(
-        &self,
-        writer: &mut W,
-    ) -> io::Result<()> {
-        if let Some(doc) = &self.doc {
-            writeln!(writer, "{}", doc)?;
-        }
-
-        if self.must_use {
-            writeln!(writer, "#[must_use]")?;
-        }
-
-        Ok(())
+    /// [attribute]: https://doc.rust-lang.org/rustdoc/write-documentation/the-doc-attribute.html
+    pub fn doc_option<S: fmt::Display>(&mut self, doc: S) -> &mut Self {
+        self.doc = Some(format!("#[doc({})]", doc));
+        self
     }
-}
 
-impl<'a, K, V> Extend<(K, V)> for TreeMatcher
-where
-    K: IntoIterator<Item = &'a u8>,
-    V: Into<String>,
-{
-    /// Add matches from iterator or collection.
+    /// Also generate a function that finds the first match anywhere in a slice.
     ///
-    /// See trait documentation in [`core::iter::Extend`].
+    /// The generated function accepts `&[u8]` and returns the start and end of
+    /// the leftmost match, and its value, i.e.
+    /// `Option<(usize, usize, {return_type})>`. It calls the matcher at each
+    /// byte that could start a match.
+    ///
+    /// This requires [`Anchor::Prefix`], and can’t be used with
+    /// [`Semantics::All`]. It works with both [`Input`] types.
     ///
     /// # Example
     ///
     /// ```rust
     /// let mut out = Vec::new();
-    /// let mut matcher = matchgen::TreeMatcher::new("fn match_bytes", "u64");
-    /// matcher.extend([("a".as_bytes(), "1")]);
-    /// matcher.render(&mut out).unwrap();
+    /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
+    ///     .find_fn("fn find")
+    ///     .add(b"a", "1")
+    ///     .add(b"b", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
     ///
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
     ///     r#"#[allow(
+    ///     clippy::match_same_arms,
     ///     clippy::missing_const_for_fn,
     ///     clippy::single_match_else,
     ///     clippy::too_many_lines,
@@ -657,26 +1121,2119 @@ where
     /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
     ///     match slice {
     ///         [b'a', ..] => (Some(1), &slice[1..]),
+    ///         [b'b', ..] => (Some(2), &slice[1..]),
     ///         _ => (None, slice),
     ///     }
     /// }
+    ///
+    /// /// Find the first match of `match_bytes()` in `slice`.
+    /// ///
+    /// /// Returns the start and end of the match, and its value.
+    /// #[allow(clippy::arithmetic_side_effects)]
+    /// #[must_use]
+    /// fn find(slice: &[u8]) -> Option<(usize, usize, u64)> {
+    ///     let mut start = 0;
+    ///     while let Some(offset) = slice[start..]
+    ///         .iter()
+    ///         .position(|&b| matches!(b, b'a' | b'b'))
+    ///     {
+    ///         start += offset;
+    ///         if let (Some(value), rest) = match_bytes(&slice[start..]) {
+    ///             let end = slice.len() - rest.len();
+    ///             return Some((start, end, value));
+    ///         }
+    ///         start += 1;
+    ///     }
+    ///     None
+    /// }
     /// "#,
     ///     out.into_string().unwrap(),
     /// );
     /// ```
-    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
-        iter.into_iter().for_each(|(key, value)| {
-            self.add(key, value);
-        });
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn find_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.find_fn = Some(fn_name.to_string());
+        self
     }
-}
 
-/// What kind of input the matcher should accept: a slice or an iterator.
-#[derive(Clone, Copy, Debug)]
-pub enum Input {
-    /// Accept a slice of bytes.
+    /// Also generate a function that finds all matches in a slice.
     ///
-    /// The generated function will accept a slice of bytes (`&[u8]`) and will
+    /// The generated function accepts `&[u8]` and returns an iterator over
+    /// the start and end of every non-overlapping match, and its value, i.e.
+    /// `impl Iterator<Item = (usize, usize, {return_type})>`. Like the
+    /// matcher, it prefers the leftmost match, then the one chosen by
+    /// [`Self::semantics()`].
+    ///
+    /// This requires [`Anchor::Prefix`], and can’t be used with
+    /// [`Semantics::All`]. It works with both [`Input`] types.
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn find_iter_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.find_iter_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Also generate a function that replaces every match in a slice with its
+    /// value.
+    ///
+    /// The generated function accepts `&[u8]` and returns
+    /// `Cow<'_, [u8]>`. It replaces the same matches that
+    /// [`Self::find_iter_fn()`] would find, and returns the input borrowed if
+    /// there are none. Values must implement `AsRef<[u8]>`, so `return_type`
+    /// will typically be `&'static str` or `&'static [u8]`.
+    ///
+    /// This requires [`Anchor::Prefix`], and can’t be used with
+    /// [`Semantics::All`]. It works with both [`Input`] types.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::TreeMatcher::new("fn match_bytes", "&'static str")
+    ///     .replace_fn("fn decode")
+    ///     .add(b"&lt;", r#""<""#)
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<&'static str>, &[u8]) {
+    ///     match slice {
+    ///         [b'&', b'l', b't', b';', ..] => (Some("<"), &slice[4..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    ///
+    /// /// Replace every match of `match_bytes()` in `slice` with its value.
+    /// ///
+    /// /// Returns the input unchanged if nothing matches.
+    /// #[allow(clippy::arithmetic_side_effects, clippy::useless_asref)]
+    /// #[must_use]
+    /// fn decode(slice: &[u8]) -> std::borrow::Cow<'_, [u8]> {
+    ///     let mut out: Option<Vec<u8>> = None;
+    ///     let mut copied = 0;
+    ///     let mut start = 0;
+    ///     while let Some(offset) = slice[start..]
+    ///         .iter()
+    ///         .position(|&b| matches!(b, b'&'))
+    ///     {
+    ///         start += offset;
+    ///         if let (Some(value), rest) = match_bytes(&slice[start..]) {
+    ///             let end = slice.len() - rest.len();
+    ///             let buffer = out
+    ///                 .get_or_insert_with(|| Vec::with_capacity(slice.len()));
+    ///             buffer.extend_from_slice(&slice[copied..start]);
+    ///             buffer.extend_from_slice(value.as_ref());
+    ///             copied = end;
+    ///             // Don’t get stuck on an empty match.
+    ///             start = if end > start { end } else { end + 1 };
+    ///             continue;
+    ///         }
+    ///         start += 1;
+    ///     }
+    ///     if let Some(mut out) = out {
+    ///         out.extend_from_slice(&slice[copied..]);
+    ///         std::borrow::Cow::Owned(out)
+    ///     } else {
+    ///         std::borrow::Cow::Borrowed(slice)
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn replace_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.replace_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Also generate a function that replaces every match in a `&str` with its
+    /// value.
+    ///
+    /// This is like [`Self::replace_fn()`], except that the generated function
+    /// accepts `&str` and returns `Cow<'_, str>`. Values must implement
+    /// `AsRef<str>`.
+    ///
+    /// All keys must be non-empty UTF-8, so that a match can’t start or end in
+    /// the middle of a character.
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn replace_str_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.replace_str_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Also generate a function that writes a slice to an [`io::Write`] with
+    /// every match replaced with its value.
+    ///
+    /// This is like [`Self::replace_fn()`], except that the generated function
+    /// is generic over `W: std::io::Write`, accepts `(&[u8], &mut W)`, and
+    /// returns `std::io::Result<()>`.
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn replace_write_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.replace_write_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Also generate a tokenizer struct that splits a slice into tokens.
+    ///
+    /// `struct_name` is the first part of the definition, e.g.
+    /// `"pub struct Lexer"`. The struct wraps a `&[u8]` and implements
+    /// `Iterator<Item = ({return_type}, Range<usize>)>`, yielding each token
+    /// with its span in the slice. It calls the matcher repeatedly, and
+    /// handles bytes that don’t start a match as configured by
+    /// [`Self::unmatched()`].
+    ///
+    /// All keys must be non-empty. This requires [`Anchor::Prefix`], and can’t
+    /// be used with [`Semantics::All`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{TreeMatcher, Unmatched};
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn match_bytes", "Token")
+    ///     .tokenizer_struct("struct Lexer")
+    ///     .unmatched(Unmatched::Token("Token::Other".to_owned()))
+    ///     .add(b"+", "Token::Plus")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<Token>, &[u8]) {
+    ///     match slice {
+    ///         [b'+', ..] => (Some(Token::Plus), &slice[1..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    ///
+    /// /// Split a slice into tokens with `match_bytes()`.
+    /// ///
+    /// /// Yields each token with its span in the slice.
+    /// /// Runs of bytes that don’t start a match are yielded as
+    /// /// `Token::Other`.
+    /// #[derive(Clone, Debug)]
+    /// struct Lexer<'a> {
+    ///     /// The input.
+    ///     slice: &'a [u8],
+    ///
+    ///     /// The offset of the next token.
+    ///     start: usize,
+    /// }
+    ///
+    /// impl<'a> Lexer<'a> {
+    ///     /// Create a tokenizer for `slice`.
+    ///     #[must_use]
+    ///     pub const fn new(slice: &'a [u8]) -> Self {
+    ///         Self { slice, start: 0 }
+    ///     }
+    /// }
+    ///
+    /// impl Iterator for Lexer<'_> {
+    ///     type Item = (Token, core::ops::Range<usize>);
+    ///
+    ///     #[allow(clippy::arithmetic_side_effects)]
+    ///     fn next(&mut self) -> Option<Self::Item> {
+    ///         let slice = self.slice;
+    ///         let first = self.start;
+    ///         let mut start = first;
+    ///         while let Some(offset) = slice[start..]
+    ///             .iter()
+    ///             .position(|&b| matches!(b, b'+'))
+    ///         {
+    ///             start += offset;
+    ///             if let (Some(value), rest) = match_bytes(&slice[start..]) {
+    ///                 let end = slice.len() - rest.len();
+    ///                 if start == first {
+    ///                     self.start = end;
+    ///                     return Some((value, start..end));
+    ///                 }
+    ///                 // Yield the unmatched bytes before this match first.
+    ///                 self.start = start;
+    ///                 return Some((Token::Other, first..start));
+    ///             }
+    ///             start += 1;
+    ///         }
+    ///         self.start = slice.len();
+    ///         if first < slice.len() {
+    ///             Some((Token::Other, first..slice.len()))
+    ///         } else {
+    ///             None
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl core::iter::FusedIterator for Lexer<'_> {}
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn tokenizer_struct<N: ToString>(
+        &mut self,
+        struct_name: N,
+    ) -> &mut Self {
+        self.tokenizer_struct = Some(struct_name.to_string());
+        self
+    }
+
+    /// Set what the tokenizer does with bytes that don’t start a match.
+    /// Defaults to [`Unmatched::Skip`].
+    ///
+    /// See [`Self::tokenizer_struct()`].
+    pub fn unmatched(&mut self, unmatched: Unmatched) -> &mut Self {
+        self.unmatched = unmatched;
+        self
+    }
+
+    /// Also generate a struct that matches the start of input fed to it in
+    /// chunks, e.g. as it arrives from the network.
+    ///
+    /// `struct_name` is the first part of the definition, e.g.
+    /// `"pub struct EntityState"`. The struct keeps its place in the tree of
+    /// keys between calls to `feed(&[u8])`, which returns one of:
+    ///
+    ///   * `Matched(value, len)` if a key matched. `len` is the length of the
+    ///     key, counting from the start of the input, which may be in an
+    ///     earlier chunk.
+    ///   * `NoMatch` if no key matches the start of the input.
+    ///   * `NeedMoreInput` if the input so far could be the start of a key.
+    ///
+    /// `finish()` returns `Matched` or `NoMatch` at the end of the input.
+    /// After a match or `NoMatch`, the struct resets to the start of the
+    /// input. Input after the match wasn’t consumed, so it should be fed
+    /// again.
+    ///
+    /// These are variants of a generic enum named after the struct with a
+    /// `Result` suffix, e.g. `EntityStateResult<T>`, which is generated with
+    /// the same visibility.
+    ///
+    /// This requires [`Anchor::Prefix`], and can’t be used with
+    /// [`Semantics::All`]. It ignores [`Self::input_type()`]; `feed()` always
+    /// accepts a slice of [`Self::element_type`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::TreeMatcher;
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn match_bytes", "u64")
+    ///     .state_machine_struct("struct OpState")
+    ///     .add(b"<", "1")
+    ///     .add(b"<=", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     match slice {
+    ///         [b'<', ..] => match &slice[1..] {
+    ///             [b'=', ..] => (Some(2), &slice[2..]),
+    ///             _ => (Some(1), &slice[1..]),
+    ///         }
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    ///
+    /// /// The result of feeding input to `OpState`.
+    /// #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    /// enum OpStateResult<T> {
+    ///     /// A key matched. Contains its value and its length.
+    ///     Matched(T, usize),
+    ///
+    ///     /// No key matches the start of the input.
+    ///     NoMatch,
+    ///
+    ///     /// The input so far could be the start of a key. Feed more input, or
+    ///     /// call `finish()` at the end of the input.
+    ///     NeedMoreInput,
+    /// }
+    ///
+    /// /// Match the start of input that arrives in chunks, like `match_bytes()`.
+    /// ///
+    /// /// The state is kept between calls to `feed()`, so a key may be split
+    /// /// across chunks.
+    /// #[derive(Clone, Debug, Default)]
+    /// struct OpState {
+    ///     /// The current node in the trie.
+    ///     state: usize,
+    ///
+    ///     /// The number of elements fed since the start of the input.
+    ///     len: usize,
+    ///
+    ///     /// The state and length of the longest key found so far.
+    ///     matched: Option<(usize, usize)>,
+    /// }
+    ///
+    /// impl OpState {
+    ///     /// Create a matcher at the start of the input.
+    ///     #[must_use]
+    ///     pub const fn new() -> Self {
+    ///         Self { state: 0, len: 0, matched: None }
+    ///     }
+    ///
+    ///     /// Feed the next chunk of input.
+    ///     ///
+    ///     /// Returns `NeedMoreInput` if the input so far could still be the start
+    ///     /// of a key. Otherwise, returns the longest key that matches, or
+    ///     /// `NoMatch`, and resets to the start of the input. The length of a match
+    ///     /// counts from the start of the input, which may be in an earlier chunk.
+    ///     /// Input after the match was not consumed, and should be fed again.
+    ///     #[allow(clippy::arithmetic_side_effects)]
+    ///     pub fn feed(&mut self, chunk: &[u8]) -> OpStateResult<u64> {
+    ///         for element in chunk {
+    ///             if Self::is_key(self.state) {
+    ///                 self.matched = Some((self.state, self.len));
+    ///             }
+    ///             match Self::next_state(self.state, element) {
+    ///                 Some(state) => {
+    ///                     self.state = state;
+    ///                     self.len += 1;
+    ///                 }
+    ///                 None => return self.end(),
+    ///             }
+    ///             if Self::is_final(self.state) {
+    ///                 self.matched = Some((self.state, self.len));
+    ///                 return self.end();
+    ///             }
+    ///         }
+    ///         OpStateResult::NeedMoreInput
+    ///     }
+    ///
+    ///     /// Finish the input, e.g. at the end of a stream.
+    ///     ///
+    ///     /// Returns the longest key that matches, or `NoMatch`, and resets to the
+    ///     /// start of the input.
+    ///     pub fn finish(&mut self) -> OpStateResult<u64> {
+    ///         if Self::is_key(self.state) {
+    ///             self.matched = Some((self.state, self.len));
+    ///         }
+    ///         self.end()
+    ///     }
+    ///
+    ///     /// Reset to the start of the input.
+    ///     pub fn reset(&mut self) {
+    ///         *self = Self::new();
+    ///     }
+    ///
+    ///     /// Reset, and return the longest key found.
+    ///     fn end(&mut self) -> OpStateResult<u64> {
+    ///         let matched = self.matched;
+    ///         self.reset();
+    ///         if let Some((state, len)) = matched {
+    ///             if let Some(value) = Self::value(state) {
+    ///                 return OpStateResult::Matched(value, len);
+    ///             }
+    ///         }
+    ///         OpStateResult::NoMatch
+    ///     }
+    ///
+    ///     /// Get the state after `state` if the next element is `element`, or
+    ///     /// `None` if no key continues with it.
+    ///     #[allow(
+    ///         clippy::match_same_arms,
+    ///         clippy::trivially_copy_pass_by_ref,
+    ///     )]
+    ///     const fn next_state(state: usize, element: &u8) -> Option<usize> {
+    ///         match (state, element) {
+    ///             (0, b'<') => Some(1),
+    ///             (1, b'=') => Some(2),
+    ///             _ => None,
+    ///         }
+    ///     }
+    ///
+    ///     /// Check if a key ends at `state`.
+    ///     const fn is_key(state: usize) -> bool {
+    ///         matches!(state, 1 | 2)
+    ///     }
+    ///
+    ///     /// Check if no key continues past `state`.
+    ///     const fn is_final(state: usize) -> bool {
+    ///         matches!(state, 2)
+    ///     }
+    ///
+    ///     /// Get the value of the key that ends at `state`.
+    ///     #[allow(clippy::match_same_arms, clippy::missing_const_for_fn)]
+    ///     fn value(state: usize) -> Option<u64> {
+    ///         match state {
+    ///             1 => Some(1),
+    ///             2 => Some(2),
+    ///             _ => None,
+    ///         }
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn state_machine_struct<N: ToString>(
+        &mut self,
+        struct_name: N,
+    ) -> &mut Self {
+        self.state_machine_struct = Some(struct_name.to_string());
+        self
+    }
+
+    /// Also generate a function that maps each value back to its canonical key.
+    ///
+    /// The generated function accepts `{return_type}` and returns the key for
+    /// the value as `Option<&'static [{element_type}]>`, or `None` if no key
+    /// has the value. This is useful to pair a decoder with an encoder, e.g.
+    /// to re-encode HTML entities.
+    ///
+    /// Each value is used as a pattern in a `match` statement, so values must
+    /// be valid patterns, such as literals or paths to enum variants. Use
+    /// [`Self::value_pattern()`] to supply a pattern for a value that isn’t
+    /// one. If several keys have the same value, [`Self::canonical()`] chooses
+    /// which one to return.
+    ///
+    /// This can’t be used with [`Pattern`][crate::Pattern] keys.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::TreeMatcher::new("fn decode", "&'static str")
+    ///     .reverse_fn("fn encode")
+    ///     .add(b"&lt;", r#""<""#)
+    ///     .add(b"&lt", r#""<""#)
+    ///     .add(b"&gt;", r#"">""#)
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn decode(slice: &[u8]) -> (Option<&'static str>, &[u8]) {
+    ///     match slice {
+    ///         [b'&', ..] => match &slice[1..] {
+    ///             [b'g', b't', b';', ..] => (Some(">"), &slice[4..]),
+    ///             [b'l', b't', ..] => match &slice[3..] {
+    ///                 [b';', ..] => (Some("<"), &slice[4..]),
+    ///                 _ => (Some("<"), &slice[3..]),
+    ///             }
+    ///             _ => (None, slice),
+    ///         }
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    ///
+    /// /// Get the canonical key for a value returned by `decode()`, or `None`
+    /// /// if no key has the value.
+    /// #[allow(clippy::missing_const_for_fn, clippy::too_many_lines)]
+    /// #[must_use]
+    /// fn encode(value: &'static str) -> Option<&'static [u8]> {
+    ///     #[allow(unreachable_patterns)]
+    ///     match value {
+    ///         ">" => Some(b"&gt;"),
+    ///         "<" => Some(b"&lt;"),
+    ///         _ => None,
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn reverse_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.reverse_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Set which key the reverse function returns when several keys have the
+    /// same value. Defaults to [`Canonical::First`].
+    ///
+    /// See [`Self::reverse_fn()`].
+    pub fn canonical(&mut self, canonical: Canonical) -> &mut Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Set the pattern the reverse function uses to match a value.
+    ///
+    /// By default each value is used as its own pattern, which doesn’t work
+    /// for values that are expressions but not patterns, e.g. function calls.
+    ///
+    /// ```rust
+    /// let mut matcher = matchgen::TreeMatcher::new("fn matcher", "u32");
+    /// matcher
+    ///     .reverse_fn("fn reverse")
+    ///     .add(b"&lt;", "u32::from(b'<')")
+    ///     .value_pattern("u32::from(b'<')", "0x3c");
+    /// ```
+    ///
+    /// See [`Self::reverse_fn()`].
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn value_pattern<V: ToString, P: ToString>(
+        &mut self,
+        value: V,
+        pattern: P,
+    ) -> &mut Self {
+        self.value_patterns
+            .push((value.to_string(), pattern.to_string()));
+        self
+    }
+
+    /// Also generate constants that list the keys and values.
+    ///
+    /// `prefix` is the first part of each definition, e.g. `"pub const "` or
+    /// `"pub const ENTITY_"`. It’s followed by:
+    ///
+    ///   * `KEYS`, a `&[&[{element_type}]]` of every key.
+    ///   * `ENTRIES`, a `&[(&[{element_type}], {return_type})]` of every key
+    ///     and its value. Values must be constant expressions.
+    ///   * `MIN_KEY_LEN` and `MAX_KEY_LEN`, the lengths of the shortest and
+    ///     longest keys as `usize`, or 0 if there are no keys.
+    ///
+    /// This is useful to iterate over the keys, e.g. for autocompletion or
+    /// tests, or to size a lookahead buffer. Keys are listed as they were
+    /// added, in the order set by [`Self::order()`]. If a key was added more
+    /// than once, only the last instance is listed.
+    ///
+    /// This can’t be used with [`Pattern`][crate::Pattern] keys.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::TreeMatcher::new("fn decode", "&'static str")
+    ///     .tables("const ENTITY_")
+    ///     .add(b"&lt;", r#""<""#)
+    ///     .add(b"&gt;", r#"">""#)
+    ///     .add(b"&lt", r#""<""#)
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn decode(slice: &[u8]) -> (Option<&'static str>, &[u8]) {
+    ///     match slice {
+    ///         [b'&', ..] => match &slice[1..] {
+    ///             [b'g', b't', b';', ..] => (Some(">"), &slice[4..]),
+    ///             [b'l', b't', ..] => match &slice[3..] {
+    ///                 [b';', ..] => (Some("<"), &slice[4..]),
+    ///                 _ => (Some("<"), &slice[3..]),
+    ///             }
+    ///             _ => (None, slice),
+    ///         }
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    ///
+    /// /// The keys matched by `decode()`.
+    /// const ENTITY_KEYS: &[&[u8]] = &[
+    ///     b"&gt;",
+    ///     b"&lt",
+    ///     b"&lt;",
+    /// ];
+    ///
+    /// /// The keys matched by `decode()` and their values.
+    /// #[allow(clippy::redundant_static_lifetimes)]
+    /// const ENTITY_ENTRIES: &[(&[u8], &'static str)] = &[
+    ///     (b"&gt;", ">"),
+    ///     (b"&lt", "<"),
+    ///     (b"&lt;", "<"),
+    /// ];
+    ///
+    /// /// The length of the shortest key matched by `decode()`.
+    /// const ENTITY_MIN_KEY_LEN: usize = 3;
+    ///
+    /// /// The length of the longest key matched by `decode()`.
+    /// const ENTITY_MAX_KEY_LEN: usize = 4;
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn tables<N: ToString>(&mut self, prefix: N) -> &mut Self {
+        self.tables = Some(prefix.to_string());
+        self
+    }
+
+    /// Store each value once in a static array, and match keys with a
+    /// function that returns the index of the value.
+    ///
+    /// By default, values are inlined in the generated `match` statements, so
+    /// a value may be copied into several arms. With this set, each distinct
+    /// value is output once in [`Self::values_static()`], and `fn_name` is the
+    /// first part of the definition of a function that matches keys and
+    /// returns the index of the value as `u16` (or `u32` if there are more
+    /// than 65,536 values). The matcher itself wraps that function, so its
+    /// signature doesn’t change. This can make the output for large sets of
+    /// keys significantly smaller and faster to compile.
+    ///
+    /// Values must be [`Copy`]. This requires [`Input::Slice`] or
+    /// [`Input::Str`], and can’t be used with [`Semantics::All`] or
+    /// [`Self::incomplete_enum()`]. The matcher can’t be `const`, even with
+    /// [`Self::return_index()`], but the index function can.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::TreeMatcher::new("fn decode", "&'static str")
+    ///     .index_fn("fn decode_index")
+    ///     .values_static("static DECODED")
+    ///     .add(b"&lt;", r#""<""#)
+    ///     .add(b"&lt", r#""<""#)
+    ///     .add(b"&gt;", r#"">""#)
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"/// The values of the keys matched by `decode()`.
+    /// #[allow(clippy::redundant_static_lifetimes)]
+    /// static DECODED: [&'static str; 2] = [
+    ///     "<",
+    ///     ">",
+    /// ];
+    ///
+    /// #[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[doc = "Get the index in `DECODED` of the value of the key matched by `decode()`."]
+    /// #[must_use]
+    /// fn decode_index(slice: &[u8]) -> (Option<u16>, &[u8]) {
+    ///     match slice {
+    ///         [b'&', ..] => match &slice[1..] {
+    ///             [b'g', b't', b';', ..] => (Some(1), &slice[4..]),
+    ///             [b'l', b't', ..] => match &slice[3..] {
+    ///                 [b';', ..] => (Some(0), &slice[4..]),
+    ///                 _ => (Some(0), &slice[3..]),
+    ///             }
+    ///             _ => (None, slice),
+    ///         }
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    ///
+    /// #[must_use]
+    /// fn decode(slice: &[u8]) -> (Option<&'static str>, &[u8]) {
+    ///     let (index, rest) = decode_index(slice);
+    ///     (index.map(|index| DECODED[usize::from(index)]), rest)
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn index_fn<N: ToString>(&mut self, fn_name: N) -> &mut Self {
+        self.index_fn = Some(fn_name.to_string());
+        self
+    }
+
+    /// Set the first part of the definition of the array of values used with
+    /// [`Self::index_fn()`]. Defaults to `"static VALUES"`.
+    #[allow(clippy::needless_pass_by_value)] // ToString can borrow.
+    pub fn values_static<N: ToString>(&mut self, static_name: N) -> &mut Self {
+        self.values_static = static_name.to_string();
+        self
+    }
+
+    /// Write the matcher as a Rust source file in `$OUT_DIR`.
+    ///
+    /// This is what you want if you’re using this in `build.rs` as intended.
+    /// See the [`TreeMatcher`] for a full example.
+    ///
+    /// This will overwrite the file if it already exists, or create a new file
+    /// if it does not.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::env::var;
+    /// use std::path::Path;
+    /// use std::fs;
+    ///
+    /// # let tmp_dir = temp_dir::TempDir::new().unwrap();
+    /// # std::env::set_var("OUT_DIR", tmp_dir.path());
+    /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
+    ///     .add("a".as_bytes(), "1")
+    ///     .write_to_out_dir("matcher.rs")
+    ///     .unwrap();
+    ///
+    /// let path = Path::new(&var("OUT_DIR").unwrap()).join("matcher.rs");
+    /// assert!(fs::read_to_string(path).unwrap().contains("fn match_bytes"));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing the file, or
+    /// if `$OUT_DIR` isn’t set to a UTF-8 string.
+    pub fn write_to_out_dir<P: AsRef<Path>>(
+        &self,
+        sub_path: P,
+    ) -> io::Result<()> {
+        let out_dir = &env::var("OUT_DIR")
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+        self.write_to_path(Path::new(out_dir).join(sub_path))
+    }
+
+    /// Write the matcher as a Rust source file at `path`.
+    ///
+    /// This will overwrite the file if it already exists, or create a new file
+    /// if it does not.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # let tmp_dir = temp_dir::TempDir::new().unwrap();
+    /// # let path = tmp_dir.child("test.rs");
+    /// matchgen::TreeMatcher::new("fn match_bytes", "u64")
+    ///     .add("a".as_bytes(), "1")
+    ///     .write_to_path(&path)
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `path`.
+    pub fn write_to_path<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = io::BufWriter::new(fs::File::create(path)?);
+        self.render(&mut out)?;
+        out.flush()
+    }
+
+    /// Render the matcher into Rust code.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// let mut matcher = matchgen::TreeMatcher::new("fn match_bytes", "u64");
+    /// matcher
+    ///     .disable_clippy(true)
+    ///     .add("a".as_bytes(), "1")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[cfg(not(clippy))]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     match slice {
+    ///         [b'a', ..] => (Some(1), &slice[1..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    ///
+    /// #[cfg(clippy)]
+    /// #[allow(clippy::missing_const_for_fn)]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     (None, slice)
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`,
+    /// or if [`Self::find_fn()`], [`Self::replace_fn()`],
+    /// [`Self::tokenizer_struct()`], [`Self::state_machine_struct()`],
+    /// [`Self::incomplete_enum()`], or a related function is set with
+    /// incompatible settings, or if [`Pattern`][crate::Pattern] keys might
+    /// overlap.
+    pub fn render<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.check_keys()?;
+        let finder = self.finder()?;
+        if self.state_machine_struct.is_some()
+            && (self.anchor != Anchor::Prefix || self.visits_all())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "state_machine_struct() requires Anchor::Prefix, and can’t be \
+                used with Semantics::All",
+            ));
+        }
+        if let Some(enum_name) = &self.incomplete_enum {
+            if self.anchor != Anchor::Prefix
+                || self.visits_all()
+                || !matches!(self.input_type, Input::Slice)
+                || finder.is_some()
+            {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "incomplete_enum() requires Anchor::Prefix and Input::Slice, \
+                    and can’t be used with Semantics::All or find and replace \
+                    functions and tokenizers",
+                ));
+            }
+            self.incomplete(enum_name).render_enum(writer)?;
+            writeln!(writer)?;
+        }
+
+        if let Some(fn_name) = &self.index_fn {
+            self.render_indexed(writer, fn_name)?;
+        } else {
+            self.render_matcher(writer)?;
+        }
+
+        if let Some(finder) = finder {
+            if let Some(fn_name) = &self.find_fn {
+                writeln!(writer)?;
+                finder.render_find(writer, fn_name)?;
+            }
+
+            if let Some(fn_name) = &self.find_iter_fn {
+                writeln!(writer)?;
+                finder.render_find_iter(writer, fn_name)?;
+            }
+
+            if let Some(fn_name) = &self.replace_fn {
+                writeln!(writer)?;
+                finder.render_replace(writer, fn_name)?;
+            }
+
+            if let Some(fn_name) = &self.replace_str_fn {
+                writeln!(writer)?;
+                finder.render_replace_str(writer, fn_name)?;
+            }
+
+            if let Some(fn_name) = &self.replace_write_fn {
+                writeln!(writer)?;
+                finder.render_replace_write(writer, fn_name)?;
+            }
+
+            if let Some(struct_name) = &self.tokenizer_struct {
+                writeln!(writer)?;
+                finder.render_tokenizer(
+                    writer,
+                    struct_name,
+                    &self.unmatched,
+                )?;
+            }
+        }
+
+        if let Some(struct_name) = &self.state_machine_struct {
+            writeln!(writer)?;
+            render_state_machine(writer, self, &self.tree(), struct_name)?;
+        }
+
+        if let Some(fn_name) = &self.reverse_fn {
+            writeln!(writer)?;
+            self.reverse().render(
+                writer,
+                fn_name,
+                &self.entries,
+                self.ignore_ascii_case,
+            )?;
+        }
+
+        if let Some(prefix) = &self.tables {
+            writeln!(writer)?;
+            self.key_tables().render(
+                writer,
+                prefix,
+                &self.entries,
+                self.ignore_ascii_case,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the renderer for find and replace functions and tokenizers, if they
+    /// were requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::Error`] if find or replace functions or tokenizers can’t
+    /// be generated with the current settings.
+    fn finder(&self) -> io::Result<Option<Finder<'_>>> {
+        if self.find_fn.is_none()
+            && self.find_iter_fn.is_none()
+            && self.replace_fn.is_none()
+            && self.replace_str_fn.is_none()
+            && self.replace_write_fn.is_none()
+            && self.tokenizer_struct.is_none()
+        {
+            return Ok(None);
+        }
+
+        let keys: Option<Vec<&[u8]>> = self
+            .entries
+            .iter()
+            .map(|(key, _)| E::as_bytes(key))
+            .collect();
+        let keys = match keys {
+            Some(keys)
+                if self.anchor == Anchor::Prefix
+                    && !self.visits_all()
+                    && matches!(
+                        self.input_type,
+                        Input::Slice | Input::Iterator
+                    ) =>
+            {
+                keys
+            }
+            _ => return Err(find::unsupported()),
+        };
+
+        if self.replace_str_fn.is_some() {
+            find::check_str_keys(keys.iter().copied())?;
+        }
+
+        if self.tokenizer_struct.is_some() {
+            find::check_tokenizer_keys(keys.iter().copied())?;
+        }
+
+        let root = self.tree();
+        let first_bytes = if root.leaf.is_some() || root.branch.is_empty() {
+            None
+        } else {
+            Some(
+                root.branch
+                    .iter()
+                    .map(|(b, _)| b.fmt_pattern(self.ignore_ascii_case))
+                    .collect::<Vec<_>>()
+                    .join(" | "),
+            )
+        };
+
+        Ok(Some(Finder {
+            matcher: find::fn_ident(&self.fn_name),
+            return_type: &self.return_type,
+            call: match self.input_type {
+                Input::Iterator => Call::Iterator,
+                _ if !self.return_slice => Call::Index,
+                _ => Call::Slice,
+            },
+            first_bytes,
+            must_use: self.must_use,
+        }))
+    }
+
+    /// Render the function that does the matching, and a stub for Clippy if
+    /// [`Self::disable_clippy()`] is set.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_matcher<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        if self.disable_clippy {
+            writeln!(writer, "#[cfg(not(clippy))]")?;
+        }
+
+        self.render_func(writer)?;
+
+        if self.disable_clippy {
+            writeln!(writer)?;
+            writeln!(writer, "#[cfg(clippy)]")?;
+            self.render_stub(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// Render the array of values, the function that returns an index into
+    /// it, and the matcher that wraps that function. See [`Self::index_fn()`].
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`,
+    /// or if the settings aren’t supported.
+    fn render_indexed<W: io::Write>(
+        &self,
+        writer: &mut W,
+        fn_name: &str,
+    ) -> io::Result<()> {
+        if !matches!(self.input_type, Input::Slice | Input::Str)
+            || self.visits_all()
+            || self.incomplete_enum.is_some()
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "index_fn() requires Input::Slice or Input::Str, and can’t be \
+                used with Semantics::All or incomplete_enum()",
+            ));
+        }
+
+        // Replace each value with its index in `values`.
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        let mut values: Vec<&str> = Vec::new();
+        let mut matcher = self.clone();
+        matcher.entries.clear();
+        for (key, value) in
+            crate::distinct_entries(&self.entries, self.ignore_ascii_case)
+        {
+            let index = *positions.entry(value).or_insert_with(|| {
+                let index = values.len();
+                values.push(value);
+                index
+            });
+            matcher.entries.push((key.to_vec(), index.to_string()));
+        }
+
+        let values_name = find::fn_ident(&self.values_static);
+        matcher.fn_name = fn_name.to_owned();
+        matcher.hoist_values = false;
+        matcher.return_type = if values.len() > 0x1_0000 {
+            "u32".to_owned()
+        } else {
+            "u16".to_owned()
+        };
+        matcher.doc(format!(
+            "Get the index in `{}` of the value of the key matched by `{}()`.",
+            values_name,
+            find::fn_ident(&self.fn_name),
+        ));
+
+        writeln!(
+            writer,
+            "/// The values of the keys matched by `{}()`.\n\
+            #[allow(clippy::redundant_static_lifetimes)]\n\
+            {}: [{}; {}] = [",
+            find::fn_ident(&self.fn_name),
+            self.values_static,
+            self.return_type,
+            values.len(),
+        )?;
+        for value in &values {
+            writeln!(writer, "    {},", value)?;
+        }
+        writeln!(writer, "];")?;
+        writeln!(writer)?;
+
+        matcher.render_matcher(writer)?;
+        writeln!(writer)?;
+
+        let input = if self.str_input() { "s" } else { "slice" };
+        let index = find::fn_ident(fn_name);
+        self.render_attributes(writer)?;
+        if self.anchor == Anchor::Exact {
+            writeln!(
+                writer,
+                "{fn_name}({param}) -> Option<{return_type}> {{\n    \
+                {index}({input}).map(|index| {values}[usize::from(index)])\n\
+                }}",
+                fn_name = self.fn_name,
+                param = self.slice_param(true),
+                return_type = self.return_type,
+                index = index,
+                input = input,
+                values = values_name,
+            )
+        } else {
+            writeln!(
+                writer,
+                "{fn_name}({param}) -> (Option<{return_type}>, {remainder}) {{\n    \
+                let (index, rest) = {index}({input});\n    \
+                (index.map(|index| {values}[usize::from(index)]), rest)\n\
+                }}",
+                fn_name = self.fn_name,
+                param = self.slice_param(true),
+                return_type = self.return_type,
+                remainder = self.remainder_type(),
+                index = index,
+                input = input,
+                values = values_name,
+            )
+        }
+    }
+
+    /// Render the function that does the matching.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_func<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        if self.buf_read_input() {
+            return self.render_buf_read(writer);
+        } else if self.owned_iter_input() {
+            return self.render_owned_iter(writer);
+        } else if self.in_place_input() {
+            return self.render_in_place(writer);
+        } else if self.str_chars_input() {
+            return self.render_str_chars(writer);
+        } else if let Some(enum_name) = &self.incomplete_enum {
+            return self.render_incomplete(writer, enum_name);
+        }
+
+        if !self.disable_clippy {
+            writeln!(writer, "#[allow(")?;
+            if !self.return_slice {
+                // Nested `match` statements on bindings could be collapsed.
+                writeln!(writer, "    clippy::collapsible_match,")?;
+            }
+            writeln!(writer, "    clippy::match_same_arms,")?;
+            writeln!(writer, "    clippy::missing_const_for_fn,")?;
+            if self.visits_all() {
+                writeln!(writer, "    clippy::single_match,")?;
+            }
+            writeln!(writer, "    clippy::single_match_else,")?;
+            writeln!(writer, "    clippy::too_many_lines,")?;
+            writeln!(writer, ")]")?;
+        }
+
+        self.render_attributes(writer)?;
+        if self.char_input() {
+            self.render_root(writer, self.char_tree())
+        } else {
+            self.render_root(writer, self.tree())
+        }
+    }
+
+    /// Render a function that matches `root`, replacing values that would be
+    /// repeated in the generated code with constants if
+    /// [`Self::hoist_values`] is set.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_root<T: Element, W: io::Write>(
+        &self,
+        writer: &mut W,
+        mut root: TreeNode<T>,
+    ) -> io::Result<()> {
+        let mut hoisted = Hoisted::default();
+        if self.hoist_values {
+            let mut values = Vec::new();
+            leaf_values(&root, &mut values);
+            hoisted = Hoisted::new(values);
+            replace_leaves(&mut root, &hoisted);
+        }
+        self.render_tree(writer, &root, &hoisted)
+    }
+
+    /// Render a stub that does nothing.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_stub<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        if self.buf_read_input() {
+            writeln!(writer, "#[allow(clippy::missing_errors_doc)]")?;
+        } else {
+            writeln!(writer, "#[allow(clippy::missing_const_for_fn)]")?;
+        }
+
+        self.render_attributes(writer)?;
+        if let Some(enum_name) = &self.incomplete_enum {
+            self.incomplete(enum_name).render_stub(writer)
+        } else if self.buf_read_input() {
+            self.render_buf_read_start(writer, false)?;
+            writeln!(writer, "    Ok(None)")?;
+            writeln!(writer, "}}")
+        } else if self.owned_iter_input() {
+            self.render_owned_iter_start(writer, false)?;
+            writeln!(writer, "    (None, Vec::new())")?;
+            writeln!(writer, "}}")
+        } else if self.in_place_input() {
+            self.render_in_place_start(writer, false)?;
+            writeln!(writer, "    None")?;
+            writeln!(writer, "}}")
+        } else if self.char_input() {
+            self.render_tree(
+                writer,
+                &TreeNode::<char>::empty(),
+                &Hoisted::default(),
+            )
+        } else {
+            self.render_tree(
+                writer,
+                &TreeNode::<E>::empty(),
+                &Hoisted::default(),
+            )
+        }
+    }
+
+    /// Render a function that matches `root` with the appropriate renderer.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_tree<T: Element, W: io::Write>(
+        &self,
+        writer: &mut W,
+        root: &TreeNode<T>,
+        hoisted: &Hoisted,
+    ) -> io::Result<()> {
+        // Other input types wrap a slice matcher, e.g. `Input::BufRead`, so
+        // they only get here to render it.
+        let iter = matches!(self.input_type, Input::Iterator | Input::Chars);
+        if self.visits_all() {
+            return if iter {
+                root.render_iter_all(writer, self, hoisted)
+            } else {
+                root.render_slice_all(writer, self, hoisted)
+            };
+        }
+
+        match (iter, self.anchor) {
+            (false, Anchor::Prefix) => {
+                root.render_slice_with(writer, self, hoisted)
+            }
+            (true, Anchor::Prefix | Anchor::Suffix) => {
+                root.render_iter_with(writer, self, hoisted)
+            }
+            (false, Anchor::Suffix) => {
+                root.render_slice_suffix(writer, self, hoisted)
+            }
+            (false, Anchor::Exact) => {
+                root.render_slice_exact(writer, self, hoisted)
+            }
+            (true, Anchor::Exact) => {
+                root.render_iter_exact(writer, self, hoisted)
+            }
+        }
+    }
+
+    /// Render a function that matches the start of [`Input::BufRead`] input.
+    ///
+    /// The function wraps a slice matcher, and copies bytes into `pending`
+    /// when it needs to look past the end of the reader’s buffer.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_buf_read<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let inner = self.nested_slice_matcher()?;
+        if !self.disable_clippy {
+            writeln!(
+                writer,
+                "#[allow(clippy::arithmetic_side_effects, \
+                clippy::missing_errors_doc)]",
+            )?;
+        }
+        self.render_attributes(writer)?;
+        self.render_buf_read_start(writer, true)?;
+        crate::write_indented(writer, &inner)?;
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "    const MAX_LEN: usize = {};",
+            self.buf_read_max_len(),
+        )?;
+        writeln!(writer)?;
+        writeln!(writer, "    loop {{")?;
+        writeln!(writer, "        let buffer = reader.fill_buf()?;")?;
+        writeln!(writer, "        let at_end = buffer.is_empty();")?;
+        writeln!(
+            writer,
+            "        if pending.is_empty() && (at_end || buffer.len() >= MAX_LEN) {{",
+        )?;
+        writeln!(writer, "            let (value, rest) = matcher(buffer);")?;
+        writeln!(writer, "            let len = buffer.len() - rest.len();")?;
+        writeln!(writer, "            reader.consume(len);")?;
+        writeln!(writer, "            return Ok(value);")?;
+        writeln!(
+            writer,
+            "        }} else if at_end || pending.len() >= MAX_LEN {{",
+        )?;
+        writeln!(writer, "            let (value, rest) = matcher(pending);")?;
+        writeln!(writer, "            let len = pending.len() - rest.len();")?;
+        writeln!(writer, "            pending.drain(..len);")?;
+        writeln!(writer, "            return Ok(value);")?;
+        writeln!(writer, "        }}")?;
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "        // A match might cross the end of the buffer.",
+        )?;
+        writeln!(
+            writer,
+            "        let len = buffer.len().min(MAX_LEN - pending.len());",
+        )?;
+        writeln!(writer, "        pending.extend_from_slice(&buffer[..len]);")?;
+        writeln!(writer, "        reader.consume(len);")?;
+        writeln!(writer, "    }}")?;
+        writeln!(writer, "}}")
+    }
+
+    /// Render a function that matches the start of [`Input::OwnedIterator`]
+    /// input.
+    ///
+    /// The function wraps a slice matcher. It reads elements into a buffer
+    /// until the input can’t be the start of a longer match, then returns the
+    /// elements after the match.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_owned_iter<W: io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        let inner = self.nested_slice_matcher()?;
+        if !self.disable_clippy {
+            writeln!(
+                writer,
+                "#[allow(clippy::arithmetic_side_effects, \
+                clippy::unnested_or_patterns)]",
+            )?;
+        }
+        self.render_attributes(writer)?;
+
+        let pattern = incomplete_pattern(
+            &self.tree(),
+            &self.boundary,
+            self.ignore_ascii_case,
+        );
+        self.render_owned_iter_start(writer, pattern.is_some())?;
+        crate::write_indented(writer, &inner)?;
+        writeln!(writer)?;
+
+        if let Some(pattern) = pattern {
+            writeln!(
+                writer,
+                "    // Read while the input could be the start of a longer match.",
+            )?;
+            writeln!(writer, "    let mut buffer = Vec::new();")?;
+            writeln!(
+                writer,
+                "    while matches!(buffer.as_slice(), {}) {{",
+                pattern,
+            )?;
+            writeln!(writer, "        match iter.next() {{")?;
+            writeln!(
+                writer,
+                "            Some(element) => buffer.push(element),"
+            )?;
+            writeln!(writer, "            None => break,")?;
+            writeln!(writer, "        }}")?;
+            writeln!(writer, "    }}")?;
+            writeln!(writer)?;
+            writeln!(writer, "    let (value, rest) = matcher(&buffer);")?;
+            writeln!(writer, "    let len = buffer.len() - rest.len();")?;
+            writeln!(writer, "    buffer.drain(..len);")?;
+            writeln!(writer, "    (value, buffer)")?;
+        } else {
+            // No key is longer than the empty key, so don’t read anything.
+            writeln!(writer, "    (matcher(&[]).0, Vec::new())")?;
+        }
+        writeln!(writer, "}}")
+    }
+
+    /// Render a function that advances the input past the match in place. See
+    /// [`Input::InPlace`] and [`Input::Cursor`].
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_in_place<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let cursor = matches!(self.input_type, Input::Cursor);
+        // `Input::Cursor` only needs the length of the match, so it uses an
+        // index matcher, which can be called from a `const fn`.
+        let inner = self.nested_matcher(!cursor)?;
+        if cursor && !self.disable_clippy {
+            if self.return_slice {
+                writeln!(writer, "#[allow(clippy::arithmetic_side_effects)]")?;
+            } else {
+                // `split_at()` is only `const` in Rust 1.71 and later, but a
+                // `const fn` with `&mut` parameters already needs Rust 1.83.
+                writeln!(
+                    writer,
+                    "#[allow(clippy::arithmetic_side_effects, \
+                    clippy::incompatible_msrv)]",
+                )?;
+            }
+        }
+        self.render_attributes(writer)?;
+        self.render_in_place_start(writer, true)?;
+        crate::write_indented(writer, &inner)?;
+        writeln!(writer)?;
+
+        if cursor {
+            writeln!(writer, "    if *pos > input.len() {{")?;
+            writeln!(writer, "        return None;")?;
+            writeln!(writer, "    }}")?;
+            writeln!(writer, "    let (_, slice) = input.split_at(*pos);")?;
+            writeln!(writer, "    let (value, len) = matcher(slice);")?;
+            writeln!(writer, "    *pos += len;")?;
+        } else {
+            writeln!(writer, "    let slice = *input;")?;
+            writeln!(writer, "    let (value, rest) = matcher(slice);")?;
+            writeln!(writer, "    *input = rest;")?;
+        }
+        writeln!(writer, "    value")?;
+        writeln!(writer, "}}")
+    }
+
+    /// Render a function that matches `&str` input on `char`. See
+    /// [`Input::StrChars`].
+    ///
+    /// The function wraps an [`Input::Chars`] matcher, and gets the remainder
+    /// of the input from the iterator with `Chars::as_str()`.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_str_chars<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut matcher = self.clone();
+        matcher.fn_name = "fn matcher".to_owned();
+        matcher.input_type = Input::Chars;
+        matcher.doc = None;
+        matcher.must_use = true;
+        let mut inner = Vec::new();
+        matcher.render_func(&mut inner)?;
+
+        let prefix_index = self.anchor == Anchor::Prefix && !self.return_slice;
+        if prefix_index && !self.disable_clippy {
+            writeln!(writer, "#[allow(clippy::arithmetic_side_effects)]")?;
+        }
+        self.render_attributes(writer)?;
+        if self.anchor == Anchor::Exact {
+            writeln!(
+                writer,
+                "{}(s: &str) -> Option<{}> {{",
+                self.fn_name, self.return_type,
+            )?;
+        } else {
+            writeln!(
+                writer,
+                "{}(s: &str) -> (Option<{}>, {}) {{",
+                self.fn_name,
+                self.return_type,
+                self.remainder_type(),
+            )?;
+        }
+        crate::write_indented(writer, &inner)?;
+        writeln!(writer)?;
+
+        if self.anchor == Anchor::Exact {
+            writeln!(writer, "    matcher(s.chars())")?;
+        } else {
+            writeln!(writer, "    let mut iter = s.chars();")?;
+            writeln!(writer, "    let value = matcher(&mut iter);")?;
+            writeln!(
+                writer,
+                "    (value, {})",
+                if self.return_slice {
+                    // The rest of the input after (or before) the match.
+                    "iter.as_str()"
+                } else if prefix_index {
+                    "s.len() - iter.as_str().len()"
+                } else {
+                    "iter.as_str().len()"
+                },
+            )?;
+        }
+        writeln!(writer, "}}")
+    }
+
+    /// Render a slice matcher named `matcher` to nest inside a function that
+    /// wraps it, e.g. for [`Input::BufRead`].
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing the matcher.
+    fn nested_slice_matcher(&self) -> io::Result<Vec<u8>> {
+        self.nested_matcher(true)
+    }
+
+    /// Render a slice matcher named `matcher` to nest inside a function that
+    /// wraps it. It returns the remainder as a slice if `return_slice` is set,
+    /// or as an index otherwise (see [`Self::return_index()`]).
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing the matcher.
+    fn nested_matcher(&self, return_slice: bool) -> io::Result<Vec<u8>> {
+        let mut matcher = self.clone();
+        matcher.fn_name = "fn matcher".to_owned();
+        matcher.input_type = Input::Slice;
+        matcher.return_slice = return_slice;
+        matcher.incomplete_enum = None;
+        matcher.doc = None;
+        matcher.must_use = true;
+
+        let mut inner = Vec::new();
+        matcher.render_func(&mut inner)?;
+        Ok(inner)
+    }
+
+    /// Render a function that wraps a slice matcher and reports when the input
+    /// ends inside a possible key. See [`Self::incomplete_enum()`].
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_incomplete<W: io::Write>(
+        &self,
+        writer: &mut W,
+        enum_name: &str,
+    ) -> io::Result<()> {
+        let inner = self.nested_slice_matcher()?;
+        if !self.disable_clippy {
+            writeln!(
+                writer,
+                "#[allow(\n    \
+                clippy::missing_const_for_fn,\n    \
+                clippy::unnested_or_patterns,\n\
+                )]",
+            )?;
+        }
+        self.render_attributes(writer)?;
+        let pattern = incomplete_pattern(
+            &self.tree(),
+            &self.boundary,
+            self.ignore_ascii_case,
+        );
+        self.incomplete(enum_name).render_fn(
+            writer,
+            &inner,
+            false,
+            pattern.as_deref(),
+        )
+    }
+
+    /// Get the renderer for a function that maps values back to keys.
+    fn reverse(&self) -> Reverse<'_> {
+        Reverse {
+            matcher: find::fn_ident(&self.fn_name),
+            return_type: &self.return_type,
+            element_type: &self.element_type,
+            canonical: self.canonical,
+            value_patterns: &self.value_patterns,
+            order: self.order,
+            must_use: self.must_use,
+        }
+    }
+
+    /// Get the renderer for constants that list the keys and values.
+    fn key_tables(&self) -> Tables<'_> {
+        Tables {
+            matcher: find::fn_ident(&self.fn_name),
+            return_type: &self.return_type,
+            element_type: &self.element_type,
+            order: self.order,
+        }
+    }
+
+    /// Get the renderer for a function that reports incomplete input.
+    fn incomplete<'a>(&'a self, enum_name: &'a str) -> Incomplete<'a> {
+        Incomplete {
+            enum_name,
+            fn_name: &self.fn_name,
+            return_type: &self.return_type,
+            element_type: &self.element_type,
+        }
+    }
+
+    /// Render the signature of an [`Input::BufRead`] function.
+    ///
+    /// If the parameters aren’t `used`, their names start with `_`.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_buf_read_start<W: io::Write>(
+        &self,
+        writer: &mut W,
+        used: bool,
+    ) -> io::Result<()> {
+        let prefix = if used { "" } else { "_" };
+        writeln!(writer, "{}<R>(", self.fn_name)?;
+        writeln!(writer, "    {}reader: &mut R,", prefix)?;
+        writeln!(writer, "    {}pending: &mut Vec<u8>,", prefix)?;
+        writeln!(writer, ") -> std::io::Result<Option<{}>>", self.return_type)?;
+        writeln!(writer, "where")?;
+        writeln!(writer, "    R: std::io::BufRead + ?Sized,")?;
+        writeln!(writer, "{{")
+    }
+
+    /// Render the signature of an [`Input::OwnedIterator`] function.
+    ///
+    /// If the parameter isn’t `used`, its name starts with `_`.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_owned_iter_start<W: io::Write>(
+        &self,
+        writer: &mut W,
+        used: bool,
+    ) -> io::Result<()> {
+        writeln!(
+            writer,
+            "{}<I>({}iter: &mut I) -> (Option<{}>, Vec<{}>)",
+            self.fn_name,
+            if used { "" } else { "_" },
+            self.return_type,
+            self.element_type,
+        )?;
+        writeln!(writer, "where")?;
+        writeln!(
+            writer,
+            "    I: core::iter::Iterator<Item = {}> + ?Sized,",
+            self.element_type,
+        )?;
+        writeln!(writer, "{{")
+    }
+
+    /// Render the signature of an [`Input::InPlace`] or [`Input::Cursor`]
+    /// function.
+    ///
+    /// If the parameters aren’t `used`, their names start with `_`.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_in_place_start<W: io::Write>(
+        &self,
+        writer: &mut W,
+        used: bool,
+    ) -> io::Result<()> {
+        let prefix = if used { "" } else { "_" };
+        if matches!(self.input_type, Input::Cursor) {
+            writeln!(
+                writer,
+                "{fn_name}({prefix}input: &[{element_type}], {prefix}pos: &mut usize) -> Option<{return_type}> {{",
+                fn_name = self.fn_name(),
+                prefix = prefix,
+                element_type = self.element_type,
+                return_type = self.return_type,
+            )
+        } else {
+            writeln!(
+                writer,
+                "{fn_name}({prefix}input: &mut &[{element_type}]) -> Option<{return_type}> {{",
+                fn_name = self.fn_name,
+                prefix = prefix,
+                element_type = self.element_type,
+                return_type = self.return_type,
+            )
+        }
+    }
+
+    /// Get the number of bytes an [`Input::BufRead`] function needs to look
+    /// ahead: the length of the longest key, plus a byte to check
+    /// [`Self::boundary`].
+    fn buf_read_max_len(&self) -> usize {
+        let len = self
+            .entries
+            .iter()
+            .map(|(key, _)| key.len())
+            .max()
+            .unwrap_or(0);
+        if self.boundary == Boundary::Any {
+            len.max(1)
+        } else {
+            len.checked_add(1).unwrap()
+        }
+    }
+
+    /// Build the tree of nodes to render, with branches sorted by
+    /// [`Self::order`].
+    fn tree(&self) -> TreeNode<E> {
+        self.build_tree(crate::normalize_entries(
+            &self.entries,
+            self.ignore_ascii_case,
+        ))
+    }
+
+    /// Build a tree of nodes that branch on `char` to render for
+    /// [`Input::Chars`], with branches sorted by [`Self::order`].
+    ///
+    /// Keys must be valid UTF-8; see [`Self::check_keys()`].
+    fn char_tree(&self) -> TreeNode<char> {
+        self.build_tree(
+            crate::normalize_entries(&self.entries, self.ignore_ascii_case)
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = E::as_bytes(&key).unwrap_or_default();
+                    (String::from_utf8_lossy(key).chars().collect(), value)
+                })
+                .collect(),
+        )
+    }
+
+    /// Build a tree of nodes from normalized entries, with branches sorted by
+    /// [`Self::order`].
+    fn build_tree<T: Element>(
+        &self,
+        mut entries: Vec<(Vec<T>, &str)>,
+    ) -> TreeNode<T> {
+        if self.anchor == Anchor::Suffix {
+            // Suffixes are matched from the end of the input, so reverse them
+            // to find which ones are suffixes of others.
+            for (key, _) in &mut entries {
+                key.reverse();
+            }
+        }
+
+        if self.anchor != Anchor::Exact {
+            entries = self.semantics.filter(
+                entries,
+                &self.boundary,
+                self.ignore_ascii_case,
+            );
+        }
+
+        let mut root: TreeNode<T> =
+            entries.iter().map(|(key, value)| (key, *value)).collect();
+        root.sort(self.order);
+        root
+    }
+
+    /// Check if the generated function should call a visitor for every match.
+    /// See [`Semantics::All`].
+    fn visits_all(&self) -> bool {
+        self.semantics == Semantics::All && self.anchor != Anchor::Exact
+    }
+
+    /// Get the name of the trait for iterator input.
+    fn iter_trait(&self) -> &'static str {
+        if self.anchor == Anchor::Suffix {
+            "DoubleEndedIterator"
+        } else {
+            "Iterator"
+        }
+    }
+
+    /// Get the method to get the next item from iterator input.
+    fn iter_next(&self) -> &'static str {
+        if self.anchor == Anchor::Suffix {
+            "next_back"
+        } else {
+            "next"
+        }
+    }
+
+    /// Check if the generated function accepts `&str`. See [`Input::Str`]
+    /// and [`Input::StrChars`].
+    const fn str_input(&self) -> bool {
+        matches!(self.input_type, Input::Str | Input::StrChars)
+    }
+
+    /// Check if the generated function accepts `&str` and matches it on
+    /// `char`. See [`Input::StrChars`].
+    const fn str_chars_input(&self) -> bool {
+        matches!(self.input_type, Input::StrChars)
+    }
+
+    /// Check if the generated function accepts an iterator over `char`. See
+    /// [`Input::Chars`].
+    const fn char_input(&self) -> bool {
+        matches!(self.input_type, Input::Chars)
+    }
+
+    /// Check if the generated function accepts [`std::io::BufRead`]. See
+    /// [`Input::BufRead`].
+    const fn buf_read_input(&self) -> bool {
+        matches!(self.input_type, Input::BufRead)
+    }
+
+    /// Check if the generated function accepts an iterator over owned
+    /// elements. See [`Input::OwnedIterator`].
+    const fn owned_iter_input(&self) -> bool {
+        matches!(self.input_type, Input::OwnedIterator)
+    }
+
+    /// Check if the generated function advances its input in place. See
+    /// [`Input::InPlace`] and [`Input::Cursor`].
+    const fn in_place_input(&self) -> bool {
+        matches!(self.input_type, Input::InPlace | Input::Cursor)
+    }
+
+    /// Get the type of slice or `&str` input.
+    fn slice_type(&self) -> String {
+        if self.str_input() {
+            "&str".to_owned()
+        } else {
+            format!("&[{}]", self.element_type)
+        }
+    }
+
+    /// Get the parameter for slice or `&str` input, e.g. `"slice: &[u8]"`.
+    ///
+    /// If the input isn’t `used`, the parameter name starts with `_`.
+    fn slice_param(&self, used: bool) -> String {
+        format!(
+            "{}{}: {}",
+            if used { "" } else { "_" },
+            if self.str_input() { "s" } else { "slice" },
+            self.slice_type(),
+        )
+    }
+
+    /// Render a statement to get `slice` from `&str` input if it’s `used`.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_str_bytes<W: io::Write>(
+        &self,
+        writer: &mut W,
+        used: bool,
+    ) -> io::Result<()> {
+        if self.str_input() && used {
+            writeln!(writer, "    let slice = s.as_bytes();")?;
+        }
+        Ok(())
+    }
+
+    /// Get the type of the remainder returned with a match: the type of the
+    /// input, or `usize` with [`Self::return_index()`].
+    fn remainder_type(&self) -> String {
+        if self.return_slice {
+            self.slice_type()
+        } else {
+            "usize".to_owned()
+        }
+    }
+
+    /// Get the input after the first `index` bytes, or `index` with
+    /// [`Self::return_index()`].
+    fn rest_str(&self, index: usize) -> String {
+        if !self.return_slice {
+            index.to_string()
+        } else if !self.str_input() {
+            slice_str(index)
+        } else if index > 0 {
+            format!("&s[{}..]", index)
+        } else {
+            "s".to_owned()
+        }
+    }
+
+    /// Get the input before the last `index` bytes, which are bound to
+    /// `before_str(index)` as a slice, or its length with
+    /// [`Self::return_index()`].
+    fn before_rest_str(&self, index: usize) -> String {
+        let input = if self.str_input() { "s" } else { "slice" };
+        if !self.return_slice {
+            if index > 0 {
+                format!("{}.len()", before_str(index))
+            } else {
+                format!("{}.len()", input)
+            }
+        } else if !self.str_input() {
+            before_str(index)
+        } else if index > 0 {
+            format!("&s[..{}.len()]", before_str(index))
+        } else {
+            "s".to_owned()
+        }
+    }
+
+    /// Get the slice after the first `index` bytes to match on.
+    ///
+    /// With [`Self::return_index()`], this is bound by
+    /// [`Self::after_binding()`], since `&slice[n..]` isn’t allowed in a
+    /// `const fn`.
+    fn after_slice_str(&self, index: usize) -> String {
+        if self.return_slice {
+            slice_str(index)
+        } else {
+            after_str(index)
+        }
+    }
+
+    /// Get the binding for the rest of a slice pattern that ends after the
+    /// first `index` bytes, e.g. `"after_2 @ "`, or an empty string if it
+    /// isn’t `used` or isn’t needed. See [`Self::after_slice_str()`].
+    fn after_binding(&self, index: usize, used: bool) -> String {
+        if used && !self.return_slice {
+            format!("{} @ ", after_str(index))
+        } else {
+            String::new()
+        }
+    }
+
+    /// Get the function name with its modifiers.
+    ///
+    /// This will add `const` in the appropriate place with
+    /// [`Self::return_index()`].
+    fn fn_name(&self) -> String {
+        if self.return_slice
+            || self.visits_all()
+            || !matches!(
+                self.input_type,
+                Input::Slice | Input::Str | Input::Cursor
+            )
+        {
+            self.fn_name.clone()
+        } else if self.fn_name.starts_with("fn ") {
+            format!("const {}", self.fn_name)
+        } else {
+            self.fn_name.replace(" fn ", " const fn ")
+        }
+    }
+
+    /// Check that keys can be matched with the current settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::Error`] if [`Input::Str`], [`Input::StrChars`], or
+    /// [`Input::Chars`] is used and a key isn’t valid UTF-8, since a match
+    /// could then end in the middle of a character, or if the keys aren’t
+    /// bytes. Also returns [`io::Error`] if [`Self::element_type`] isn’t set
+    /// for [`Pattern`][crate::Pattern] keys or the patterns might overlap, if
+    /// [`Input::BufRead`] is used
+    /// with keys that aren’t bytes, if [`Input::StrChars`] is used with
+    /// [`Semantics::All`], or if an input type that only supports
+    /// [`Anchor::Prefix`] is used with other settings.
+    fn check_keys(&self) -> io::Result<()> {
+        check_element_type(&self.element_type)?;
+        self.tree().check_overlap()?;
+        if self.str_chars_input() && self.visits_all() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Input::StrChars can’t be used with Semantics::All",
+            ));
+        }
+        if (self.buf_read_input()
+            || self.owned_iter_input()
+            || self.in_place_input())
+            && (self.anchor != Anchor::Prefix || self.visits_all())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Input::{:?} requires Anchor::Prefix, and can’t be used \
+                    with Semantics::All",
+                    self.input_type,
+                ),
+            ));
+        }
+        if self.str_input() || self.char_input() || self.buf_read_input() {
+            for (key, _) in &self.entries {
+                let key = E::as_bytes(key).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Input::{:?} requires keys to be bytes (u8)",
+                            self.input_type,
+                        ),
+                    )
+                })?;
+                if !self.buf_read_input() && std::str::from_utf8(key).is_err() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!(
+                            "Input::{:?} requires keys to be valid UTF-8, \
+                            but got {:?}",
+                            self.input_type,
+                            String::from_utf8_lossy(key),
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Render attributes for the function or stub.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    fn render_attributes<W: io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        if let Some(doc) = &self.doc {
+            writeln!(writer, "{}", doc)?;
+        }
+
+        // `io::Result` is already `#[must_use]`.
+        if self.must_use && !self.visits_all() && !self.buf_read_input() {
+            writeln!(writer, "#[must_use]")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, E, K, V> Extend<(K, V)> for TreeMatcher<E>
+where
+    E: Element + 'a,
+    K: IntoIterator<Item = &'a E>,
+    V: Into<String>,
+{
+    /// Add matches from iterator or collection.
+    ///
+    /// See trait documentation in [`core::iter::Extend`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// let mut matcher = matchgen::TreeMatcher::new("fn match_bytes", "u64");
+    /// matcher.extend([("a".as_bytes(), "1")]);
+    /// matcher.render(&mut out).unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     match slice {
+    ///         [b'a', ..] => (Some(1), &slice[1..]),
+    ///         _ => (None, slice),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|(key, value)| {
+            self.add(key, value);
+        });
+    }
+}
+
+/// What kind of input the matcher should accept: a slice, an iterator, a
+/// string slice, an iterator over `char`, a [`std::io::BufRead`], an iterator
+/// over owned bytes, or a slice that’s advanced in place.
+#[derive(Clone, Copy, Debug)]
+pub enum Input {
+    /// Accept a slice of bytes.
+    ///
+    /// The generated function will accept a slice of bytes (`&[u8]`) and will
     /// return a tuple containing the match, if any, and the remainder of the
     /// slice, i.e. `(Option<{return_type}>, &[u8])`.
     ///
@@ -688,12 +3245,15 @@ pub enum Input {
     /// ```
     ///
     ///   * The prefixes it checks do not all have to be the same length.
-    ///   * If more than one prefix matches, it will return the longest one.
+    ///   * If more than one prefix matches, it will return the longest one
+    ///     (see [`TreeMatcher::semantics()`]).
     ///   * If nothing matches, it will return `(None, &input)`.
     ///
     /// Since the matchers only check the start of the input, you will want to
     /// use [`iter().position()`] or the [memchr crate][memchr] to find the
-    /// start of a potential match.
+    /// start of a potential match, or generate a function that does that with
+    /// [`TreeMatcher::find_fn()`] or [`TreeMatcher::find_iter_fn()`]. To
+    /// replace every match with its value, see [`TreeMatcher::replace_fn()`].
     ///
     /// [`iter().position()`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.position
     /// [memchr]: http://docs.rs/memchr
@@ -707,30 +3267,631 @@ pub enum Input {
     /// finds a given byte sequence at the start of the iterator. It will update
     /// the iterator to move past the match (and no farther).
     ///
-    /// For example, suppose you generate a [matcher for all HTML
-    /// entities][htmlize] called `entity_matcher()`:
+    /// For example, suppose you generate a [matcher for all HTML
+    /// entities][htmlize] called `entity_matcher()`:
+    ///
+    /// ```rust,ignore
+    /// let mut iter = b"&times;XY".iter();
+    /// assert!(entity_matcher(&mut iter) == Some("×"));
+    /// assert!(iter.next() == Some(b'X'));
+    /// ```
+    ///
+    ///   * **The prefixes it checks do not all have to be the same length.**
+    ///     The matcher will clone the iterator if it needs to look ahead, so
+    ///     when the matcher returns the iterator will only have consumed what
+    ///     was matched.
+    ///   * **If more than one prefix matches, it will return the longest one.**
+    ///     See [`TreeMatcher::semantics()`].
+    ///   * **If nothing matches, the iterator will not be advanced.** You may
+    ///     want to call `iterator.next()` if the matcher returns `None`.
+    ///   * **It only checks the start of the iterator.** Often you will want to
+    ///     use [`position()`] or the [memchr crate][memchr] to find the start
+    ///     of a potential match. [`TreeMatcher::find_fn()`] can generate a
+    ///     function to do that for a slice.
+    ///
+    /// [`position()`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.position
+    /// [memchr]: http://docs.rs/memchr
+    /// [htmlize]: https://crates.io/crates/htmlize
+    Iterator,
+
+    /// Accept a string slice.
+    ///
+    /// The generated function will accept `&str` and return the match, if
+    /// any, and the remainder of the `&str` after it (or before it, with
+    /// [`Anchor::Suffix`]), i.e. `(Option<{return_type}>, &str)`. Otherwise it
+    /// works exactly like [`Input::Slice`].
+    ///
+    /// All keys must be valid UTF-8, so that a match can’t end in the middle
+    /// of a character. [`TreeMatcher::render()`] returns an error otherwise.
+    /// This means the remainder is just a subslice of the input; it doesn’t
+    /// need to be checked again or converted with `unsafe` code.
+    ///
+    /// The generated function branches on bytes, like [`Input::Slice`], not
+    /// on `char` like [`Input::Chars`]. Since the keys are valid UTF-8, a byte
+    /// match always ends on a character boundary, and matching bytes keeps
+    /// the generated code a slice `match` that is as fast as the
+    /// [`Input::Slice`] version. To branch on `char` instead, use
+    /// [`Input::StrChars`].
+    ///
+    /// ```rust
+    /// use matchgen::{Input, TreeMatcher};
+    ///
+    /// let mut matcher = TreeMatcher::new("fn match_str", "u64");
+    /// matcher.input_type(Input::Str).add(b"\xC3", "1");
+    /// assert!(matcher.render(&mut Vec::new()).is_err());
+    /// ```
+    ///
+    /// Find and replace functions and tokenizers (e.g.
+    /// [`TreeMatcher::find_fn()`]) aren’t supported with this input type.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{Input, TreeMatcher};
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn match_str", "u64")
+    ///     .input_type(Input::Str)
+    ///     .add("×".as_bytes(), "1")
+    ///     .add("÷".as_bytes(), "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_str(s: &str) -> (Option<u64>, &str) {
+    ///     let slice = s.as_bytes();
+    ///     match slice {
+    ///         [195, ..] => match &slice[1..] {
+    ///             [151, ..] => (Some(1), &s[2..]),
+    ///             [183, ..] => (Some(2), &s[2..]),
+    ///             _ => (None, s),
+    ///         }
+    ///         _ => (None, s),
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    Str,
+
+    /// Accept an iterator over `char`.
+    ///
+    /// The generated function will accept an iterator over characters
+    /// (`core::iter::Iterator<Item = char>`), and otherwise works exactly
+    /// like [`Input::Iterator`]. The matcher branches on `char` rather than
+    /// on bytes, so multi-byte keys like emoji and math symbols produce a
+    /// shallow `match` on readable `char` literals instead of a deep chain of
+    /// `match` statements on bytes.
+    ///
+    /// To match a `&str`, use [`Input::StrChars`], or pass [`str::chars()`].
+    /// Afterward, [`as_str()`] returns the remainder:
+    ///
+    /// ```rust,ignore
+    /// let mut iter = "×÷XY".chars();
+    /// assert!(op_matcher(&mut iter) == Some(Op::TimesDivide));
+    /// assert!(iter.as_str() == "XY");
+    /// ```
+    ///
+    /// All keys must be valid UTF-8. [`TreeMatcher::render()`] returns an
+    /// error otherwise. With [`TreeMatcher::boundary()`], ASCII characters
+    /// are handled as bytes would be, and other characters are never in a
+    /// [`Boundary::NotFollowedBy`] class.
+    ///
+    /// Find and replace functions and tokenizers (e.g.
+    /// [`TreeMatcher::find_fn()`]) aren’t supported with this input type.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{Input, TreeMatcher};
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn match_chars", "u64")
+    ///     .input_type(Input::Chars)
+    ///     .add("×".as_bytes(), "1")
+    ///     .add("÷".as_bytes(), "2")
+    ///     .add("×÷".as_bytes(), "3")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(
+    ///     clippy::match_same_arms,
+    ///     clippy::missing_const_for_fn,
+    ///     clippy::single_match_else,
+    ///     clippy::too_many_lines,
+    /// )]
+    /// #[must_use]
+    /// fn match_chars<I>(iter: &mut I) -> Option<u64>
+    /// where
+    ///     I: core::iter::Iterator<Item = char> + core::clone::Clone,
+    /// {
+    ///     let fallback_iter = iter.clone();
+    ///     match iter.next() {
+    ///         Some('×') => {
+    ///             let fallback_iter = iter.clone();
+    ///             match iter.next() {
+    ///                 Some('÷') => Some(3),
+    ///                 _ => {
+    ///                     *iter = fallback_iter;
+    ///                     Some(1)
+    ///                 }
+    ///             }
+    ///         }
+    ///         Some('÷') => Some(2),
+    ///         _ => {
+    ///             *iter = fallback_iter;
+    ///             None
+    ///         }
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    ///
+    /// [`as_str()`]: https://doc.rust-lang.org/std/str/struct.Chars.html#method.as_str
+    Chars,
+
+    /// Accept a string slice, and match it on `char` rather than on bytes.
+    ///
+    /// The generated function takes and returns the same types as
+    /// [`Input::Str`]. Inside, it runs an [`Input::Chars`] matcher on
+    /// [`str::chars()`], so multi-byte keys like emoji and math symbols
+    /// produce a shallow `match` on readable `char` literals. [`Input::Str`]
+    /// is faster, since it matches bytes in a slice `match` and can be
+    /// `const`.
+    ///
+    /// With [`TreeMatcher::return_index()`], the index is in bytes, as with
+    /// [`Input::Str`].
+    ///
+    /// All keys must be valid UTF-8. [`Semantics::All`] isn’t supported, nor
+    /// are find and replace functions and tokenizers (e.g.
+    /// [`TreeMatcher::find_fn()`]). [`TreeMatcher::render()`] returns an error
+    /// otherwise.
+    ///
+    /// ```rust
+    /// use matchgen::{Input, Semantics, TreeMatcher};
+    ///
+    /// let mut matcher = TreeMatcher::new("fn match_str", "u64");
+    /// matcher
+    ///     .input_type(Input::StrChars)
+    ///     .semantics(Semantics::All)
+    ///     .add(b"a", "1");
+    /// assert!(matcher.render(&mut Vec::new()).is_err());
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{Input, TreeMatcher};
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn match_str", "u64")
+    ///     .input_type(Input::StrChars)
+    ///     .add("×".as_bytes(), "1")
+    ///     .add("÷".as_bytes(), "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[must_use]
+    /// fn match_str(s: &str) -> (Option<u64>, &str) {
+    ///     #[allow(
+    ///         clippy::match_same_arms,
+    ///         clippy::missing_const_for_fn,
+    ///         clippy::single_match_else,
+    ///         clippy::too_many_lines,
+    ///     )]
+    ///     #[must_use]
+    ///     fn matcher<I>(iter: &mut I) -> Option<u64>
+    ///     where
+    ///         I: core::iter::Iterator<Item = char> + core::clone::Clone,
+    ///     {
+    ///         let fallback_iter = iter.clone();
+    ///         match iter.next() {
+    ///             Some('×') => Some(1),
+    ///             Some('÷') => Some(2),
+    ///             _ => {
+    ///                 *iter = fallback_iter;
+    ///                 None
+    ///             }
+    ///         }
+    ///     }
+    ///
+    ///     let mut iter = s.chars();
+    ///     let value = matcher(&mut iter);
+    ///     (value, iter.as_str())
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    StrChars,
+
+    /// Accept a reader that implements [`std::io::BufRead`].
+    ///
+    /// The generated function will accept `&mut R` where `R: BufRead`, and a
+    /// `&mut Vec<u8>` of pending bytes. It returns
+    /// `std::io::Result<Option<{return_type}>>`. It peeks at the input with
+    /// [`fill_buf()`], and if it finds a match, it consumes only the bytes of
+    /// the match.
+    ///
+    /// `BufRead` can’t put bytes back once they’re consumed, so when a match
+    /// might cross the end of the reader’s buffer, the function moves the
+    /// bytes it needs to look at into `pending`. It never moves more than the
+    /// length of the longest key (plus one byte if [`TreeMatcher::boundary()`]
+    /// is set), and it doesn’t allocate unless that happens.
+    ///
+    /// **The input is the bytes in `pending` followed by the bytes in the
+    /// reader.** Pass the same `pending` to every call, and read from it
+    /// before the reader:
+    ///
+    ///   * If a key matches, it is removed from the input and its value is
+    ///     returned.
+    ///   * If nothing matches, the function returns `Ok(None)`. The input is
+    ///     unchanged, but **the reader may still have been advanced**: the
+    ///     bytes it looked at may have been moved from the reader into
+    ///     `pending`. Don’t read from the reader directly without emptying
+    ///     `pending` first.
+    ///
+    /// For example, suppose you generate a [matcher for all HTML
+    /// entities][htmlize] called `entity_matcher()`:
+    ///
+    /// ```rust,ignore
+    /// /// Find the length of the bytes to skip to the next possible match.
+    /// fn skip(bytes: &[u8]) -> usize {
+    ///     (1..bytes.len())
+    ///         .find(|&i| bytes[i] == b'&')
+    ///         .unwrap_or(bytes.len())
+    /// }
+    ///
+    /// let mut reader = std::io::BufReader::new(file);
+    /// let mut pending = Vec::new();
+    /// let mut out = Vec::new();
+    /// loop {
+    ///     if let Some(value) = entity_matcher(&mut reader, &mut pending)? {
+    ///         out.extend_from_slice(value.as_bytes());
+    ///     } else if !pending.is_empty() {
+    ///         // Copy pending bytes through until the next possible match.
+    ///         let len = skip(&pending);
+    ///         out.extend(pending.drain(..len));
+    ///     } else {
+    ///         // Copy bytes through until the next possible match.
+    ///         let buffer = reader.fill_buf()?;
+    ///         if buffer.is_empty() {
+    ///             break;
+    ///         }
+    ///         let len = skip(buffer);
+    ///         out.extend_from_slice(&buffer[..len]);
+    ///         reader.consume(len);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Keys must be bytes, and [`TreeMatcher::anchor()`] must be
+    /// [`Anchor::Prefix`]. [`Semantics::All`] isn’t supported, nor are find
+    /// and replace functions and tokenizers (e.g. [`TreeMatcher::find_fn()`]).
+    /// [`TreeMatcher::render()`] returns an error otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{Input, TreeMatcher};
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn read_op", "u64")
+    ///     .input_type(Input::BufRead)
+    ///     .add(b"<", "1")
+    ///     .add(b"<=", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(clippy::arithmetic_side_effects, clippy::missing_errors_doc)]
+    /// fn read_op<R>(
+    ///     reader: &mut R,
+    ///     pending: &mut Vec<u8>,
+    /// ) -> std::io::Result<Option<u64>>
+    /// where
+    ///     R: std::io::BufRead + ?Sized,
+    /// {
+    ///     #[allow(
+    ///         clippy::match_same_arms,
+    ///         clippy::missing_const_for_fn,
+    ///         clippy::single_match_else,
+    ///         clippy::too_many_lines,
+    ///     )]
+    ///     #[must_use]
+    ///     fn matcher(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///         match slice {
+    ///             [b'<', ..] => match &slice[1..] {
+    ///                 [b'=', ..] => (Some(2), &slice[2..]),
+    ///                 _ => (Some(1), &slice[1..]),
+    ///             }
+    ///             _ => (None, slice),
+    ///         }
+    ///     }
+    ///
+    ///     const MAX_LEN: usize = 2;
+    ///
+    ///     loop {
+    ///         let buffer = reader.fill_buf()?;
+    ///         let at_end = buffer.is_empty();
+    ///         if pending.is_empty() && (at_end || buffer.len() >= MAX_LEN) {
+    ///             let (value, rest) = matcher(buffer);
+    ///             let len = buffer.len() - rest.len();
+    ///             reader.consume(len);
+    ///             return Ok(value);
+    ///         } else if at_end || pending.len() >= MAX_LEN {
+    ///             let (value, rest) = matcher(pending);
+    ///             let len = pending.len() - rest.len();
+    ///             pending.drain(..len);
+    ///             return Ok(value);
+    ///         }
+    ///
+    ///         // A match might cross the end of the buffer.
+    ///         let len = buffer.len().min(MAX_LEN - pending.len());
+    ///         pending.extend_from_slice(&buffer[..len]);
+    ///         reader.consume(len);
+    ///     }
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    ///
+    /// [`fill_buf()`]: std::io::BufRead::fill_buf
+    /// [htmlize]: https://crates.io/crates/htmlize
+    BufRead,
+
+    /// Accept an iterator over owned bytes that can’t be cloned.
+    ///
+    /// The generated function will accept any iterator over bytes
+    /// (`core::iter::Iterator<Item = u8>`), such as a decompressor or
+    /// [`std::io::Read::bytes()`] mapped through a function that handles
+    /// errors. [`Input::Iterator`] requires `Clone` so that it can rewind the
+    /// iterator after looking ahead; this doesn’t, so it returns the bytes it
+    /// read past the match instead: `(Option<{return_type}>, Vec<u8>)`.
+    ///
+    /// The function reads bytes into a buffer until the input can’t be the
+    /// start of a longer match, so it reads at most the length of the longest
+    /// key (plus one byte if [`TreeMatcher::boundary()`] is set). The caller
+    /// should process the returned bytes before reading more from the
+    /// iterator:
+    ///
+    /// ```rust,ignore
+    /// let (value, lookahead) = op_matcher(&mut iter);
+    /// let rest = lookahead.into_iter().chain(iter);
+    /// ```
+    ///
+    /// With [`TreeMatcher::new_generic()`], this accepts an iterator over
+    /// owned elements of any type, e.g. tokens that can’t be cloned.
+    ///
+    /// [`TreeMatcher::anchor()`] must be [`Anchor::Prefix`].
+    /// [`Semantics::All`] isn’t supported, nor are find and replace functions
+    /// and tokenizers (e.g. [`TreeMatcher::find_fn()`]).
+    /// [`TreeMatcher::render()`] returns an error otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{Input, TreeMatcher};
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn next_op", "u64")
+    ///     .input_type(Input::OwnedIterator)
+    ///     .add(b"<", "1")
+    ///     .add(b"<=", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(clippy::arithmetic_side_effects, clippy::unnested_or_patterns)]
+    /// #[must_use]
+    /// fn next_op<I>(iter: &mut I) -> (Option<u64>, Vec<u8>)
+    /// where
+    ///     I: core::iter::Iterator<Item = u8> + ?Sized,
+    /// {
+    ///     #[allow(
+    ///         clippy::match_same_arms,
+    ///         clippy::missing_const_for_fn,
+    ///         clippy::single_match_else,
+    ///         clippy::too_many_lines,
+    ///     )]
+    ///     #[must_use]
+    ///     fn matcher(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///         match slice {
+    ///             [b'<', ..] => match &slice[1..] {
+    ///                 [b'=', ..] => (Some(2), &slice[2..]),
+    ///                 _ => (Some(1), &slice[1..]),
+    ///             }
+    ///             _ => (None, slice),
+    ///         }
+    ///     }
+    ///
+    ///     // Read while the input could be the start of a longer match.
+    ///     let mut buffer = Vec::new();
+    ///     while matches!(buffer.as_slice(), [] | [b'<']) {
+    ///         match iter.next() {
+    ///             Some(element) => buffer.push(element),
+    ///             None => break,
+    ///         }
+    ///     }
+    ///
+    ///     let (value, rest) = matcher(&buffer);
+    ///     let len = buffer.len() - rest.len();
+    ///     buffer.drain(..len);
+    ///     (value, buffer)
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    OwnedIterator,
+
+    /// Accept a mutable reference to a slice of bytes, and advance it past the
+    /// match.
+    ///
+    /// The generated function will accept `&mut &[u8]` and return
+    /// `Option<{return_type}>`. If a key matches, the slice is set to the
+    /// input after the match; otherwise, it is unchanged. This saves binding
+    /// the remainder returned by [`Input::Slice`] at every call site:
+    ///
+    /// ```rust,ignore
+    /// let mut input = &b"&lt;&gt;"[..];
+    /// while let Some(value) = entity_matcher(&mut input) {
+    ///     out.push_str(value);
+    /// }
+    /// ```
+    ///
+    /// [`TreeMatcher::anchor()`] must be [`Anchor::Prefix`].
+    /// [`Semantics::All`] isn’t supported, nor are find and replace functions
+    /// and tokenizers (e.g. [`TreeMatcher::find_fn()`]).
+    /// [`TreeMatcher::render()`] returns an error otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{Input, TreeMatcher};
+    ///
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn advance_op", "u64")
+    ///     .input_type(Input::InPlace)
+    ///     .add(b"<", "1")
+    ///     .add(b"<=", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[must_use]
+    /// fn advance_op(input: &mut &[u8]) -> Option<u64> {
+    ///     #[allow(
+    ///         clippy::match_same_arms,
+    ///         clippy::missing_const_for_fn,
+    ///         clippy::single_match_else,
+    ///         clippy::too_many_lines,
+    ///     )]
+    ///     #[must_use]
+    ///     fn matcher(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///         match slice {
+    ///             [b'<', ..] => match &slice[1..] {
+    ///                 [b'=', ..] => (Some(2), &slice[2..]),
+    ///                 _ => (Some(1), &slice[1..]),
+    ///             }
+    ///             _ => (None, slice),
+    ///         }
+    ///     }
+    ///
+    ///     let slice = *input;
+    ///     let (value, rest) = matcher(slice);
+    ///     *input = rest;
+    ///     value
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    InPlace,
+
+    /// Accept a slice of bytes and a position in it, and advance the position
+    /// past the match.
+    ///
+    /// The generated function will accept `&[u8]` and `&mut usize`, and
+    /// return `Option<{return_type}>`. It matches the input starting at the
+    /// position. If a key matches, the position is moved to the end of the
+    /// match; otherwise, it is unchanged.
+    ///
+    /// If the position is past the end of the input, the function returns
+    /// `None` without matching anything, even the empty key, and leaves the
+    /// position unchanged. (A position equal to the length of the input is
+    /// the end of the input, so only the empty key can match there.)
+    ///
+    /// The function only does index arithmetic on the input, so with
+    /// [`TreeMatcher::return_index()`] it is a `const fn`. **That requires
+    /// Rust 1.83 or later,** since earlier versions don’t allow `&mut`
+    /// parameters in a `const fn`. Without `return_index()`, the function
+    /// isn’t `const` and works with older versions of Rust.
+    ///
+    /// This is useful in parsers that track their position as an index, since
+    /// the caller never has to slice the input:
+    ///
+    /// ```rust,ignore
+    /// let mut pos = 0;
+    /// while let Some(value) = entity_matcher(input, &mut pos) {
+    ///     out.push_str(value);
+    /// }
+    /// let rest = &input[pos..];
+    /// ```
+    ///
+    /// [`TreeMatcher::anchor()`] must be [`Anchor::Prefix`].
+    /// [`Semantics::All`] isn’t supported, nor are find and replace functions
+    /// and tokenizers (e.g. [`TreeMatcher::find_fn()`]).
+    /// [`TreeMatcher::render()`] returns an error otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use matchgen::{Input, TreeMatcher};
     ///
-    /// ```rust,ignore
-    /// let mut iter = b"&times;XY".iter();
-    /// assert!(entity_matcher(&mut iter) == Some("×"));
-    /// assert!(iter.next() == Some(b'X'));
-    /// ```
+    /// let mut out = Vec::new();
+    /// TreeMatcher::new("fn cursor_op", "u64")
+    ///     .input_type(Input::Cursor)
+    ///     .add(b"<", "1")
+    ///     .add(b"<=", "2")
+    ///     .render(&mut out)
+    ///     .unwrap();
     ///
-    ///   * **The prefixes it checks do not all have to be the same length.**
-    ///     The matcher will clone the iterator if it needs to look ahead, so
-    ///     when the matcher returns the iterator will only have consumed what
-    ///     was matched.
-    ///   * **If more than one prefix matches, it will return the longest one.**
-    ///   * **If nothing matches, the iterator will not be advanced.** You may
-    ///     want to call `iterator.next()` if the matcher returns `None`.
-    ///   * **It only checks the start of the iterator.** Often you will want to
-    ///     use [`position()`] or the [memchr crate][memchr] to find the start
-    ///     of a potential match.
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     r#"#[allow(clippy::arithmetic_side_effects)]
+    /// #[must_use]
+    /// fn cursor_op(input: &[u8], pos: &mut usize) -> Option<u64> {
+    ///     #[allow(
+    ///         clippy::collapsible_match,
+    ///         clippy::match_same_arms,
+    ///         clippy::missing_const_for_fn,
+    ///         clippy::single_match_else,
+    ///         clippy::too_many_lines,
+    ///     )]
+    ///     #[must_use]
+    ///     const fn matcher(slice: &[u8]) -> (Option<u64>, usize) {
+    ///         match slice {
+    ///             [b'<', after_1 @ ..] => match after_1 {
+    ///                 [b'=', ..] => (Some(2), 2),
+    ///                 _ => (Some(1), 1),
+    ///             }
+    ///             _ => (None, 0),
+    ///         }
+    ///     }
     ///
-    /// [`position()`]: https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.position
-    /// [memchr]: http://docs.rs/memchr
-    /// [htmlize]: https://crates.io/crates/htmlize
-    Iterator,
+    ///     if *pos > input.len() {
+    ///         return None;
+    ///     }
+    ///     let (_, slice) = input.split_at(*pos);
+    ///     let (value, len) = matcher(slice);
+    ///     *pos += len;
+    ///     value
+    /// }
+    /// "#,
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    Cursor,
 }
 
 impl Default for Input {
@@ -742,8 +3903,8 @@ impl Default for Input {
 /// A node in a tree matcher’s simple finite-state automaton.
 ///
 /// You probably want to use [`TreeMatcher`] instead.
-#[derive(Clone, Debug, Default)]
-pub struct TreeNode {
+#[derive(Clone, Debug)]
+pub struct TreeNode<E: Element = u8> {
     /// If the matcher gets to this node and `leaf` is `Some(_)`, then we found
     /// a (potential) match.
     ///
@@ -756,10 +3917,24 @@ pub struct TreeNode {
     ///
     /// If none of these characters match, then return `leaf` as the match
     /// (it might be None, indicating that nothing matches).
-    pub branch: HashMap<u8, Self>,
+    ///
+    /// The renderers output arms in the order of this list, which is the order
+    /// in which the characters were added unless [`Self::sort()`] was called.
+    pub branch: Vec<(E, Self)>,
+}
+
+impl Default for TreeNode {
+    fn default() -> Self {
+        Self::empty()
+    }
 }
 
-impl TreeNode {
+impl<E: Element> TreeNode<E> {
+    /// Create a node with no leaf or branches.
+    const fn empty() -> Self {
+        Self { leaf: None, branch: Vec::new() }
+    }
+
     /// Add a match rooted in this node.
     ///
     /// ```rust
@@ -768,26 +3943,435 @@ impl TreeNode {
     /// ```
     pub fn add<'a, K, V>(&mut self, key: K, value: V) -> &mut Self
     where
-        K: IntoIterator<Item = &'a u8>,
+        K: IntoIterator<Item = &'a E>,
         V: Into<String>,
+        E: 'a,
     {
         /// Without this internal function `self` is moved into `fold()` and
         /// thus cannot be returned.
         #[inline]
-        fn internal<'a, K: Iterator<Item = &'a u8>>(
-            node: &mut TreeNode,
+        fn internal<'a, E: Element + 'a, K: Iterator<Item = &'a E>>(
+            node: &mut TreeNode<E>,
             key: K,
             value: String,
         ) {
-            let node =
-                key.fold(node, |node, &c| node.branch.entry(c).or_default());
+            let node = key.fold(node, |node, c| {
+                let i = node
+                    .branch
+                    .iter()
+                    .position(|(byte, _)| byte == c)
+                    .unwrap_or_else(|| {
+                        node.branch.push((c.clone(), TreeNode::empty()));
+                        node.branch.len().checked_sub(1).unwrap()
+                    });
+                &mut node.branch[i].1
+            });
             node.leaf = Some(value);
         }
-        internal(self, key.into_iter(), value.into());
-        self
+        internal(self, key.into_iter(), value.into());
+        self
+    }
+
+    /// Sort the branches of this node and all of its descendants.
+    ///
+    /// [`Order::Insertion`] leaves the branches in the order they were added.
+    ///
+    /// ```rust
+    /// let mut node = matchgen::TreeNode::default();
+    /// node.add(b"b", "1").add(b"a", "2").sort(matchgen::Order::Bytes);
+    /// assert_eq!(node.branch[0].0, b'a');
+    /// ```
+    pub fn sort(&mut self, order: Order) -> &mut Self {
+        /// Sort recursively, tracking the prefix (as bytes) for
+        /// [`Order::Custom`].
+        fn internal<E: Element>(
+            node: &mut TreeNode<E>,
+            order: Order,
+            prefix: &mut Vec<u8>,
+        ) {
+            let mut a = prefix.clone();
+            let mut b = prefix.clone();
+            node.branch.sort_by(|(x, _), (y, _)| {
+                a.truncate(prefix.len());
+                x.push_bytes(&mut a);
+                b.truncate(prefix.len());
+                y.push_bytes(&mut b);
+                order.compare(&a, &b)
+            });
+
+            let len = prefix.len();
+            for (element, child) in &mut node.branch {
+                element.push_bytes(prefix);
+                internal(child, order, prefix);
+                prefix.truncate(len);
+            }
+        }
+
+        if !matches!(order, Order::Insertion) {
+            internal(self, order, &mut Vec::new());
+        }
+        self
+    }
+
+    /// Check that no two branches of this node or its descendants might match
+    /// the same element, since the generated code doesn’t backtrack to try
+    /// later branches. This can only happen with [`Pattern`][crate::Pattern]
+    /// keys.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`io::Error`] if two branches might overlap.
+    pub(crate) fn check_overlap(&self) -> io::Result<()> {
+        let mut branches = self.branch.as_slice();
+        while let Some(((a, child), rest)) = branches.split_first() {
+            if let Some((b, _)) = rest.iter().find(|(b, _)| a.overlaps(b)) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "patterns `{}` and `{}` might match the same element \
+                        after the same prefix, but the generated code doesn’t \
+                        backtrack; see the Pattern docs",
+                        a.fmt_literal(),
+                        b.fmt_literal(),
+                    ),
+                ));
+            }
+            child.check_overlap()?;
+            branches = rest;
+        }
+        Ok(())
+    }
+
+    /// Render the matcher into Rust code that works on an iterator.
+    ///
+    /// The parameters are:
+    ///
+    ///   1. An instance of [`std::io::Write`], like [`std::io::stdout()`], a
+    ///      file, or a [`Vec`].
+    ///   2. The first part of the function definition to generate, e.g.
+    ///      `"pub fn matcher"`.
+    ///   3. The return type (will be wrapped in [`Option`]), e.g.
+    ///      `"&'static str"`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// let mut out = Vec::new();
+    /// matchgen::TreeNode::from_iter([("a".as_bytes(), "1")])
+    ///     .render_iter(&mut out, "fn match_bytes", "u64")
+    ///     .unwrap();
+    ///
+    /// use bstr::ByteVec;
+    /// pretty_assertions::assert_str_eq!(
+    ///     "\
+    /// fn match_bytes<'a, I>(iter: &mut I) -> Option<u64>
+    /// where
+    ///     I: core::iter::Iterator<Item = &'a u8> + core::clone::Clone,
+    /// {
+    ///     let fallback_iter = iter.clone();
+    ///     match iter.next() {
+    ///         Some(b'a') => Some(1),
+    ///         _ => {
+    ///             *iter = fallback_iter;
+    ///             None
+    ///         }
+    ///     }
+    /// }
+    /// ",
+    ///     out.into_string().unwrap(),
+    /// );
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    pub fn render_iter<W, N, R>(
+        &self,
+        writer: &mut W,
+        fn_name: N,
+        return_type: R,
+    ) -> io::Result<()>
+    where
+        W: io::Write,
+        N: fmt::Display,
+        R: fmt::Display,
+    {
+        self.render_iter_with(
+            writer,
+            &TreeMatcher::<E>::new_generic(fn_name, return_type),
+            &Hoisted::default(),
+        )
+    }
+
+    /// Render the matcher into Rust code that works on an iterator, using the
+    /// settings in `matcher`.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    #[allow(clippy::items_after_statements, clippy::too_many_lines)]
+    fn render_iter_with<M: Element, W: io::Write>(
+        &self,
+        writer: &mut W,
+        matcher: &TreeMatcher<M>,
+        hoisted: &Hoisted,
+    ) -> io::Result<()> {
+        let indent = "    "; // Our formatting prevents embedding this.
+
+        if self.branch.is_empty() {
+            // Special handling for when no matches were added.
+            let checks_boundary = self.leaf.is_some()
+                && matcher
+                    .boundary
+                    .forbidden_pattern::<E>(matcher.ignore_ascii_case)
+                    .is_some();
+            write!(
+                writer,
+                "{fn_name}<{lifetime}I>({iter}: &mut I) -> Option<{return_type}>\n\
+                where\n\
+                {indent}I: core::iter::{iterator}<Item = {item}> + core::clone::Clone,\n\
+                {{\n\
+                {indent}",
+                fn_name = matcher.fn_name,
+                lifetime = E::ITEM_LIFETIME,
+                item = E::item(&matcher.element_type),
+                iter = if checks_boundary { "iter" } else { "_iter" },
+                return_type = matcher.return_type,
+                iterator = matcher.iter_trait(),
+                indent = indent,
+            )?;
+            render_child(self, writer, matcher, 1, 0, (None, None))?;
+        } else {
+            write!(
+                writer,
+                "{fn_name}<{lifetime}I>(iter: &mut I) -> Option<{return_type}>\n\
+                where\n\
+                {indent}I: core::iter::{iterator}<Item = {item}> + core::clone::Clone,\n\
+                {{\n",
+                fn_name = matcher.fn_name,
+                lifetime = E::ITEM_LIFETIME,
+                item = E::item(&matcher.element_type),
+                return_type = matcher.return_type,
+                iterator = matcher.iter_trait(),
+                indent = indent,
+            )?;
+            hoisted.render(writer, &matcher.return_type)?;
+
+            // Rewind the iterator if nothing matches, even if the root isn’t
+            // a leaf or the boundary prevents it from matching.
+            write!(
+                writer,
+                "{indent}let fallback_iter = iter.clone();\n{indent}",
+                indent = indent,
+            )?;
+            render_match(
+                self,
+                writer,
+                matcher,
+                1,
+                0,
+                (self.leaf.as_ref(), Some("fallback_iter")),
+                (None, Some("fallback_iter")),
+            )?;
+        }
+        writeln!(writer, "\n}}")?;
+
+        // FIXME: this is recursive, so for long patterns it could blow out the
+        // stack. Transform this to an iterative algorithm.
+
+        /// The value to return if nothing longer matches, and the name of the
+        /// variable containing the iterator to rewind to, if any.
+        type Fallback<'a> = (Option<&'a String>, Option<&'a str>);
+
+        /// Render a node: handle nodes that are a leaf, are a branch, or both.
+        ///
+        /// `depth` is the number of bytes consumed to get to `node`.
+        #[inline]
+        fn render_child<E: Element, M: Element, W: io::Write>(
+            node: &TreeNode<E>,
+            writer: &mut W,
+            matcher: &TreeMatcher<M>,
+            level: usize,
+            depth: usize,
+            fallback: Fallback,
+        ) -> io::Result<()> {
+            // Bytes that prevent the leaf from matching if they follow it.
+            let forbidden = node.leaf.as_ref().and_then(|_| {
+                matcher
+                    .boundary
+                    .forbidden_pattern::<E>(matcher.ignore_ascii_case)
+            });
+
+            if node.branch.is_empty() {
+                if let (Some(leaf), Some(forbidden)) = (&node.leaf, forbidden) {
+                    // Terminal, but make sure the next byte is allowed.
+                    let indent = "    ".repeat(level);
+                    write!(
+                        writer,
+                        "match iter.clone().{next}() {{\n\
+                        {indent}    Some({forbidden}) => ",
+                        next = matcher.iter_next(),
+                        indent = indent,
+                        forbidden = forbidden,
+                    )?;
+                    render_fallback(
+                        writer,
+                        level.checked_add(1).unwrap(),
+                        fallback,
+                    )?;
+                    write!(
+                        writer,
+                        "\n{indent}    _ => Some({leaf}),\n{indent}}}",
+                        indent = indent,
+                        leaf = leaf,
+                    )?;
+                } else {
+                    // Terminal. node.leaf should be Some(_), but might not be.
+                    write!(
+                        writer,
+                        "{}",
+                        leaf_to_str(node.leaf.as_ref().or(fallback.0))
+                    )?;
+                }
+            } else if node.leaf.is_none() {
+                // No patterns end here: branch only.
+                render_match(
+                    node, writer, matcher, level, depth, fallback, fallback,
+                )?;
+            } else {
+                // A pattern ends here. If the boundary might prevent it from
+                // matching, the fallback iterator from before it might be
+                // needed, so it can’t be shadowed.
+                let name = if depth > 0 && forbidden.is_some() {
+                    format!("fallback_iter_{}", depth)
+                } else {
+                    "fallback_iter".to_owned()
+                };
+                let indent = "    ".repeat(level);
+                write!(
+                    writer,
+                    "{{\n\
+                    {indent}    let {name} = iter.clone();\n\
+                    {indent}    ",
+                    indent = indent,
+                    name = name,
+                )?;
+                render_match(
+                    node,
+                    writer,
+                    matcher,
+                    level.checked_add(1).unwrap(),
+                    depth,
+                    (node.leaf.as_ref(), Some(&name)),
+                    fallback,
+                )?;
+                write!(writer, "\n{}}}", indent)?;
+            }
+
+            Ok(())
+        }
+
+        /// Render a match statement to find the next node based on the next
+        /// character, i.e. renders `node.branch`.
+        ///
+        /// `here` is the fallback including the leaf of `node`, and `outer` is
+        /// the fallback to use if the boundary prevents the leaf from matching.
+        #[inline]
+        fn render_match<E: Element, M: Element, W: io::Write>(
+            node: &TreeNode<E>,
+            writer: &mut W,
+            matcher: &TreeMatcher<M>,
+            level: usize,
+            depth: usize,
+            here: Fallback,
+            outer: Fallback,
+        ) -> io::Result<()> {
+            let indent = "    ".repeat(level);
+            let forbidden = matcher
+                .boundary
+                .forbidden_pattern::<E>(matcher.ignore_ascii_case);
+            writeln!(writer, "match iter.{}() {{", matcher.iter_next())?;
+            for (byte, child) in &node.branch {
+                write!(
+                    writer,
+                    "{indent}    Some({byte}) => ",
+                    indent = indent,
+                    byte = byte.fmt_pattern(matcher.ignore_ascii_case),
+                )?;
+                render_child(
+                    child,
+                    writer,
+                    matcher,
+                    level.checked_add(1).unwrap(),
+                    depth.checked_add(1).unwrap(),
+                    // If the byte prevents the leaf from matching, fall back
+                    // to an earlier match.
+                    if node.leaf.is_none()
+                        || matcher
+                            .boundary
+                            .allows(byte, matcher.ignore_ascii_case)
+                    {
+                        here
+                    } else {
+                        outer
+                    },
+                )?;
+                if child.branch.is_empty()
+                    && (child.leaf.is_none() || forbidden.is_none())
+                {
+                    // render_child() wrote a value, not a match block.
+                    writeln!(writer, ",")?;
+                } else {
+                    // render_child() wrote a match block.
+                    writeln!(writer)?;
+                }
+            }
+
+            if let (Some(_), Some(forbidden)) = (&node.leaf, forbidden) {
+                write!(
+                    writer,
+                    "{indent}    Some({forbidden}) => ",
+                    indent = indent,
+                    forbidden = forbidden,
+                )?;
+                render_fallback(writer, level.checked_add(1).unwrap(), outer)?;
+                writeln!(writer)?;
+            }
+
+            write!(writer, "{}    _ => ", indent)?;
+            render_fallback(writer, level.checked_add(1).unwrap(), here)?;
+            write!(writer, "\n{}}}", indent)
+        }
+
+        /// Render a fallback as a `match` arm’s value, including a trailing
+        /// comma if necessary.
+        #[inline]
+        fn render_fallback<W: io::Write>(
+            writer: &mut W,
+            level: usize,
+            fallback: Fallback,
+        ) -> io::Result<()> {
+            let indent = "    ".repeat(level);
+            if let (value, Some(iter)) = fallback {
+                write!(
+                    writer,
+                    "{{\n\
+                    {indent}    *iter = {iter};\n\
+                    {indent}    {value}\n\
+                    {indent}}}",
+                    indent = indent,
+                    iter = iter,
+                    value = leaf_to_str(value),
+                )
+            } else {
+                write!(writer, "{},", leaf_to_str(fallback.0))
+            }
+        }
+
+        Ok(())
     }
 
-    /// Render the matcher into Rust code that works on an iterator.
+    /// Render the matcher into Rust code that works on a slice.
     ///
     /// The parameters are:
     ///
@@ -795,31 +4379,27 @@ impl TreeNode {
     ///      file, or a [`Vec`].
     ///   2. The first part of the function definition to generate, e.g.
     ///      `"pub fn matcher"`.
-    ///   3. The return type (will be wrapped in [`Option`]), e.g.
+    ///   3. The return type (will be wrapped in [`Option`] and a tuple), e.g.
     ///      `"&'static str"`.
+    ///   4. Whether or not to collapse nested single arm `match`s into a flat
+    ///      slice match (See
+    ///      [`TreeMatcher::collapse_nested_single_arms()`]).
     ///
     /// # Example
     ///
     /// ```rust
     /// let mut out = Vec::new();
     /// matchgen::TreeNode::from_iter([("a".as_bytes(), "1")])
-    ///     .render_iter(&mut out, "fn match_bytes", "u64")
+    ///     .render_slice(&mut out, "fn match_bytes", "u64", false)
     ///     .unwrap();
     ///
     /// use bstr::ByteVec;
     /// pretty_assertions::assert_str_eq!(
     ///     "\
-    /// fn match_bytes<'a, I>(iter: &mut I) -> Option<u64>
-    /// where
-    ///     I: core::iter::Iterator<Item = &'a u8> + core::clone::Clone,
-    /// {
-    ///     let fallback_iter = iter.clone();
-    ///     match iter.next() {
-    ///         Some(b'a') => Some(1),
-    ///         _ => {
-    ///             *iter = fallback_iter;
-    ///             None
-    ///         }
+    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
+    ///     match slice {
+    ///         [b'a', ..] => (Some(1), &slice[1..]),
+    ///         _ => (None, slice),
     ///     }
     /// }
     /// ",
@@ -830,341 +4410,981 @@ impl TreeNode {
     /// # Errors
     ///
     /// This can return [`io::Error`] if there is a problem writing to `writer`.
-    #[allow(clippy::items_after_statements, clippy::too_many_lines)]
-    pub fn render_iter<W, N, R>(
+    pub fn render_slice<W, N, R>(
         &self,
         writer: &mut W,
         fn_name: N,
         return_type: R,
+        collapse_nested_single_arms: bool,
     ) -> io::Result<()>
     where
         W: io::Write,
         N: fmt::Display,
         R: fmt::Display,
     {
+        self.render_slice_with(
+            writer,
+            TreeMatcher::<E>::new_generic(fn_name, return_type)
+                .collapse_nested_single_arms(collapse_nested_single_arms),
+            &Hoisted::default(),
+        )
+    }
+
+    /// Check if a function rendered from this root node with
+    /// [`Self::render_slice_with()`] or [`Self::render_slice_suffix()`] will
+    /// need to look at the input slice.
+    fn matches_slice<M: Element>(&self, matcher: &TreeMatcher<M>) -> bool {
+        !self.branch.is_empty()
+            || (self.leaf.is_some()
+                && matcher
+                    .boundary
+                    .forbidden_pattern::<E>(matcher.ignore_ascii_case)
+                    .is_some())
+    }
+
+    /// Render the matcher into Rust code that works on a slice, using the
+    /// settings in `matcher`.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    #[allow(clippy::items_after_statements, clippy::too_many_lines)]
+    fn render_slice_with<M: Element, W: io::Write>(
+        &self,
+        writer: &mut W,
+        matcher: &TreeMatcher<M>,
+        hoisted: &Hoisted,
+    ) -> io::Result<()> {
         let indent = "    "; // Our formatting prevents embedding this.
 
-        if self.branch.is_empty() {
+        writeln!(
+            writer,
+            "{fn_name}({param}) -> (Option<{return_type}>, {remainder_type}) {{",
+            fn_name = matcher.fn_name(),
+            param = matcher
+                .slice_param(matcher.return_slice || self.matches_slice(matcher)),
+            return_type = matcher.return_type,
+            remainder_type = matcher.remainder_type(),
+        )?;
+        hoisted.render(writer, &matcher.return_type)?;
+        matcher.render_str_bytes(writer, self.matches_slice(matcher))?;
+        write!(writer, "{}", indent)?;
+        render_child(self, writer, matcher, 0, "", None)?;
+        writeln!(writer, "}}")?;
+
+        // FIXME: this is recursive, so for long patterns it could blow out the
+        // stack. Transform this to an iterative algorithm.
+
+        /// Render a node: handle nodes that are a leaf, are a branch, or both.
+        #[inline]
+        fn render_child<E: Element, M: Element, W: io::Write>(
+            node: &TreeNode<E>,
+            writer: &mut W,
+            matcher: &TreeMatcher<M>,
+            index: usize,
+            indent: &str,
+            fallback: Option<(&String, usize)>,
+        ) -> io::Result<()> {
+            let leaf = node.leaf.as_ref().map(|leaf| (leaf, index));
+            // Bytes that prevent the leaf from matching if they follow it.
+            let forbidden = leaf.and_then(|_| {
+                matcher
+                    .boundary
+                    .forbidden_pattern::<E>(matcher.ignore_ascii_case)
+            });
+
+            if node.branch.is_empty() && forbidden.is_none() {
+                // Terminal. Write a value, followed by a comma if this is a
+                // nested `match` statement.
+                let comma = if index > 0 { "," } else { "" };
+                // node.leaf should be Some(_), but might not be.
+                writeln!(
+                    writer,
+                    "{}{}",
+                    result_str(matcher, leaf.or(fallback)),
+                    comma
+                )
+            } else {
+                // `&slice[n..]` returns `[]` when `n == slice.len()`, so as
+                // long as we return on `[]` in the previous `match`, this will
+                // never panic.
+                writeln!(
+                    writer,
+                    "match {} {{",
+                    matcher.after_slice_str(index)
+                )?;
+                let indent = format!("{}    ", indent);
+
+                for (byte, child) in &node.branch {
+                    let (bytes, child) = collapse_arm(byte, child, matcher);
+                    let index = index.checked_add(bytes.len()).unwrap();
+                    write!(
+                        writer,
+                        "{indent}    [{bytes}, {after}..] => ",
+                        indent = indent,
+                        bytes = fmt_patterns(&bytes, matcher),
+                        after = matcher
+                            .after_binding(index, child.matches_slice(matcher)),
+                    )?;
+                    render_child(
+                        child,
+                        writer,
+                        matcher,
+                        index,
+                        &indent,
+                        // If the byte prevents the leaf from matching, fall
+                        // back to an earlier match.
+                        if matcher
+                            .boundary
+                            .allows(byte, matcher.ignore_ascii_case)
+                        {
+                            leaf.or(fallback)
+                        } else {
+                            fallback
+                        },
+                    )?;
+                }
+
+                if let Some(forbidden) = forbidden {
+                    writeln!(
+                        writer,
+                        "{indent}    [{forbidden}, ..] => {result},",
+                        indent = indent,
+                        forbidden = forbidden,
+                        result = result_str(matcher, fallback),
+                    )?;
+                }
+
+                // This catches the `[]` case.
+                writeln!(
+                    writer,
+                    "{indent}    _ => {default},\n\
+                    {indent}}}",
+                    indent = indent,
+                    default = result_str(matcher, leaf.or(fallback)),
+                )
+            }
+        }
+
+        /// Get the return value for a match.
+        #[must_use]
+        #[inline]
+        fn result_str<M: Element>(
+            matcher: &TreeMatcher<M>,
+            fallback: Option<(&String, usize)>,
+        ) -> String {
+            if let Some((value, index)) = fallback {
+                format!("(Some({}), {})", value, matcher.rest_str(index))
+            } else {
+                format!("(None, {})", matcher.rest_str(0))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render the matcher into Rust code that matches the end of a slice,
+    /// using the settings in `matcher`.
+    ///
+    /// The tree must have been built from reversed keys.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    #[allow(clippy::items_after_statements)]
+    fn render_slice_suffix<M: Element, W: io::Write>(
+        &self,
+        writer: &mut W,
+        matcher: &TreeMatcher<M>,
+        hoisted: &Hoisted,
+    ) -> io::Result<()> {
+        let indent = "    "; // Our formatting prevents embedding this.
+
+        writeln!(
+            writer,
+            "{fn_name}({param}) -> (Option<{return_type}>, {remainder_type}) {{",
+            fn_name = matcher.fn_name(),
+            param = matcher.slice_param(true),
+            return_type = matcher.return_type,
+            remainder_type = matcher.remainder_type(),
+        )?;
+        hoisted.render(writer, &matcher.return_type)?;
+        matcher.render_str_bytes(writer, self.matches_slice(matcher))?;
+        write!(writer, "{}", indent)?;
+        render_child(self, writer, matcher, 0, "", None)?;
+        writeln!(writer, "}}")?;
+
+        // FIXME: this is recursive, so for long patterns it could blow out the
+        // stack. Transform this to an iterative algorithm.
+
+        /// Render a node: handle nodes that are a leaf, are a branch, or both.
+        ///
+        /// `index` is the number of bytes matched from the end of the slice.
+        /// The part of the slice before them is bound to `before_str(index)`.
+        #[inline]
+        fn render_child<E: Element, M: Element, W: io::Write>(
+            node: &TreeNode<E>,
+            writer: &mut W,
+            matcher: &TreeMatcher<M>,
+            index: usize,
+            indent: &str,
+            fallback: Option<(&String, usize)>,
+        ) -> io::Result<()> {
+            let leaf = node.leaf.as_ref().map(|leaf| (leaf, index));
+            // Bytes that prevent the leaf from matching if they precede it.
+            let forbidden = leaf.and_then(|_| {
+                matcher
+                    .boundary
+                    .forbidden_pattern::<E>(matcher.ignore_ascii_case)
+            });
+
+            if node.branch.is_empty() && forbidden.is_none() {
+                // Terminal. Write a value, followed by a comma if this is a
+                // nested `match` statement.
+                let comma = if index > 0 { "," } else { "" };
+                writeln!(
+                    writer,
+                    "{}{}",
+                    result_str(matcher, leaf.or(fallback)),
+                    comma
+                )
+            } else {
+                writeln!(writer, "match {} {{", before_str(index))?;
+                let indent = format!("{}    ", indent);
+
+                for (byte, child) in &node.branch {
+                    let (mut bytes, child) = collapse_arm(byte, child, matcher);
+                    let index = index.checked_add(bytes.len()).unwrap();
+                    bytes.reverse();
+                    write!(
+                        writer,
+                        "{indent}    [{before} @ .., {bytes}] => ",
+                        indent = indent,
+                        before = before_str(index),
+                        bytes = fmt_patterns(&bytes, matcher),
+                    )?;
+                    render_child(
+                        child,
+                        writer,
+                        matcher,
+                        index,
+                        &indent,
+                        // If the byte prevents the leaf from matching, fall
+                        // back to an earlier match.
+                        if matcher
+                            .boundary
+                            .allows(byte, matcher.ignore_ascii_case)
+                        {
+                            leaf.or(fallback)
+                        } else {
+                            fallback
+                        },
+                    )?;
+                }
+
+                if let Some(forbidden) = forbidden {
+                    writeln!(
+                        writer,
+                        "{indent}    [.., {forbidden}] => {result},",
+                        indent = indent,
+                        forbidden = forbidden,
+                        result = result_str(matcher, fallback),
+                    )?;
+                }
+
+                // This catches the `[]` case.
+                writeln!(
+                    writer,
+                    "{indent}    _ => {result},\n\
+                    {indent}}}",
+                    indent = indent,
+                    result = result_str(matcher, leaf.or(fallback)),
+                )
+            }
+        }
+
+        /// Get the return value for a match.
+        #[must_use]
+        #[inline]
+        fn result_str<M: Element>(
+            matcher: &TreeMatcher<M>,
+            fallback: Option<(&String, usize)>,
+        ) -> String {
+            if let Some((value, index)) = fallback {
+                format!("(Some({}), {})", value, matcher.before_rest_str(index))
+            } else {
+                format!("(None, {})", matcher.before_rest_str(0))
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Render the matcher into Rust code that checks if an entire slice is a
+    /// key, using the settings in `matcher`.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    #[allow(clippy::items_after_statements)]
+    fn render_slice_exact<M: Element, W: io::Write>(
+        &self,
+        writer: &mut W,
+        matcher: &TreeMatcher<M>,
+        hoisted: &Hoisted,
+    ) -> io::Result<()> {
+        let indent = "    "; // Our formatting prevents embedding this.
+
+        if self.branch.is_empty() && self.leaf.is_none() {
             // Special handling for when no matches were added.
-            write!(
+            return write!(
                 writer,
-                "{fn_name}<'a, I>(_iter: &mut I) -> Option<{return_type}>\n\
-                where\n\
-                {indent}I: core::iter::Iterator<Item = &'a u8> + core::clone::Clone,\n\
-                {{\n\
-                {indent}",
-                fn_name = fn_name,
-                return_type = return_type,
+                "{fn_name}({param}) -> Option<{return_type}> {{\n\
+                {indent}None\n\
+                }}\n",
+                fn_name = matcher.fn_name(),
+                param = matcher.slice_param(false),
+                return_type = matcher.return_type,
                 indent = indent,
-            )?;
-            render_child(self, writer, 0, None)?;
-            writeln!(writer, "\n}}")?;
-        } else {
-            write!(
+            );
+        }
+
+        writeln!(
+            writer,
+            "{fn_name}({param}) -> Option<{return_type}> {{",
+            fn_name = matcher.fn_name(),
+            param = matcher.slice_param(true),
+            return_type = matcher.return_type,
+        )?;
+        hoisted.render(writer, &matcher.return_type)?;
+        matcher.render_str_bytes(writer, true)?;
+        write!(writer, "{}", indent)?;
+        render_match(self, writer, matcher, 0, "")?;
+        writeln!(writer, "}}")?;
+
+        // FIXME: this is recursive, so for long patterns it could blow out the
+        // stack. Transform this to an iterative algorithm.
+
+        /// Render a `match` on the slice starting at `index`.
+        #[inline]
+        fn render_match<E: Element, M: Element, W: io::Write>(
+            node: &TreeNode<E>,
+            writer: &mut W,
+            matcher: &TreeMatcher<M>,
+            index: usize,
+            indent: &str,
+        ) -> io::Result<()> {
+            writeln!(writer, "match {} {{", matcher.after_slice_str(index))?;
+            let indent = format!("{}    ", indent);
+
+            if let Some(leaf) = &node.leaf {
+                writeln!(writer, "{}    [] => Some({}),", indent, leaf)?;
+            }
+
+            for (byte, child) in &node.branch {
+                let (bytes, child) = collapse_arm(byte, child, matcher);
+                if child.branch.is_empty() {
+                    // Terminal. The rest of the slice must match exactly.
+                    writeln!(
+                        writer,
+                        "{indent}    [{bytes}] => {value},",
+                        indent = indent,
+                        bytes = fmt_patterns(&bytes, matcher),
+                        value = leaf_to_str(child.leaf.as_ref()),
+                    )?;
+                } else {
+                    let index = index.checked_add(bytes.len()).unwrap();
+                    write!(
+                        writer,
+                        "{indent}    [{bytes}, {after}..] => ",
+                        indent = indent,
+                        bytes = fmt_patterns(&bytes, matcher),
+                        after = matcher.after_binding(index, true),
+                    )?;
+                    render_match(child, writer, matcher, index, &indent)?;
+                }
+            }
+
+            writeln!(
+                writer,
+                "{indent}    _ => None,\n{indent}}}",
+                indent = indent
+            )
+        }
+
+        Ok(())
+    }
+
+    /// Render the matcher into Rust code that checks if an entire iterator is
+    /// a key, using the settings in `matcher`.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    #[allow(clippy::items_after_statements)]
+    fn render_iter_exact<M: Element, W: io::Write>(
+        &self,
+        writer: &mut W,
+        matcher: &TreeMatcher<M>,
+        hoisted: &Hoisted,
+    ) -> io::Result<()> {
+        let indent = "    "; // Our formatting prevents embedding this.
+
+        if self.branch.is_empty() && self.leaf.is_none() {
+            // Special handling for when no matches were added.
+            return write!(
                 writer,
-                "{fn_name}<'a, I>(iter: &mut I) -> Option<{return_type}>\n\
+                "{fn_name}<{lifetime}I>(_iter: I) -> Option<{return_type}>\n\
                 where\n\
-                {indent}I: core::iter::Iterator<Item = &'a u8> + core::clone::Clone,\n",
-                fn_name = fn_name,
-                return_type = return_type,
+                {indent}I: core::iter::IntoIterator<Item = {item}>,\n\
+                {{\n\
+                {indent}None\n\
+                }}\n",
+                fn_name = matcher.fn_name,
+                lifetime = E::ITEM_LIFETIME,
+                item = E::item(&matcher.element_type),
+                return_type = matcher.return_type,
                 indent = indent,
-            )?;
-            render_child(self, writer, 0, None)?;
-            writeln!(writer)?;
+            );
         }
 
+        write!(
+            writer,
+            "{fn_name}<{lifetime}I>(iter: I) -> Option<{return_type}>\n\
+            where\n\
+            {indent}I: core::iter::IntoIterator<Item = {item}>,\n\
+            {{\n",
+            fn_name = matcher.fn_name,
+            lifetime = E::ITEM_LIFETIME,
+            item = E::item(&matcher.element_type),
+            return_type = matcher.return_type,
+            indent = indent,
+        )?;
+        hoisted.render(writer, &matcher.return_type)?;
+        write!(
+            writer,
+            "{indent}let mut iter = iter.into_iter();\n{indent}",
+            indent = indent,
+        )?;
+        render_match(self, writer, matcher, 1)?;
+        writeln!(writer, "\n}}")?;
+
         // FIXME: this is recursive, so for long patterns it could blow out the
         // stack. Transform this to an iterative algorithm.
 
-        /// Render a node: handle nodes that are a leaf, are a branch, or both.
+        /// Render a `match` on the next item in the iterator.
         #[inline]
-        fn render_child<W: io::Write>(
-            node: &TreeNode,
+        fn render_match<E: Element, M: Element, W: io::Write>(
+            node: &TreeNode<E>,
             writer: &mut W,
+            matcher: &TreeMatcher<M>,
             level: usize,
-            fallback: Option<&String>,
         ) -> io::Result<()> {
-            if node.branch.is_empty() {
-                // Terminal. node.leaf should be Some(_), but might not be.
-                write!(
-                    writer,
-                    "{}",
-                    leaf_to_str(node.leaf.as_ref().or(fallback))
-                )?;
-            } else if node.leaf.is_none() && level > 0 {
-                // No patterns end here: branch only. (The level check creates
-                // a default root pattern of `[] => None` so that we rewind the
-                // iter when nothing matches.)
-                render_match(node, writer, level, fallback)?;
-            } else {
-                // A pattern ends here.
-                let indent = "    ".repeat(level);
+            let indent = "    ".repeat(level);
+            writeln!(writer, "match iter.next() {{")?;
+
+            if let Some(leaf) = &node.leaf {
+                writeln!(writer, "{}    None => Some({}),", indent, leaf)?;
+            }
+
+            for (byte, child) in &node.branch {
                 write!(
                     writer,
-                    "{{\n\
-                    {indent}    let fallback_iter = iter.clone();\n\
-                    {indent}    ",
+                    "{indent}    Some({byte}) => ",
                     indent = indent,
+                    byte = byte.fmt_pattern(matcher.ignore_ascii_case),
                 )?;
                 render_match(
-                    node,
+                    child,
                     writer,
+                    matcher,
                     level.checked_add(1).unwrap(),
-                    node.leaf.as_ref(),
                 )?;
-                write!(writer, "\n{}}}", indent)?;
+                writeln!(writer)?;
             }
 
-            Ok(())
+            write!(
+                writer,
+                "{indent}    _ => None,\n{indent}}}",
+                indent = indent
+            )
         }
 
-        /// Render a match statement to find the next node based on the next
-        /// character, i.e. renders `node.branch`.
+        Ok(())
+    }
+
+    /// Render the matcher into Rust code that calls a visitor for every key
+    /// that matches a slice, using the settings in `matcher`.
+    ///
+    /// With [`Anchor::Suffix`], the tree must have been built from reversed
+    /// keys.
+    ///
+    /// # Errors
+    ///
+    /// This can return [`io::Error`] if there is a problem writing to `writer`.
+    #[allow(clippy::items_after_statements, clippy::too_many_lines)]
+    fn render_slice_all<M: Element, W: io::Write>(
+        &self,
+        writer: &mut W,
+        matcher: &TreeMatcher<M>,
+        hoisted: &Hoisted,
+    ) -> io::Result<()> {
+        let indent = "    "; // Our formatting prevents embedding this.
+        let empty = self.branch.is_empty() && self.leaf.is_none();
+
+        write!(
+            writer,
+            "{fn_name}<F>({param}, {visit}: F)\n\
+            where\n\
+            {indent}F: FnMut({return_type}, usize),\n\
+            {{\n",
+            fn_name = matcher.fn_name,
+            param = matcher.slice_param(!empty),
+            visit = if empty { "_visit" } else { "mut visit" },
+            return_type = matcher.return_type,
+            indent = indent,
+        )?;
+        hoisted.render(writer, &matcher.return_type)?;
+        matcher.render_str_bytes(writer, !empty)?;
+        render_node(self, writer, matcher, 0, indent)?;
+        writeln!(writer, "}}")?;
+
+        // FIXME: this is recursive, so for long patterns it could blow out the
+        // stack. Transform this to an iterative algorithm.
+
+        /// Render statements to visit the leaf of `node`, if any, and then the
+        /// leaves of its descendants.
+        ///
+        /// `index` is the number of bytes matched.
         #[inline]
-        fn render_match<W: io::Write>(
-            node: &TreeNode,
+        fn render_node<E: Element, M: Element, W: io::Write>(
+            node: &TreeNode<E>,
             writer: &mut W,
-            level: usize,
-            fallback: Option<&String>,
+            matcher: &TreeMatcher<M>,
+            index: usize,
+            indent: &str,
         ) -> io::Result<()> {
-            let indent = "    ".repeat(level);
-            writeln!(writer, "match iter.next() {{")?;
-            for (&byte, child) in &node.branch {
-                write!(
-                    writer,
-                    "{indent}    Some({byte}) => ",
-                    indent = indent,
-                    byte = crate::fmt_byte(byte),
-                )?;
-                render_child(
-                    child,
-                    writer,
-                    level.checked_add(1).unwrap(),
-                    fallback,
-                )?;
-                if child.branch.is_empty() {
-                    // render_child() wrote a value, not a match block.
-                    writeln!(writer, ",")?;
+            if let Some(leaf) = &node.leaf {
+                if let Some(check) = boundary_check::<E, M>(matcher, index) {
+                    writeln!(
+                        writer,
+                        "{indent}if {check} {{\n\
+                        {indent}    visit({leaf}, {index});\n\
+                        {indent}}}",
+                        indent = indent,
+                        check = check,
+                        leaf = leaf,
+                        index = index,
+                    )?;
                 } else {
-                    // render_child() wrote a match block.
-                    writeln!(writer)?;
+                    writeln!(writer, "{}visit({}, {});", indent, leaf, index)?;
                 }
             }
 
-            let fallback = leaf_to_str(fallback);
-            write!(
+            if node.branch.is_empty() {
+                return Ok(());
+            }
+
+            writeln!(
                 writer,
-                "{indent}    _ => {{\n\
-                {indent}        *iter = fallback_iter;\n\
-                {indent}        {fallback}\n\
-                {indent}    }}\n\
-                {indent}}}",
-                indent = indent,
-                fallback = fallback
+                "{}match {} {{",
+                indent,
+                subject_str(matcher, index)
             )?;
+            for (byte, child) in &node.branch {
+                let (mut bytes, child) = collapse_arm(byte, child, matcher);
+                let index = index.checked_add(bytes.len()).unwrap();
+                if matcher.anchor == Anchor::Suffix {
+                    bytes.reverse();
+                    write!(
+                        writer,
+                        "{}    [{} @ .., {}]",
+                        indent,
+                        before_str(index),
+                        fmt_patterns(&bytes, matcher),
+                    )?;
+                } else {
+                    write!(
+                        writer,
+                        "{}    [{}, ..]",
+                        indent,
+                        fmt_patterns(&bytes, matcher),
+                    )?;
+                }
 
-            Ok(())
+                if let (Some(leaf), true) =
+                    (&child.leaf, child.branch.is_empty())
+                {
+                    // Terminal. Check the boundary in a guard.
+                    writeln!(
+                        writer,
+                        "{guard} => visit({leaf}, {index}),",
+                        guard = boundary_check::<E, M>(matcher, index)
+                            .map_or_else(String::new, |check| {
+                                format!(" if {}", check)
+                            }),
+                        leaf = leaf,
+                        index = index,
+                    )?;
+                } else {
+                    writeln!(writer, " => {{")?;
+                    render_node(
+                        child,
+                        writer,
+                        matcher,
+                        index,
+                        &format!("{}        ", indent),
+                    )?;
+                    writeln!(writer, "{}    }}", indent)?;
+                }
+            }
+            writeln!(
+                writer,
+                "{indent}    _ => {{}}\n{indent}}}",
+                indent = indent
+            )
+        }
+
+        /// Get the name of the part of the slice that hasn’t been matched
+        /// after matching `index` bytes.
+        #[inline]
+        fn subject_str<M: Element>(
+            matcher: &TreeMatcher<M>,
+            index: usize,
+        ) -> String {
+            if matcher.anchor == Anchor::Suffix {
+                before_str(index)
+            } else {
+                slice_str(index)
+            }
         }
 
-        /// Format `Option<"Rust code">` as Rust code.
+        /// Get an expression that checks that the boundary allows a key of
+        /// length `index` to match, if necessary.
         #[inline]
-        fn leaf_to_str(leaf: Option<&String>) -> String {
-            leaf.map(|leaf| format!("Some({})", leaf))
-                .unwrap_or_else(|| "None".to_owned())
+        fn boundary_check<E: Element, M: Element>(
+            matcher: &TreeMatcher<M>,
+            index: usize,
+        ) -> Option<String> {
+            matcher
+                .boundary
+                .forbidden_pattern::<E>(matcher.ignore_ascii_case)
+                .map(|forbidden| {
+                    if matcher.anchor == Anchor::Suffix {
+                        format!(
+                            "!matches!({}, [.., {}])",
+                            before_str(index),
+                            forbidden
+                        )
+                    } else {
+                        format!(
+                            "!matches!({}, [{}, ..])",
+                            slice_str(index),
+                            forbidden
+                        )
+                    }
+                })
         }
 
         Ok(())
     }
 
-    /// Render the matcher into Rust code that works on a slice.
-    ///
-    /// The parameters are:
-    ///
-    ///   1. An instance of [`std::io::Write`], like [`std::io::stdout()`], a
-    ///      file, or a [`Vec`].
-    ///   2. The first part of the function definition to generate, e.g.
-    ///      `"pub fn matcher"`.
-    ///   3. The return type (will be wrapped in [`Option`] and a tuple), e.g.
-    ///      `"&'static str"`.
-    ///   4. Whether or not to collapse nested single arm `match`s into a flat
-    ///      slice match (See
-    ///      [`TreeMatcher::collapse_nested_single_arms()`]).
-    ///
-    /// # Example
+    /// Render the matcher into Rust code that calls a visitor for every key
+    /// that matches an iterator, using the settings in `matcher`.
     ///
-    /// ```rust
-    /// let mut out = Vec::new();
-    /// matchgen::TreeNode::from_iter([("a".as_bytes(), "1")])
-    ///     .render_slice(&mut out, "fn match_bytes", "u64", false)
-    ///     .unwrap();
-    ///
-    /// use bstr::ByteVec;
-    /// pretty_assertions::assert_str_eq!(
-    ///     "\
-    /// fn match_bytes(slice: &[u8]) -> (Option<u64>, &[u8]) {
-    ///     match slice {
-    ///         [b'a', ..] => (Some(1), &slice[1..]),
-    ///         _ => (None, slice),
-    ///     }
-    /// }
-    /// ",
-    ///     out.into_string().unwrap(),
-    /// );
-    /// ```
+    /// With [`Anchor::Suffix`], the tree must have been built from reversed
+    /// keys.
     ///
     /// # Errors
     ///
     /// This can return [`io::Error`] if there is a problem writing to `writer`.
-    #[allow(clippy::items_after_statements)]
-    pub fn render_slice<W, N, R>(
+    #[allow(clippy::items_after_statements, clippy::too_many_lines)]
+    fn render_iter_all<M: Element, W: io::Write>(
         &self,
         writer: &mut W,
-        fn_name: N,
-        return_type: R,
-        collapse_nested_single_arms: bool,
-    ) -> io::Result<()>
-    where
-        W: io::Write,
-        N: fmt::Display,
-        R: fmt::Display,
-    {
+        matcher: &TreeMatcher<M>,
+        hoisted: &Hoisted,
+    ) -> io::Result<()> {
         let indent = "    "; // Our formatting prevents embedding this.
+        let forbidden = matcher
+            .boundary
+            .forbidden_pattern::<E>(matcher.ignore_ascii_case);
+        let checks_boundary = self.leaf.is_some() && forbidden.is_some();
+        let uses_iter = !self.branch.is_empty() || checks_boundary;
 
+        // Bounds on the iterator produced by `into_iter()`.
+        let mut bounds = Vec::new();
+        if matcher.anchor == Anchor::Suffix {
+            bounds.push("core::iter::DoubleEndedIterator");
+        }
+        if forbidden.is_some() {
+            // Needed to look at the next byte without consuming it.
+            bounds.push("core::clone::Clone");
+        }
+
+        write!(
+            writer,
+            "{fn_name}<{lifetime}I, F>({iter}: I, {visit}: F)\n\
+            where\n\
+            {indent}I: core::iter::IntoIterator<Item = {item}>,\n",
+            fn_name = matcher.fn_name,
+            lifetime = E::ITEM_LIFETIME,
+            item = E::item(&matcher.element_type),
+            iter = if uses_iter { "iter" } else { "_iter" },
+            visit = if self.leaf.is_none() && self.branch.is_empty() {
+                "_visit"
+            } else {
+                "mut visit"
+            },
+            indent = indent,
+        )?;
+        if !bounds.is_empty() {
+            writeln!(writer, "{}I::IntoIter: {},", indent, bounds.join(" + "))?;
+        }
         write!(
             writer,
-            "{fn_name}(slice: &[u8]) -> (Option<{return_type}>, &[u8]) {{\n\
-            {indent}",
-            fn_name = fn_name,
-            return_type = return_type,
+            "{indent}F: FnMut({return_type}, usize),\n\
+            {{\n",
+            return_type = matcher.return_type,
             indent = indent,
         )?;
-        render_child(self, writer, 0, "", None, collapse_nested_single_arms)?;
+        hoisted.render(writer, &matcher.return_type)?;
+
+        if !self.branch.is_empty() {
+            writeln!(writer, "{}let mut iter = iter.into_iter();", indent)?;
+        } else if checks_boundary {
+            writeln!(writer, "{}let iter = iter.into_iter();", indent)?;
+        }
+        render_node(self, writer, matcher, 0, indent)?;
         writeln!(writer, "}}")?;
 
         // FIXME: this is recursive, so for long patterns it could blow out the
         // stack. Transform this to an iterative algorithm.
 
-        /// Render a node: handle nodes that are a leaf, are a branch, or both.
+        /// Render statements to visit the leaf of `node`, if any, and then the
+        /// leaves of its descendants.
+        ///
+        /// `depth` is the number of bytes consumed.
         #[inline]
-        fn render_child<W: io::Write>(
-            node: &TreeNode,
+        fn render_node<E: Element, M: Element, W: io::Write>(
+            node: &TreeNode<E>,
             writer: &mut W,
-            index: usize,
+            matcher: &TreeMatcher<M>,
+            depth: usize,
             indent: &str,
-            fallback: Option<(&String, usize)>,
-            collapse_nested_single_arms: bool,
         ) -> io::Result<()> {
-            /// Render a subslice operation.
-            #[must_use]
-            #[inline]
-            fn slice_str(i: usize) -> String {
-                if i > 0 {
-                    format!("&slice[{}..]", i)
-                } else {
-                    "slice".to_owned()
-                }
-            }
-
-            if node.branch.is_empty() {
-                // Terminal. Write a value, followed by a comma if this is a
-                // nested `match` statement.
-                let comma = if index > 0 { "," } else { "" };
-                // node.leaf should be Some(_), but might not be.
-                if let Some(leaf) = &node.leaf {
+            if let Some(leaf) = &node.leaf {
+                if let Some(check) = boundary_check::<E, M>(matcher) {
                     writeln!(
                         writer,
-                        "(Some({leaf}), {slice}){comma}",
+                        "{indent}if {check} {{\n\
+                        {indent}    visit({leaf}, {depth});\n\
+                        {indent}}}",
+                        indent = indent,
+                        check = check,
                         leaf = leaf,
-                        slice = slice_str(index),
-                        comma = comma
-                    )
-                } else if let Some((value, fallback)) = fallback {
-                    writeln!(
-                        writer,
-                        "(Some({value}), {slice}){comma}",
-                        value = value,
-                        slice = slice_str(fallback),
-                        comma = comma
-                    )
+                        depth = depth,
+                    )?;
                 } else {
-                    writeln!(writer, "(None, slice){comma}", comma = comma)
+                    writeln!(writer, "{}visit({}, {});", indent, leaf, depth)?;
                 }
-            } else {
-                // `&slice[n..]` returns `[]` when `n == slice.len()`, so as
-                // long as we return on `[]` in the previous `match`, this will
-                // never panic.
-                writeln!(writer, "match {} {{", slice_str(index))?;
+            }
 
-                let fallback =
-                    node.leaf.as_ref().map(|leaf| (leaf, index)).or(fallback);
-                let indent = format!("{}    ", indent);
+            if node.branch.is_empty() {
+                return Ok(());
+            }
 
-                for (&byte, mut child) in &node.branch {
-                    let mut bytes = vec![byte];
-                    while collapse_nested_single_arms
-                        && child.branch.len() == 1
-                        && child.leaf.is_none()
-                    {
-                        // Destructuring assignments are unstable in 1.56.1
-                        let tuple = child.branch.iter().next().unwrap();
-                        bytes.push(*tuple.0);
-                        child = tuple.1;
-                    }
-                    write!(
+            writeln!(
+                writer,
+                "{}match iter.{}() {{",
+                indent,
+                matcher.iter_next()
+            )?;
+            for (byte, child) in &node.branch {
+                let depth = depth.checked_add(1).unwrap();
+                write!(
+                    writer,
+                    "{}    Some({})",
+                    indent,
+                    byte.fmt_pattern(matcher.ignore_ascii_case),
+                )?;
+
+                if let (Some(leaf), true) =
+                    (&child.leaf, child.branch.is_empty())
+                {
+                    // Terminal. Check the boundary in a guard.
+                    writeln!(
                         writer,
-                        "{indent}    [{bytes}..] => ",
-                        indent = indent,
-                        bytes = bytes
-                            .iter()
-                            .map(|&b| crate::fmt_byte(b) + ", ")
-                            .collect::<String>(),
+                        "{guard} => visit({leaf}, {depth}),",
+                        guard = boundary_check::<E, M>(matcher)
+                            .map_or_else(String::new, |check| {
+                                format!(" if {}", check)
+                            }),
+                        leaf = leaf,
+                        depth = depth,
                     )?;
-                    render_child(
+                } else {
+                    writeln!(writer, " => {{")?;
+                    render_node(
                         child,
                         writer,
-                        index.checked_add(bytes.len()).unwrap(),
-                        &indent,
-                        fallback,
-                        collapse_nested_single_arms,
+                        matcher,
+                        depth,
+                        &format!("{}        ", indent),
                     )?;
+                    writeln!(writer, "{}    }}", indent)?;
                 }
-
-                let default = if let Some((value, index)) = fallback {
-                    format!("(Some({}), {})", value, slice_str(index))
-                } else {
-                    "(None, slice)".to_owned()
-                };
-
-                // This catches the `[]` case.
-                writeln!(
-                    writer,
-                    "{indent}    _ => {default},\n\
-                    {indent}}}",
-                    indent = indent,
-                    default = default,
-                )
             }
+            writeln!(
+                writer,
+                "{indent}    _ => {{}}\n{indent}}}",
+                indent = indent
+            )
+        }
+
+        /// Get an expression that checks that the boundary allows a key to
+        /// match at the current position, if necessary.
+        #[inline]
+        fn boundary_check<E: Element, M: Element>(
+            matcher: &TreeMatcher<M>,
+        ) -> Option<String> {
+            matcher
+                .boundary
+                .forbidden_pattern::<E>(matcher.ignore_ascii_case)
+                .map(|forbidden| {
+                    format!(
+                        "!matches!(iter.clone().{}(), Some({}))",
+                        matcher.iter_next(),
+                        forbidden
+                    )
+                })
         }
 
         Ok(())
     }
 }
 
-impl<'a, K, V> FromIterator<(K, V)> for TreeNode
+/// Render a subslice operation.
+#[must_use]
+#[inline]
+fn slice_str(i: usize) -> String {
+    if i > 0 {
+        format!("&slice[{}..]", i)
+    } else {
+        "slice".to_owned()
+    }
+}
+
+/// Get the name of the part of the slice after the first `index` bytes.
+#[must_use]
+#[inline]
+fn after_str(index: usize) -> String {
+    if index > 0 {
+        format!("after_{}", index)
+    } else {
+        "slice".to_owned()
+    }
+}
+
+/// Get the name of the part of the slice before the last `index` bytes.
+#[must_use]
+#[inline]
+fn before_str(index: usize) -> String {
+    if index > 0 {
+        format!("before_{}", index)
+    } else {
+        "slice".to_owned()
+    }
+}
+
+/// Collect the values of `node` and its descendants, once for each time they
+/// appear in the generated code, or twice if it’s more than once.
+fn leaf_values<'a, E: Element>(
+    node: &'a TreeNode<E>,
+    values: &mut Vec<&'a str>,
+) {
+    if let Some(value) = &node.leaf {
+        values.push(value);
+        if !node.branch.is_empty() {
+            // The value is the fallback for nested `match` statements.
+            values.push(value);
+        }
+    }
+    for (_, child) in &node.branch {
+        leaf_values(child, values);
+    }
+}
+
+/// Replace the values of `node` and its descendants that are in `hoisted` with
+/// the names of their constants.
+fn replace_leaves<E: Element>(node: &mut TreeNode<E>, hoisted: &Hoisted) {
+    if let Some(value) = &mut node.leaf {
+        *value = hoisted.replace(value).into_owned();
+    }
+    for (_, child) in &mut node.branch {
+        replace_leaves(child, hoisted);
+    }
+}
+
+/// Format `Option<"Rust code">` as Rust code.
+#[inline]
+fn leaf_to_str(leaf: Option<&String>) -> String {
+    leaf.map(|leaf| format!("Some({})", leaf))
+        .unwrap_or_else(|| "None".to_owned())
+}
+
+/// Format elements as comma-separated patterns, e.g. `b'a', b'b'`.
+fn fmt_patterns<E: Element, M: Element>(
+    elements: &[E],
+    matcher: &TreeMatcher<M>,
+) -> String {
+    elements
+        .iter()
+        .map(|e| e.fmt_pattern(matcher.ignore_ascii_case))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Get the bytes to match for an arm and the node it leads to.
+///
+/// If [`TreeMatcher::collapse_nested_single_arms`] is set, this follows chains
+/// of nodes that have a single branch and no leaf so that they can be matched
+/// with a single slice pattern.
+fn collapse_arm<'a, E: Element, M: Element>(
+    byte: &E,
+    mut child: &'a TreeNode<E>,
+    matcher: &TreeMatcher<M>,
+) -> (Vec<E>, &'a TreeNode<E>) {
+    let mut bytes = vec![byte.clone()];
+    while matcher.collapse_nested_single_arms
+        && child.branch.len() == 1
+        && child.leaf.is_none()
+    {
+        // Destructuring assignments are unstable in 1.56.1
+        let tuple = &child.branch[0];
+        bytes.push(tuple.0.clone());
+        child = &tuple.1;
+    }
+    (bytes, child)
+}
+
+impl<'a, E, K, V> FromIterator<(K, V)> for TreeNode<E>
 where
-    K: IntoIterator<Item = &'a u8>,
+    E: Element + 'a,
+    K: IntoIterator<Item = &'a E>,
     V: Into<String>,
 {
     fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
-        let mut root = Self::default();
+        let mut root = Self::empty();
         root.extend(iter);
         root
     }
 }
 
-impl<'a, K, V> Extend<(K, V)> for TreeNode
+impl<'a, E, K, V> Extend<(K, V)> for TreeNode<E>
 where
-    K: IntoIterator<Item = &'a u8>,
+    E: Element + 'a,
+    K: IntoIterator<Item = &'a E>,
     V: Into<String>,
 {
     fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
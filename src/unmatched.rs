@@ -0,0 +1,34 @@
+//! Code for [`Unmatched`].
+
+/// What a generated tokenizer does with bytes that don’t start a match.
+///
+/// See [`TreeMatcher::tokenizer_struct()`][crate::TreeMatcher::tokenizer_struct]
+/// and [`FlatMatcher::tokenizer_struct()`][crate::FlatMatcher::tokenizer_struct].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Unmatched {
+    /// Skip bytes that don’t start a match. This is the default.
+    Skip,
+
+    /// Yield each run of bytes that don’t start a match as a single token.
+    ///
+    /// The `String` is an expression of the return type, e.g.
+    /// `"Token::Other"`.
+    Token(String),
+
+    /// Call a function when no key matches.
+    ///
+    /// The `String` is the path to a function that accepts the remaining input
+    /// as `&[u8]` and returns a token and its length, i.e.
+    /// `({return_type}, usize)`. The tokenizer always advances by at least one
+    /// byte, even if the function returns a length of 0.
+    ///
+    /// This is useful for tokens that can’t be listed as keys, such as
+    /// identifiers and numbers.
+    Fallback(String),
+}
+
+impl Default for Unmatched {
+    fn default() -> Self {
+        Self::Skip
+    }
+}